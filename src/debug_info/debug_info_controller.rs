@@ -1,5 +1,6 @@
-use piston::input::{Button, Key, GenericEvent};
+use piston::input::GenericEvent;
 use debug_info::DebugInfo;
+use input::Control;
 
 /// Implements logic related to collecting and storing
 /// debugging and troubleshooting information.
@@ -61,8 +62,8 @@ impl DebugInfoController {
     }
 
     /// Updates the state of the DebugInfoController based on the provided
-    /// event information.
-    pub fn update<E>(&mut self, event: &E)
+    /// event information and the controls that it triggered.
+    pub fn update<E>(&mut self, event: &E, controls: &[Control])
     where
         E: GenericEvent,
     {
@@ -74,10 +75,8 @@ impl DebugInfoController {
             self.state.mouse_window = pos;
         } else if let Some(amt) = event.mouse_scroll_args() {
             self.state.mouse_scroll = amt;
-        } else if let Some(btn) = event.press_args() {
-            if let Button::Keyboard(Key::F3) = btn {
-                self.state.should_draw = !self.state.should_draw;
-            }
+        } else if controls.contains(&Control::ToggleDebugInfo) {
+            self.state.should_draw = !self.state.should_draw;
         }
     }
 }
@@ -1,6 +1,5 @@
-use opengl_graphics::GlGraphics;
-use graphics::{Transformed, DrawState, Context};
-use text_renderer::{FontSize, TextRenderer};
+use render::Renderer;
+use text_renderer::FontSize;
 use super::DebugInfoController;
 
 const TEXT_VERTICAL_SPACING: f64 = 12.0;
@@ -8,19 +7,17 @@ const TEXT_HORIZONTAL_POSITION: f64 = 2.0;
 
 /// Provides functionality for rendering visual information
 /// pertaining to the status of the program and its performance.
-pub struct DebugInfoView {
-    text_renderer: &'static TextRenderer,
-}
+pub struct DebugInfoView;
 
 impl DebugInfoView {
-    /// Creates and returns a new instance of the DebugInfoView struct
-    /// with a pre-populated glyph cache.
-    pub fn new(txt: &'static TextRenderer) -> DebugInfoView {
-        DebugInfoView { text_renderer: txt }
+    /// Creates and returns a new instance of the DebugInfoView struct.
+    pub fn new() -> DebugInfoView {
+        DebugInfoView
     }
 
-    /// Draws the information provided by the DebugInfoController to the screen.
-    pub fn draw(&mut self, controller: &DebugInfoController, c: &Context, g: &mut GlGraphics) {
+    /// Draws the information provided by the DebugInfoController with the
+    /// provided renderer.
+    pub fn draw(&mut self, controller: &DebugInfoController, r: &mut Renderer) {
         if !controller.should_draw() {
             return;
         }
@@ -34,48 +31,36 @@ impl DebugInfoView {
         self.write_at(
             format!("W/H: {:?}", window_size),
             [TEXT_HORIZONTAL_POSITION, TEXT_VERTICAL_SPACING * 0.0],
-            c,
-            g,
+            r,
         );
 
         self.write_at(
             format!("Mouse relative: {:?}", mouse_rel_pos),
             [TEXT_HORIZONTAL_POSITION, TEXT_VERTICAL_SPACING * 1.0],
-            c,
-            g,
+            r,
         );
 
         self.write_at(
             format!("Mouse pos: {:?}", mouse_win_pos),
             [TEXT_HORIZONTAL_POSITION, TEXT_VERTICAL_SPACING * 2.0],
-            c,
-            g,
+            r,
         );
 
         self.write_at(
             format!("Mouse scroll: {:?}", mouse_scroll),
             [TEXT_HORIZONTAL_POSITION, TEXT_VERTICAL_SPACING * 3.0],
-            c,
-            g,
+            r,
         );
 
         self.write_at(
             format!("FPS: {:?}", current_fps),
             [TEXT_HORIZONTAL_POSITION, TEXT_VERTICAL_SPACING * 4.0],
-            c,
-            g,
+            r,
         );
     }
 
     /// Helper function for rendering text to an area on the screen.
-    fn write_at(&mut self, text: String, pos: [f64; 2], c: &Context, g: &mut GlGraphics) {
-        self.text_renderer.draw_at(
-            pos,
-            text,
-            c.transform,
-            FontSize::Size18,
-            [1.0, 0.0, 0.0, 1.0],
-            g,
-        );
+    fn write_at(&mut self, text: String, pos: [f64; 2], r: &mut Renderer) {
+        r.draw_text(text.as_str(), pos, FontSize::Size18, [1.0, 0.0, 0.0, 1.0]);
     }
 }
@@ -1,11 +1,16 @@
 use std::collections::VecDeque;
+use std::rc::Rc;
 use mopa;
 use uuid::Uuid;
-use super::{Drawable, Message, Map, Movable, Positioned};
+use super::{Drawable, Message, Map, Movable, Noise, Positioned};
 use super::actors::*;
+use super::combat::{RangedAttack, Vitals};
+use super::faction::{Factions, Relationship};
+use super::rng;
+use super::status_effect::{StatusEffect, StatusEffects};
 
 /// Dictates which set of behavior patterns the actor will exhibit
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum BehaviorStyle {
     Friendly,
     Oblivious,
@@ -15,30 +20,61 @@ pub enum BehaviorStyle {
 }
 
 /// Markers for the various types of actors that are available
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ActorType {
     Player,
     Soldier,
-    // Scripted,
+    /// An actor built from the actor definition with the contained ID.
+    Scripted(String),
+}
+
+impl ActorType {
+    /// Returns the name used to refer to this type of actor in data files
+    /// and scripts.
+    pub fn name(&self) -> String {
+        match *self {
+            ActorType::Player => String::from("player"),
+            ActorType::Soldier => String::from("soldier"),
+            ActorType::Scripted(ref id) => id.clone(),
+        }
+    }
+
+    /// Returns the type of actor referred to by the provided name.  Any name
+    /// not belonging to a built-in actor is assumed to be the ID of an
+    /// actor definition.
+    pub fn from_name(name: &str) -> ActorType {
+        match name {
+            "player" => ActorType::Player,
+            "soldier" => ActorType::Soldier,
+            id => ActorType::Scripted(String::from(id)),
+        }
+    }
 }
 
 /// Used by Actor implementations to force their controller to perform
 /// some action
 #[derive(Debug, Clone)]
 pub enum ActorStatus {
-    Resize([u32; 2]),
     LoadMapAtRelativeOffset([i32; 2]),
-    ToggleMessageVisibility,
-    SpawnActorAt(ActorType, [i32; 2]),
+    /// Attack the adjacent actor with the contained ID.
+    Attack(Uuid),
+    /// Launch a projectile toward the contained position.
+    Shoot([i32; 2], RangedAttack),
     Quit,
 }
 
 /// Used as a means of communicating information about actors to other actors.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ActorInfo {
     pub id: Uuid,
     pub actor_type: ActorType,
     pub position: [i32; 2],
+    pub name: String,
+    pub faction: String,
+
+    /// How the actor feels about whoever is receiving this information.
+    /// Defaults to `Neutral` until set with `relative_to`.
+    pub relationship: Relationship,
 }
 
 impl ActorInfo {
@@ -49,8 +85,19 @@ impl ActorInfo {
             id: actor.id(),
             actor_type: actor.actor_type(),
             position: actor.current_position(),
+            name: actor.name(),
+            faction: actor.faction(),
+            relationship: Relationship::Neutral,
         }
     }
+
+    /// Returns a copy of this information with its `relationship` describing
+    /// how this actor feels about members of the provided faction.
+    pub fn relative_to(&self, faction: &str, factions: &Factions) -> ActorInfo {
+        let mut info = self.clone();
+        info.relationship = factions.relationship(self.faction.as_str(), faction);
+        info
+    }
 }
 
 /// Stores information pertaining to a single actor in the game's
@@ -61,8 +108,9 @@ pub trait Actor: mopa::Any + Drawable + Movable + Positioned {
     /// Called when the object is created, after it is initialized
     fn on_create(&mut self);
 
-    /// Called on each update tick
-    fn on_update(&mut self, map: &Map, actors: &[ActorInfo]);
+    /// Called on each update tick.  The map is shared rather than borrowed
+    /// so that actors, such as scripted ones, can hold on to it cheaply.
+    fn on_update(&mut self, map: &Rc<Map>, actors: &[ActorInfo]);
 
     /// Called when interacted with by another Actor
     fn on_interact(&mut self, actors: &[ActorInfo]);
@@ -70,12 +118,56 @@ pub trait Actor: mopa::Any + Drawable + Movable + Positioned {
     /// Called before the Actor is removed from game state
     fn on_remove(&mut self, actors: &[ActorInfo]);
 
+    /// Called before each update tick for every noise, made since the
+    /// previous tick by something other than this Actor, that reached the
+    /// Actor's position.  `volume` is how loud the noise was when it arrived.
+    fn on_hear(&mut self, _: &Noise, _volume: u32) {}
+
     /// Returns a marker indicating what type of actor this is
     fn actor_type(&self) -> ActorType;
 
     /// Returns the numerical ID of the Actor
     fn id(&self) -> Uuid;
 
+    /// Returns the name of the faction that the Actor belongs to
+    fn faction(&self) -> String;
+
+    /// Returns the name used to describe the Actor to the player
+    fn name(&self) -> String {
+        self.actor_type().name()
+    }
+
+    /// Returns the Actor's health and combat statistics, if it is able to fight
+    fn vitals(&self) -> Option<&Vitals> {
+        None
+    }
+
+    /// Returns a mutable reference to the Actor's health and combat statistics,
+    /// if it is able to fight
+    fn vitals_mut(&mut self) -> Option<&mut Vitals> {
+        None
+    }
+
+    /// Called after the Actor has been attacked by another Actor
+    fn on_attacked(&mut self, _attacker: &ActorInfo, _damage: i32) {}
+
+    /// Returns the status effects currently affecting the Actor, if it can
+    /// be affected by them
+    fn status_effects(&self) -> Option<&StatusEffects> {
+        None
+    }
+
+    /// Returns a mutable reference to the status effects currently affecting
+    /// the Actor, if it can be affected by them
+    fn status_effects_mut(&mut self) -> Option<&mut StatusEffects> {
+        None
+    }
+
+    /// Returns the status effects inflicted on whoever the Actor hits
+    fn attack_effects(&self) -> Vec<StatusEffect> {
+        Vec::new()
+    }
+
     /// Returns the implementor's current `ActorStatus` if one exists
     fn status(&mut self) -> Option<ActorStatus> {
         None
@@ -85,14 +177,26 @@ pub trait Actor: mopa::Any + Drawable + Movable + Positioned {
     fn messages(&mut self) -> Option<&mut VecDeque<Message>> {
         None
     }
+
+    /// Returns the implementor's queue of noises that it has made
+    fn noises(&mut self) -> Option<&mut VecDeque<Noise>> {
+        None
+    }
 }
 mopafy!(Actor);
 
-pub fn create(actor_type: &ActorType) -> Box<Actor> {
+/// Creates and initializes a new actor of the specified type.
+pub fn create(actor_type: &ActorType) -> Result<Box<Actor>, String> {
     let mut actor: Box<Actor> = match *actor_type {
-        ActorType::Player => Box::new(player::Player::new(Uuid::new_v4())),
+        ActorType::Player => Box::new(player::Player::new(rng::new_id())),
         ActorType::Soldier => Box::new(soldier::Soldier::new()),
+        ActorType::Scripted(ref id) => {
+            match scripted_behavior::get(id.as_str()) {
+                Some(behavior) => Box::new(scripted_actor::ScriptedActor::new(behavior)),
+                None => return Err(format!("No actor definition found with ID {:?}", id)),
+            }
+        }
     };
     actor.on_create();
-    actor
+    Ok(actor)
 }
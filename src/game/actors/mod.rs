@@ -1,3 +1,4 @@
 pub mod player;
 pub mod soldier;
 pub mod scripted_actor;
+pub mod scripted_behavior;
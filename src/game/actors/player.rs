@@ -1,15 +1,39 @@
 use std::collections::VecDeque;
+use std::rc::Rc;
 use uuid::Uuid;
-use piston::input::{Button, Key, GenericEvent};
 use game;
 use game::actor::{Actor, ActorStatus, ActorType, ActorInfo, BehaviorStyle};
+use game::combat::{RangedAttack, Vitals};
+use game::faction::{Relationship, PLAYER_FACTION};
+use game::status_effect::StatusEffects;
 use game::message::MessageType;
-use game::{Message, Map, Movable, MovementResult, MovementDirection, Drawable, Positioned,
-           SpriteInfo};
+use game::{Command, Item, Message, Map, Movable, MovementResult, MovementDirection, Drawable,
+           Noise, Positioned, SpriteInfo, Weapon};
 
 /// The default number of spaces that the player moves at once.
 pub const MOVEMENT_AMOUNT: i32 = 1;
 
+/// How loud the player's footsteps are.
+const FOOTSTEP_LOUDNESS: u32 = 4;
+
+const MAX_HEALTH: i32 = 30;
+const ATTACK: i32 = 4;
+const DEFENSE: i32 = 1;
+
+/// The number of turns it takes the player to recover a point of health.
+const REGENERATION_TURNS: u32 = 10;
+
+const SLING_RANGE: u32 = 8;
+const SLING_ATTACK: i32 = 2;
+const SLING_COLOR: [f32; 4] = [0.6, 0.6, 0.6, 1.0];
+
+/// The name of a throwing knife, both as a weapon and as an item.
+const KNIFE_NAME: &'static str = "throwing knife";
+const KNIFE_RANGE: u32 = 5;
+const KNIFE_ATTACK: i32 = 5;
+const KNIFE_COLOR: [f32; 4] = [0.85, 0.85, 0.95, 1.0];
+const STARTING_KNIVES: u32 = 5;
+
 const SPRITE_INFO: SpriteInfo = SpriteInfo {
     key: "pc",
     color: [0.141, 0.424, 0.376, 1.0],
@@ -22,7 +46,22 @@ pub struct Player {
     id: Uuid,
     status: Option<ActorStatus>,
     messages: VecDeque<Message>,
+    noises: VecDeque<Noise>,
     ticks: Option<u32>,
+    vitals: Vitals,
+    effects: StatusEffects,
+    sling: RangedAttack,
+    knives: u32,
+    targeting: Option<Targeting>,
+    turns_until_healed: u32,
+}
+
+/// The state of the player's aim while choosing where to send a projectile.
+struct Targeting {
+    attack: RangedAttack,
+    thrown: bool,
+    cursor: [i32; 2],
+    target: Option<Uuid>,
 }
 
 impl Player {
@@ -33,64 +72,176 @@ impl Player {
             id: id,
             status: None,
             messages: VecDeque::<Message>::new(),
+            noises: VecDeque::<Noise>::new(),
             ticks: None,
+            vitals: Vitals::new(MAX_HEALTH, ATTACK, DEFENSE),
+            effects: StatusEffects::new(),
+            sling: RangedAttack::new("sling stone", SLING_RANGE, SLING_ATTACK, SLING_COLOR),
+            knives: STARTING_KNIVES,
+            targeting: None,
+            turns_until_healed: REGENERATION_TURNS,
         }
     }
 
+    /// Returns the position that the player is aiming at and the range of
+    /// the attack being aimed, if the player is choosing a target.
+    pub fn aim(&self) -> Option<([i32; 2], u32)> {
+        self.targeting.as_ref().map(|t| (t.cursor, t.attack.range))
+    }
+
     pub fn ticks(&mut self) -> Option<u32> {
         let ticks = self.ticks;
         self.ticks = None;
         ticks
     }
 
-    /// Allows the Player actor to process input events
-    pub fn event_update<G: GenericEvent>(&mut self, event: &G, map: &Map) {
-        if let Some(btn) = event.press_args() {
-            use game::MovementDirection::*;
-            match btn {
-                Button::Keyboard(Key::F1) => {
-                    self.status = Some(ActorStatus::SpawnActorAt(ActorType::Soldier, [10, 10]));
-                }
-                Button::Keyboard(Key::Tab) => {
-                    self.status = Some(ActorStatus::ToggleMessageVisibility);
-                }
-                Button::Keyboard(Key::NumPad1) => {
-                    self.input_move(map, DownLeft);
-                }
-                Button::Keyboard(Key::NumPad2) => {
-                    self.input_move(map, Down);
-                }
-                Button::Keyboard(Key::NumPad3) => {
-                    self.input_move(map, DownRight);
-                }
-                Button::Keyboard(Key::NumPad4) => {
-                    self.input_move(map, Left);
-                }
-                Button::Keyboard(Key::NumPad6) => {
-                    self.input_move(map, Right);
-                }
-                Button::Keyboard(Key::NumPad7) => {
-                    self.input_move(map, UpLeft);
-                }
-                Button::Keyboard(Key::NumPad8) => {
-                    self.input_move(map, Up);
-                }
-                Button::Keyboard(Key::NumPad9) => {
-                    self.input_move(map, UpRight);
+    /// Carries out a command given to the player.  Commands used for aiming
+    /// are ignored unless the player is choosing a target, and all others
+    /// are ignored while they are.
+    pub fn perform(&mut self, command: &Command, map: &Map, actors: &[ActorInfo]) {
+        if command.is_aiming() != self.targeting.is_some() {
+            return;
+        }
+
+        match *command {
+            Command::Move(dir) => self.input_move(map, actors, dir),
+            Command::Wait => self.perform_ticks(1),
+            Command::Aim(Weapon::Sling) => {
+                let sling = self.sling.clone();
+                self.begin_targeting(sling, false, actors);
+            }
+            Command::Aim(Weapon::Knife) => {
+                if self.knives > 0 {
+                    let knife =
+                        RangedAttack::new(KNIFE_NAME, KNIFE_RANGE, KNIFE_ATTACK, KNIFE_COLOR);
+                    self.begin_targeting(knife, true, actors);
+                } else {
+                    self.messages.push_back(Message {
+                        contents: String::from("You have no knives left to throw."),
+                        message_type: MessageType::Normal,
+                    });
                 }
-                Button::Keyboard(Key::NumPad0) => {
-                    self.perform_ticks(1);
+            }
+            Command::MoveCursor(dir) => {
+                if let Some(targeting) = self.targeting.as_mut() {
+                    // the cursor moves freely, no longer following any particular actor
+                    targeting.cursor =
+                        game::map_direction_to_position(targeting.cursor, &dir, MOVEMENT_AMOUNT);
+                    targeting.target = None;
                 }
-                _ => {}
+            }
+            Command::CycleTarget => self.cycle_target(actors),
+            Command::Release => self.release(),
+            Command::CancelAim => {
+                self.targeting = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Takes the provided item from the ground.  Returns the item if the
+    /// player has no use for it, so that it can be left where it was.
+    pub fn pick_up(&mut self, item: Item) -> Option<Item> {
+        if item.name == KNIFE_NAME {
+            self.knives += 1;
+            self.messages.push_back(Message {
+                contents: format!("You pick up a {}.", item.name),
+                message_type: MessageType::Normal,
+            });
+            self.perform_ticks(1);
+            None
+        } else {
+            self.messages.push_back(Message {
+                contents: format!("You have no use for the {}.", item.name),
+                message_type: MessageType::Normal,
+            });
+            Some(item)
+        }
+    }
+
+    fn begin_targeting(&mut self, attack: RangedAttack, thrown: bool, actors: &[ActorInfo]) {
+        self.targeting = Some(Targeting {
+            attack: attack,
+            thrown: thrown,
+            cursor: self.position,
+            target: None,
+        });
+        self.cycle_target(actors);
+        self.messages.push_back(Message {
+            contents: String::from(
+                "Choose a target: Tab cycles enemies, numpad aims, Enter fires, Backspace cancels.",
+            ),
+            message_type: MessageType::Background,
+        });
+    }
+
+    /// Aims at the next visible enemy, nearest first.
+    fn cycle_target(&mut self, actors: &[ActorInfo]) {
+        let position = self.position;
+        let mut enemies: Vec<&ActorInfo> = actors
+            .iter()
+            .filter(|a| a.id != self.id && a.relationship == Relationship::Hostile)
+            .collect();
+        enemies.sort_by_key(|a| game::chebyshev_distance(position, a.position));
+
+        if let Some(targeting) = self.targeting.as_mut() {
+            let next = match targeting.target.and_then(
+                |id| enemies.iter().position(|a| a.id == id),
+            ) {
+                Some(index) => enemies.get((index + 1) % enemies.len()),
+                None => enemies.first(),
+            };
+            if let Some(enemy) = next {
+                targeting.cursor = enemy.position;
+                targeting.target = Some(enemy.id);
             }
         }
     }
 
-    fn input_move(&mut self, map: &Map, dir: MovementDirection) {
+    /// Launches the projectile being aimed, if the player is aiming anywhere
+    /// but at themselves.
+    fn release(&mut self) {
+        if let Some(targeting) = self.targeting.take() {
+            if targeting.cursor == self.position {
+                return;
+            }
+            if targeting.thrown {
+                self.knives = self.knives.saturating_sub(1);
+            }
+            self.status = Some(ActorStatus::Shoot(targeting.cursor, targeting.attack));
+            self.perform_ticks(1);
+        }
+    }
+
+    /// Moves the player a single step in the provided direction, attacking
+    /// whoever is standing there unless they are an ally.
+    fn input_move(&mut self, map: &Map, actors: &[ActorInfo], dir: MovementDirection) {
+        let dir = self.effects.redirect(dir);
+
+        // moving into another actor attacks it, unless it is an ally
+        let destination = game::map_direction_to_position(self.position, &dir, MOVEMENT_AMOUNT);
+        if let Some(other) = actors.iter().find(
+            |a| a.id != self.id && a.position == destination,
+        )
+        {
+            if other.relationship != Relationship::Ally {
+                self.status = Some(ActorStatus::Attack(other.id));
+            }
+            self.perform_ticks(1);
+            return;
+        }
+
         match game::try_move(self, map, &dir, MOVEMENT_AMOUNT) {
-            MovementResult::Clear => self.move_toward(&dir),
+            MovementResult::Clear => {
+                self.move_toward(&dir);
+                self.noises.push_back(Noise::new(
+                    self.position,
+                    FOOTSTEP_LOUDNESS,
+                    Some(self.id),
+                    "footsteps",
+                ));
+            }
             MovementResult::MapEdge(edge_pos) => {
-                self.move_over_edge(map, edge_pos);
                 let new_map_offset = Player::get_new_map_offset_from_edge(map, edge_pos);
                 self.status = Some(ActorStatus::LoadMapAtRelativeOffset(new_map_offset));
             }
@@ -103,21 +254,6 @@ impl Player {
         self.ticks = Some(count);
     }
 
-    fn move_over_edge(&mut self, map: &Map, edge_pos: [i32; 2]) {
-        let (edge_x, edge_y) = (edge_pos[0], edge_pos[1]);
-        let (width, height) = (map.width() as i32, map.height() as i32);
-        if edge_x == -1 {
-            self.set_x(width - 1);
-        } else if edge_x == width {
-            self.set_x(0);
-        }
-        if edge_y == -1 {
-            self.set_y(height - 1);
-        } else if edge_y == height {
-            self.set_y(0);
-        }
-    }
-
     fn get_new_map_offset_from_edge(map: &Map, edge_pos: [i32; 2]) -> [i32; 2] {
         let (mut offset_x, mut offset_y) = (0, 0);
         let (edge_x, edge_y) = (edge_pos[0], edge_pos[1]);
@@ -170,7 +306,16 @@ impl Actor for Player {
         });
     }
 
-    fn on_update(&mut self, _: &Map, _: &[ActorInfo]) {}
+    fn on_update(&mut self, _: &Rc<Map>, _: &[ActorInfo]) {
+        // slowly recover from wounds
+        if self.vitals.health < self.vitals.max_health {
+            self.turns_until_healed -= 1;
+            if self.turns_until_healed == 0 {
+                self.vitals.heal(1);
+                self.turns_until_healed = REGENERATION_TURNS;
+            }
+        }
+    }
 
     fn on_interact(&mut self, _: &[ActorInfo]) {}
 
@@ -184,6 +329,30 @@ impl Actor for Player {
         self.id
     }
 
+    fn faction(&self) -> String {
+        String::from(PLAYER_FACTION)
+    }
+
+    fn name(&self) -> String {
+        String::from("you")
+    }
+
+    fn vitals(&self) -> Option<&Vitals> {
+        Some(&self.vitals)
+    }
+
+    fn vitals_mut(&mut self) -> Option<&mut Vitals> {
+        Some(&mut self.vitals)
+    }
+
+    fn status_effects(&self) -> Option<&StatusEffects> {
+        Some(&self.effects)
+    }
+
+    fn status_effects_mut(&mut self) -> Option<&mut StatusEffects> {
+        Some(&mut self.effects)
+    }
+
     fn status(&mut self) -> Option<ActorStatus> {
         let status = self.status.clone();
         self.status = None;
@@ -193,4 +362,8 @@ impl Actor for Player {
     fn messages(&mut self) -> Option<&mut VecDeque<Message>> {
         Some(&mut self.messages)
     }
+
+    fn noises(&mut self) -> Option<&mut VecDeque<Noise>> {
+        Some(&mut self.noises)
+    }
 }
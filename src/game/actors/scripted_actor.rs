@@ -1,96 +1,383 @@
-// use std::collections::VecDeque;
-// use uuid::Uuid;
-// use bresenham::Bresenham;
-// use game::actor::{Actor, ActorStatus, ActorType, ActorInfo, BehaviorStyle};
-// use game;
-// use game::message::MessageType;
-// use game::{Message, Movable, MovementDirection, Drawable, SpriteInfo};
-
-// /// The default number of spaces that the player moves at once.
-// pub const MOVEMENT_AMOUNT: i32 = 1;
-
-// /// Stores information and statistics pertaining to the
-// /// player's avatar.
-// pub struct ScriptedActor {
-//     position: [i32; 2],
-//     id: Uuid,
-//     status: Option<ActorStatus>,
-//     messages: VecDeque<Message>,
-// }
-
-// impl ScriptedActor {
-//     /// Creates and returns a new instance of the Player struct
-//     pub fn new() -> ScriptedActor {
-//         ScriptedActor {
-//             position: [0; 2],
-//             id: Uuid::new_v4(),
-//             status: None,
-//             messages: VecDeque::<Message>::new(),
-//         }
-//     }
-// }
-
-// impl Movable for ScriptedActor {
-//     fn move_toward(&mut self, dir: &MovementDirection) {
-//         self.position = game::map_direction_to_position(self.position, dir, MOVEMENT_AMOUNT);
-//     }
-
-//     fn current_position(&self) -> [i32; 2] {
-//         self.position
-//     }
-
-//     fn set_x(&mut self, x: i32) {
-//         self.position = [x, self.position[1]];
-//     }
-
-//     fn set_y(&mut self, y: i32) {
-//         self.position = [self.position[0], y];
-//     }
-// }
-
-// impl Drawable for ScriptedActor {
-//     fn sprite_components(&self) -> SpriteInfo {
-//         SpriteInfo {
-//             key: "asdf",
-
-//         }
-//     }
-// }
-
-// impl Actor for ScriptedActor {
-//     fn init(&mut self, position: [i32; 2], _: BehaviorStyle) -> Result<Uuid, String> {
-//         Ok(self.id)
-//     }
-
-//     fn on_create(&mut self) {
-
-//     }
-
-//     fn on_update(&mut self, actors: &[ActorInfo]) {
-
-//     }
-
-//     fn on_interact(&mut self, actors: &[ActorInfo]) {
-
-//     }
-
-//     fn on_remove(&mut self, actors: &[ActorInfo]) {}
-
-//     fn actor_type(&self) -> ActorType {
-//         ActorType::Scripted
-//     }
-
-//     fn id(&self) -> Uuid {
-//         self.id
-//     }
-
-//     fn status(&mut self) -> Option<ActorStatus> {
-//         let status = self.status.clone();
-//         self.status = None;
-//         status
-//     }
-
-//     fn messages(&mut self) -> Option<&mut VecDeque<Message>> {
-//         Some(&mut self.messages)
-//     }
-// }
+use std::collections::VecDeque;
+use std::rc::Rc;
+use rand::Rng;
+use uuid::Uuid;
+use game::actor::{Actor, ActorStatus, ActorType, ActorInfo, BehaviorStyle};
+use game;
+use game::combat::Vitals;
+use game::faction::Relationship;
+use game::sprite_info;
+use game::perception::Perception;
+use game::rng;
+use game::script::{self, ActorScript, ScriptFrame};
+use game::status_effect::{StatusEffect, StatusEffects};
+use game::{Message, Map, Movable, MovementDirection, MovementResult, Drawable, Noise, Positioned,
+           SpriteInfo};
+use super::scripted_behavior::{self, ScriptedBehavior};
+
+/// The default number of spaces that a scripted actor moves at once.
+pub const MOVEMENT_AMOUNT: i32 = 1;
+
+/// The distance that friendly actors try to keep from the player, and
+/// within which fearful actors will try to flee.
+const PERSONAL_SPACE: i32 = 3;
+
+/// An actor whose appearance, statistics, and behavior are defined by a
+/// `ScriptedBehavior` loaded from the actor definition files.  If the
+/// definition names a script, its hooks are run in place of the built-in
+/// behavior.
+pub struct ScriptedActor {
+    position: [i32; 2],
+    id: Uuid,
+    status: Option<ActorStatus>,
+    messages: VecDeque<Message>,
+    noises: VecDeque<Noise>,
+    behavior: ScriptedBehavior,
+    revision: u32,
+    sprite_key: &'static str,
+    vitals: Vitals,
+    effects: StatusEffects,
+    script: Option<ActorScript>,
+    perception: Perception,
+}
+
+impl ScriptedActor {
+    /// Creates and returns a new instance of the ScriptedActor struct
+    /// based on the provided definition.
+    pub fn new(behavior: ScriptedBehavior) -> ScriptedActor {
+        ScriptedActor {
+            position: [0; 2],
+            id: rng::new_id(),
+            status: None,
+            messages: VecDeque::<Message>::new(),
+            noises: VecDeque::<Noise>::new(),
+            sprite_key: sprite_info::intern_key(behavior.sprite.as_str()),
+            vitals: Vitals::new(
+                behavior.stats.max_health,
+                behavior.stats.attack,
+                behavior.stats.defense,
+            ),
+            effects: StatusEffects::new(),
+            revision: scripted_behavior::revision(),
+            script: ScriptedActor::compile_script(&behavior),
+            perception: Perception::new(
+                behavior.stats.sight_radius,
+                behavior.stats.hearing_radius,
+            ),
+            behavior: behavior,
+        }
+    }
+
+    fn compile_script(behavior: &ScriptedBehavior) -> Option<ActorScript> {
+        match behavior.script_source {
+            Some(ref source) => {
+                match ActorScript::compile(source.as_str()) {
+                    Ok(script) => Some(script),
+                    Err(why) => {
+                        error!("Script for actor {:?}: {}", behavior.id, why);
+                        None
+                    }
+                }
+            }
+            None => None,
+        }
+    }
+
+    /// Runs the named script hook, if the actor has a script that defines it,
+    /// and applies whatever changes the script made.  Returns a value
+    /// indicating whether the hook was run.
+    fn run_hook(&mut self, hook: &str, map: Option<&Rc<Map>>, actors: &[ActorInfo]) -> bool {
+        let frame = ScriptFrame {
+            id: Some(self.id),
+            name: self.behavior.name.clone(),
+            position: self.position,
+            health: self.vitals.health,
+            actors: actors.iter().filter(|a| a.id != self.id).cloned().collect(),
+            map: map.cloned(),
+            ..ScriptFrame::default()
+        };
+
+        let result = match self.script {
+            Some(ref mut script) => script.call(hook, frame),
+            None => None,
+        };
+
+        match result {
+            Some(frame) => {
+                self.position = frame.position;
+                if let Some(target) = frame.attack_target {
+                    self.status = Some(ActorStatus::Attack(target));
+                }
+                self.messages.extend(frame.messages);
+                self.noises.extend(frame.noises);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Picks up any changes made to this actor's definition since it was created.
+    fn refresh_behavior(&mut self) {
+        let revision = scripted_behavior::revision();
+        if revision == self.revision {
+            return;
+        }
+        self.revision = revision;
+
+        if let Some(behavior) = scripted_behavior::get(self.behavior.id.as_str()) {
+            self.sprite_key = sprite_info::intern_key(behavior.sprite.as_str());
+            self.vitals.max_health = behavior.stats.max_health;
+            self.vitals.attack = behavior.stats.attack;
+            self.vitals.defense = behavior.stats.defense;
+            if self.vitals.health > self.vitals.max_health {
+                self.vitals.health = self.vitals.max_health;
+            }
+            self.perception.hearing_radius = behavior.stats.hearing_radius;
+
+            // other definitions may have changed instead, so only recompile when
+            // this one's script has, and keep whatever the old script remembered
+            let recompile = behavior.script_source != self.behavior.script_source;
+            self.behavior = behavior;
+            if recompile {
+                let previous = self.script.take();
+                match (ScriptedActor::compile_script(&self.behavior), previous) {
+                    (Some(mut script), Some(previous)) => {
+                        script.inherit_memory(previous);
+                        self.script = Some(script);
+                    }
+
+                    // a script with nothing to remember yet starts out as it would
+                    // if the actor had just been created
+                    (Some(script), None) => {
+                        self.script = Some(script);
+                        self.run_hook(script::HOOK_ON_CREATE, None, &[]);
+                    }
+
+                    // a script that no longer compiles is kept running as it was
+                    (None, previous) => self.script = previous,
+                }
+            }
+        } else {
+            warn!(
+                "Definition {:?} was removed; actor {} keeps its previous behavior",
+                self.behavior.id,
+                self.id
+            );
+        }
+    }
+
+    fn distance_to(&self, other: &ActorInfo) -> i32 {
+        game::chebyshev_distance(self.position, other.position)
+    }
+
+    /// Attacks the target if it is adjacent, or otherwise returns the
+    /// direction to move in order to reach it.
+    fn engage(&mut self, target: &ActorInfo) -> Option<MovementDirection> {
+        if self.distance_to(target) <= 1 {
+            self.status = Some(ActorStatus::Attack(target.id));
+            None
+        } else {
+            game::direction_toward(self.position, target.position)
+        }
+    }
+
+    /// Returns the direction toward wherever the player was last seen or
+    /// heard, if the player is an enemy that is still being searched for.
+    fn search(&mut self, actors: &[ActorInfo], player_visible: bool) -> Option<MovementDirection> {
+        let player = match actors.iter().find(|a| a.actor_type == ActorType::Player) {
+            Some(player) if player.relationship == Relationship::Hostile => player,
+            _ => return None,
+        };
+        match self.perception.last_known_position(&player.id) {
+            Some(position) => {
+                if position == self.position && !player_visible {
+                    // nobody here; give up the search
+                    self.perception.forget(&player.id);
+                }
+                game::direction_toward(self.position, position)
+            }
+            None => None,
+        }
+    }
+
+    /// Attempts to take a single step in the provided direction, avoiding
+    /// walls, fluids, the map edge, and other actors.
+    fn step(&mut self, map: &Map, actors: &[ActorInfo], dir: MovementDirection) {
+        let dir = self.effects.redirect(dir);
+        let destination = game::map_direction_to_position(self.position, &dir, MOVEMENT_AMOUNT);
+        if actors.iter().any(|a| a.position == destination) {
+            return;
+        }
+        if let MovementResult::Clear = game::try_move(self, map, &dir, MOVEMENT_AMOUNT) {
+            self.move_toward(&dir);
+        }
+    }
+}
+
+impl Movable for ScriptedActor {
+    fn move_toward(&mut self, dir: &MovementDirection) {
+        self.position = game::map_direction_to_position(self.position, dir, MOVEMENT_AMOUNT);
+    }
+
+    fn set_x(&mut self, x: i32) {
+        self.position = [x, self.position[1]];
+    }
+
+    fn set_y(&mut self, y: i32) {
+        self.position = [self.position[0], y];
+    }
+}
+
+impl Positioned for ScriptedActor {
+    fn current_position(&self) -> [i32; 2] {
+        self.position
+    }
+}
+
+impl Drawable for ScriptedActor {
+    fn sprite_components(&self) -> SpriteInfo {
+        SpriteInfo {
+            key: self.sprite_key,
+            color: self.behavior.color,
+        }
+    }
+}
+
+impl Actor for ScriptedActor {
+    fn on_create(&mut self) {
+        if let Some(ref message) = self.behavior.on_create_message {
+            self.messages.push_back(message.clone());
+        }
+        self.run_hook(script::HOOK_ON_CREATE, None, &[]);
+    }
+
+    fn on_update(&mut self, map: &Rc<Map>, actors: &[ActorInfo]) {
+        self.refresh_behavior();
+        self.perception.sight_radius = self.effects.sight_radius(self.behavior.stats.sight_radius);
+
+        // scripts only learn about the actors that this actor can see
+        let visible = self.perception.observe(map, self.position, actors);
+        if self.run_hook(script::HOOK_ON_UPDATE, Some(map), &visible) {
+            return;
+        }
+
+        // the nearest visible actor that this actor is hostile toward
+        let enemy = visible
+            .iter()
+            .filter(|a| a.relationship == Relationship::Hostile)
+            .min_by_key(|a| game::chebyshev_distance(self.position, a.position))
+            .cloned();
+        let player = actors
+            .iter()
+            .find(|a| a.actor_type == ActorType::Player)
+            .cloned();
+        let visible_player = player.and_then(|p| visible.iter().find(|a| a.id == p.id).cloned());
+
+        let direction = match self.behavior.behavior.clone() {
+            BehaviorStyle::Hostile => {
+                match enemy {
+                    Some(ref enemy) => self.engage(enemy),
+                    None => self.search(actors, visible_player.is_some()),
+                }
+            }
+            BehaviorStyle::Friendly => {
+                match (enemy, visible_player) {
+                    (Some(ref enemy), _) if self.distance_to(enemy) <= PERSONAL_SPACE * 2 => {
+                        self.engage(enemy)
+                    }
+                    (_, Some(ref player)) if self.distance_to(player) > PERSONAL_SPACE => {
+                        game::direction_toward(self.position, player.position)
+                    }
+                    _ => None,
+                }
+            }
+            BehaviorStyle::Fearful => {
+                // run from enemies, and from the player if they get too close
+                enemy
+                    .into_iter()
+                    .chain(visible_player)
+                    .find(|threat| self.distance_to(threat) <= PERSONAL_SPACE)
+                    .and_then(|threat| game::direction_toward(threat.position, self.position))
+            }
+            BehaviorStyle::Oblivious => {
+                use game::MovementDirection::*;
+                let directions = [Up, Down, Left, Right, UpLeft, UpRight, DownLeft, DownRight];
+                rng::with(|rng| rng.choose(&directions).cloned())
+            }
+            BehaviorStyle::Inactive => None,
+        };
+
+        if let Some(dir) = direction {
+            self.step(map, actors, dir);
+        }
+    }
+
+    fn on_interact(&mut self, actors: &[ActorInfo]) {
+        self.run_hook(script::HOOK_ON_INTERACT, None, actors);
+    }
+
+    fn on_hear(&mut self, noise: &Noise, volume: u32) {
+        self.perception.hear(self.position, noise, volume);
+    }
+
+    fn on_attacked(&mut self, attacker: &ActorInfo, _: i32) {
+        self.perception.remember(attacker.id, attacker.position);
+    }
+
+    fn on_remove(&mut self, actors: &[ActorInfo]) {
+        if let Some(ref message) = self.behavior.on_remove_message {
+            self.messages.push_back(message.clone());
+        }
+        self.run_hook(script::HOOK_ON_REMOVE, None, actors);
+    }
+
+    fn actor_type(&self) -> ActorType {
+        ActorType::Scripted(self.behavior.id.clone())
+    }
+
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn faction(&self) -> String {
+        self.behavior.faction.clone()
+    }
+
+    fn name(&self) -> String {
+        self.behavior.name.clone()
+    }
+
+    fn vitals(&self) -> Option<&Vitals> {
+        Some(&self.vitals)
+    }
+
+    fn vitals_mut(&mut self) -> Option<&mut Vitals> {
+        Some(&mut self.vitals)
+    }
+
+    fn status_effects(&self) -> Option<&StatusEffects> {
+        Some(&self.effects)
+    }
+
+    fn status_effects_mut(&mut self) -> Option<&mut StatusEffects> {
+        Some(&mut self.effects)
+    }
+
+    fn attack_effects(&self) -> Vec<StatusEffect> {
+        self.behavior.stats.attack_effects.clone()
+    }
+
+    fn status(&mut self) -> Option<ActorStatus> {
+        let status = self.status.clone();
+        self.status = None;
+        status
+    }
+
+    fn messages(&mut self) -> Option<&mut VecDeque<Message>> {
+        Some(&mut self.messages)
+    }
+
+    fn noises(&mut self) -> Option<&mut VecDeque<Noise>> {
+        Some(&mut self.noises)
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::SystemTime;
+use find_folder::Search;
+use serde_json;
+use super::ScriptedBehavior;
+
+const ACTORS_DIR: &'static str = "assets/actors";
+const DEFINITION_EXTENSION: &'static str = "json";
+const SCRIPT_EXTENSION: &'static str = "rhai";
+
+lazy_static! {
+    static ref LIBRARY: RwLock<BehaviorLibrary> = RwLock::new(BehaviorLibrary::load());
+}
+
+/// Stores every actor definition found in the actors asset folder, along with
+/// the modification times of the files they were read from.
+struct BehaviorLibrary {
+    behaviors: HashMap<String, ScriptedBehavior>,
+    modified: HashMap<PathBuf, SystemTime>,
+    revision: u32,
+}
+
+/// Returns a copy of the actor definition with the provided ID, if one exists.
+pub fn get(id: &str) -> Option<ScriptedBehavior> {
+    LIBRARY.read().unwrap().behaviors.get(id).cloned()
+}
+
+/// Returns the IDs of every loaded actor definition.
+pub fn ids() -> Vec<String> {
+    let mut ids: Vec<String> = LIBRARY.read().unwrap().behaviors.keys().cloned().collect();
+    ids.sort();
+    ids
+}
+
+/// Returns a number that is incremented every time the definitions are reloaded.
+pub fn revision() -> u32 {
+    LIBRARY.read().unwrap().revision
+}
+
+/// Reloads every actor definition if any of the definition files have been
+/// added, removed, or modified since they were last loaded.
+pub fn reload_if_changed() {
+    let changed = LIBRARY.read().unwrap().modified != BehaviorLibrary::scan();
+    if changed {
+        let mut library = LIBRARY.write().unwrap();
+        let revision = library.revision + 1;
+        *library = BehaviorLibrary::load();
+        library.revision = revision;
+        info!(
+            "Reloaded {} actor definitions (revision {})",
+            library.behaviors.len(),
+            revision
+        );
+    }
+}
+
+impl BehaviorLibrary {
+    fn load() -> BehaviorLibrary {
+        let modified = BehaviorLibrary::scan();
+        let paths = modified
+            .keys()
+            .filter(|path| path.extension().map_or(false, |ext| ext == DEFINITION_EXTENSION))
+            .cloned()
+            .collect();
+
+        BehaviorLibrary {
+            behaviors: BehaviorLibrary::read_definitions(paths),
+            modified: modified,
+            revision: 0,
+        }
+    }
+
+    /// Reads the actor definitions from the provided files in order of their
+    /// paths, so that the same files always load the same way.  A definition
+    /// whose ID is already taken by an earlier file is skipped.
+    fn read_definitions(mut paths: Vec<PathBuf>) -> HashMap<String, ScriptedBehavior> {
+        paths.sort();
+        let mut behaviors = HashMap::<String, ScriptedBehavior>::new();
+        let mut sources = HashMap::<String, PathBuf>::new();
+
+        for path in paths {
+            let file = match File::open(&path) {
+                Ok(file) => file,
+                Err(why) => {
+                    error!("Unable to open actor definition {:?}: {:?}", path, why);
+                    continue;
+                }
+            };
+            match serde_json::from_reader::<_, ScriptedBehavior>(BufReader::new(file)) {
+                Ok(mut behavior) => {
+                    if let Some(first) = sources.get(&behavior.id) {
+                        error!(
+                            "Actor definition ID {:?} in {:?} is already used by {:?}; skipping it",
+                            behavior.id,
+                            path,
+                            first
+                        );
+                        continue;
+                    }
+                    if let Some(script) = behavior.script.clone() {
+                        match BehaviorLibrary::read_script(&path.with_file_name(script)) {
+                            Ok(source) => behavior.script_source = Some(source),
+                            Err(why) => error!("{}", why),
+                        }
+                    }
+                    sources.insert(behavior.id.clone(), path.clone());
+                    behaviors.insert(behavior.id.clone(), behavior);
+                }
+                Err(why) => error!("Unable to parse actor definition {:?}: {}", path, why),
+            }
+        }
+        behaviors
+    }
+
+    fn read_script(path: &PathBuf) -> Result<String, String> {
+        let mut source = String::new();
+        File::open(path)
+            .and_then(|mut file| file.read_to_string(&mut source))
+            .map_err(|why| format!("Unable to read actor script {:?}: {:?}", path, why))?;
+        Ok(source)
+    }
+
+    /// Returns the path and modification time of every definition and script file.
+    fn scan() -> HashMap<PathBuf, SystemTime> {
+        let mut modified = HashMap::<PathBuf, SystemTime>::new();
+        let dir = match Search::ParentsThenKids(3, 3).for_folder(ACTORS_DIR) {
+            Ok(dir) => dir,
+            Err(why) => {
+                warn!("Unable to find actor definition folder: {:?}", why);
+                return modified;
+            }
+        };
+
+        if let Ok(entries) = fs::read_dir(dir) {
+            for entry in entries.filter_map(|e| e.ok()) {
+                let path = entry.path();
+                if path.extension().map_or(false, |ext| {
+                    ext == DEFINITION_EXTENSION || ext == SCRIPT_EXTENSION
+                })
+                {
+                    if let Ok(time) = entry.metadata().and_then(|m| m.modified()) {
+                        modified.insert(path, time);
+                    }
+                }
+            }
+        }
+        modified
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::io::Write;
+    use std::process;
+    use super::*;
+
+    fn write_definition(dir: &PathBuf, file: &str, id: &str, name: &str) -> PathBuf {
+        let path = dir.join(file);
+        let definition = format!(
+            r#"{{ "id": "{}", "name": "{}", "sprite": "cat", "color": [1, 1, 1, 1],
+                 "behavior": "Fearful" }}"#,
+            id,
+            name
+        );
+        File::create(&path)
+            .and_then(|mut f| f.write_all(definition.as_bytes()))
+            .unwrap();
+        path
+    }
+
+    #[test]
+    fn duplicate_ids_keep_the_first_path() {
+        let dir = env::temp_dir().join(format!("behavior-test-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let paths = vec![
+            write_definition(&dir, "b.json", "cat", "second cat"),
+            write_definition(&dir, "c.json", "dog", "dog"),
+            write_definition(&dir, "a.json", "cat", "first cat"),
+        ];
+
+        let behaviors = BehaviorLibrary::read_definitions(paths);
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(behaviors.len(), 2);
+        assert_eq!(behaviors["cat"].name, "first cat");
+        assert_eq!(behaviors["dog"].name, "dog");
+    }
+}
@@ -1,5 +1,95 @@
-// #[derive(Serialize, Deserialize)]
-// pub struct ScriptedBehavior {
-//     pub id: String,
-    
-// }
\ No newline at end of file
+//! Data-driven actor definitions, loaded from JSON files in the assets
+//! folder and used to instantiate `ScriptedActor`s.
+
+mod library;
+
+use game::actor::BehaviorStyle;
+use game::Message;
+use game::status_effect::StatusEffect;
+
+pub use self::library::{get, ids, reload_if_changed, revision};
+
+/// The statistics that a scripted actor starts out with.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct BehaviorStats {
+    /// The maximum (and starting) health of the actor.
+    pub max_health: i32,
+
+    /// The amount of damage the actor deals when it attacks.
+    pub attack: i32,
+
+    /// The amount of damage the actor ignores when it is attacked.
+    pub defense: i32,
+
+    /// The greatest distance, in tiles, that the actor can see.
+    pub sight_radius: u32,
+
+    /// The greatest distance, in tiles, that the actor can hear.
+    pub hearing_radius: u32,
+
+    /// The status effects inflicted on whoever the actor hits.
+    pub attack_effects: Vec<StatusEffect>,
+}
+
+impl Default for BehaviorStats {
+    fn default() -> BehaviorStats {
+        BehaviorStats {
+            max_health: 10,
+            attack: 1,
+            defense: 0,
+            sight_radius: 8,
+            hearing_radius: 8,
+            attack_effects: Vec::new(),
+        }
+    }
+}
+
+fn default_faction() -> String {
+    String::from("wildlife")
+}
+
+/// A template describing the appearance and behavior of a type of actor.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScriptedBehavior {
+    /// The unique identifier used to refer to this template.
+    pub id: String,
+
+    /// The name displayed to the player when referring to the actor.
+    pub name: String,
+
+    /// The key of the sprite used to draw the actor.
+    pub sprite: String,
+
+    /// The color used to tint the actor's sprite.
+    pub color: [f32; 4],
+
+    /// The actor's initial statistics.
+    #[serde(default)]
+    pub stats: BehaviorStats,
+
+    /// The pattern of behavior that the actor follows.
+    pub behavior: BehaviorStyle,
+
+    /// The name of the faction that the actor belongs to.
+    #[serde(default = "default_faction")]
+    pub faction: String,
+
+    /// A message shown to the player when the actor is created.
+    #[serde(default)]
+    pub on_create_message: Option<Message>,
+
+    /// A message shown to the player when the actor is removed.
+    #[serde(default)]
+    pub on_remove_message: Option<Message>,
+
+    /// The name of a script file, relative to the definition file, whose
+    /// hooks take the place of the actor's built-in behavior.
+    #[serde(default)]
+    pub script: Option<String>,
+
+    /// The contents of the file named by `script`, read when the definition
+    /// is loaded.
+    #[serde(skip)]
+    pub script_source: Option<String>,
+}
@@ -1,10 +1,17 @@
 use std::collections::VecDeque;
+use std::rc::Rc;
+use rand::Rng;
 use uuid::Uuid;
 use bresenham::Bresenham;
 use game::actor::{Actor, ActorStatus, ActorType, ActorInfo, BehaviorStyle};
 use game;
+use game::combat::{RangedAttack, Vitals};
+use game::faction::Relationship;
 use game::message::MessageType;
-use game::{Message, Movable, Map, MovementDirection, Drawable, Positioned, SpriteInfo};
+use game::perception::Perception;
+use game::rng;
+use game::status_effect::StatusEffects;
+use game::{Message, Movable, Map, MovementDirection, Drawable, Noise, Positioned, SpriteInfo};
 
 /// The default number of spaces that the player moves at once.
 pub const MOVEMENT_AMOUNT: i32 = 1;
@@ -15,6 +22,20 @@ const SPRITE_INFO: SpriteInfo = SpriteInfo {
 };
 
 const PERSONAL_SPACE: f32 = 3.0;
+const SIGHT_RADIUS: u32 = 8;
+const HEARING_RADIUS: u32 = 10;
+const SHOUT_LOUDNESS: u32 = 14;
+const FACTION: &'static str = "soldiers";
+const MAX_HEALTH: i32 = 15;
+const ATTACK: i32 = 3;
+const DEFENSE: i32 = 1;
+const RIFLE_RANGE: u32 = 6;
+const RIFLE_ATTACK: i32 = 2;
+const RIFLE_COLOR: [f32; 4] = [1.0, 0.9, 0.5, 1.0];
+
+/// The chance, as one in this many, that a soldier fires at a visible
+/// player who is out of reach instead of closing the distance.
+const FIRE_CHANCE: u32 = 3;
 
 /// Stores information and statistics pertaining to the
 /// player's avatar.
@@ -23,6 +44,10 @@ pub struct Soldier {
     id: Uuid,
     status: Option<ActorStatus>,
     messages: VecDeque<Message>,
+    noises: VecDeque<Noise>,
+    perception: Perception,
+    vitals: Vitals,
+    effects: StatusEffects,
 }
 
 impl Soldier {
@@ -30,9 +55,13 @@ impl Soldier {
     pub fn new() -> Soldier {
         Soldier {
             position: [0; 2],
-            id: Uuid::new_v4(),
+            id: rng::new_id(),
             status: None,
             messages: VecDeque::<Message>::new(),
+            noises: VecDeque::<Noise>::new(),
+            perception: Perception::new(SIGHT_RADIUS, HEARING_RADIUS),
+            vitals: Vitals::new(MAX_HEALTH, ATTACK, DEFENSE),
+            effects: StatusEffects::new(),
         }
     }
 }
@@ -71,12 +100,71 @@ impl Actor for Soldier {
         });
     }
 
-    fn on_update(&mut self, _: &Map, actors: &[ActorInfo]) {
+    fn on_update(&mut self, map: &Rc<Map>, actors: &[ActorInfo]) {
         if let Some(player) = actors.iter().find(|a| a.actor_type == ActorType::Player) {
+            if player.relationship != Relationship::Hostile {
+                return;
+            }
+
+            self.perception.sight_radius = self.effects.sight_radius(SIGHT_RADIUS);
+            let was_tracking = self.perception.last_known_position(&player.id).is_some();
+            let visible = self.perception.observe(map, self.position, actors);
+
+            // alert everyone nearby upon first spotting the player
+            if !was_tracking && visible.iter().any(|a| a.id == player.id) {
+                self.noises.push_back(Noise::new(
+                    self.position,
+                    SHOUT_LOUDNESS,
+                    Some(self.id),
+                    "someone shouting",
+                ));
+            }
+
+            // take a shot at the player if they are in sight but out of reach
+            let distance = game::chebyshev_distance(self.position, player.position);
+            if visible.iter().any(|a| a.id == player.id) && distance > 1 &&
+                distance <= RIFLE_RANGE as i32 &&
+                rng::with(|rng| rng.gen_weighted_bool(FIRE_CHANCE))
+            {
+                self.status = Some(ActorStatus::Shoot(
+                    player.position,
+                    RangedAttack::new("rifle shot", RIFLE_RANGE, RIFLE_ATTACK, RIFLE_COLOR),
+                ));
+                return;
+            }
+
+            // head for wherever the player was last seen or heard
+            let target = match self.perception.last_known_position(&player.id) {
+                Some(position) => position,
+                None => return,
+            };
+            if target == self.position && !visible.iter().any(|a| a.id == player.id) {
+                // nobody here; give up the search
+                self.perception.forget(&player.id);
+                return;
+            }
+
             let self_pos = (self.position[0], self.position[1]);
-            let player_pos = (player.position[0], player.position[1]);
+            let player_pos = (target[0], target[1]);
             if let Some(next_pos) = Bresenham::new(self_pos, player_pos).nth(1) {
-                if !actors.iter().any(|a| {
+                // confusion may send the soldier somewhere other than intended
+                let next_pos = match game::direction_toward(self.position, [next_pos.0, next_pos.1]) {
+                    Some(dir) => {
+                        let position = game::map_direction_to_position(
+                            self.position,
+                            &self.effects.redirect(dir),
+                            MOVEMENT_AMOUNT,
+                        );
+                        if !map.get_at(position).map_or(false, |t| t.passable()) {
+                            return;
+                        }
+                        (position[0], position[1])
+                    }
+                    None => next_pos,
+                };
+                if [next_pos.0, next_pos.1] == player.position {
+                    self.status = Some(ActorStatus::Attack(player.id));
+                } else if !actors.iter().any(|a| {
                     let distance_from_destination = (((next_pos.0 - a.position[0]).pow(2) +
                                                           (next_pos.1 - a.position[1]).pow(2)) as
                                                          f32)
@@ -104,6 +192,14 @@ impl Actor for Soldier {
 
     fn on_remove(&mut self, _: &[ActorInfo]) {}
 
+    fn on_hear(&mut self, noise: &Noise, volume: u32) {
+        self.perception.hear(self.position, noise, volume);
+    }
+
+    fn on_attacked(&mut self, attacker: &ActorInfo, _: i32) {
+        self.perception.remember(attacker.id, attacker.position);
+    }
+
     fn actor_type(&self) -> ActorType {
         ActorType::Soldier
     }
@@ -112,6 +208,26 @@ impl Actor for Soldier {
         self.id
     }
 
+    fn faction(&self) -> String {
+        String::from(FACTION)
+    }
+
+    fn vitals(&self) -> Option<&Vitals> {
+        Some(&self.vitals)
+    }
+
+    fn vitals_mut(&mut self) -> Option<&mut Vitals> {
+        Some(&mut self.vitals)
+    }
+
+    fn status_effects(&self) -> Option<&StatusEffects> {
+        Some(&self.effects)
+    }
+
+    fn status_effects_mut(&mut self) -> Option<&mut StatusEffects> {
+        Some(&mut self.effects)
+    }
+
     fn status(&mut self) -> Option<ActorStatus> {
         let status = self.status.clone();
         self.status = None;
@@ -121,4 +237,8 @@ impl Actor for Soldier {
     fn messages(&mut self) -> Option<&mut VecDeque<Message>> {
         Some(&mut self.messages)
     }
+
+    fn noises(&mut self) -> Option<&mut VecDeque<Noise>> {
+        Some(&mut self.noises)
+    }
 }
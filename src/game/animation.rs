@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::time::Instant;
+use super::sprite_info::intern_key;
+use super::TextureMapper;
+
+/// Keeps time for animations.  It runs separately from the game's ticks, so
+/// that sprites keep moving while the game waits for the player.
+pub struct AnimationClock {
+    start: Instant,
+    stopped_at: Option<u64>,
+}
+
+impl AnimationClock {
+    /// Creates and returns a new instance of the AnimationClock struct,
+    /// starting from zero.
+    pub fn new() -> AnimationClock {
+        AnimationClock {
+            start: Instant::now(),
+            stopped_at: None,
+        }
+    }
+
+    /// Creates and returns a new instance of the AnimationClock struct that
+    /// always reads the provided time, so that the same state is always
+    /// drawn the same way.
+    pub fn stopped(millis: u64) -> AnimationClock {
+        AnimationClock {
+            start: Instant::now(),
+            stopped_at: Some(millis),
+        }
+    }
+
+    /// Returns the number of milliseconds since the clock started.
+    pub fn millis(&self) -> u64 {
+        if let Some(millis) = self.stopped_at {
+            return millis;
+        }
+        let elapsed = self.start.elapsed();
+        elapsed.as_secs() * 1000 + (elapsed.subsec_nanos() / 1_000_000) as u64
+    }
+}
+
+/// The frames of a single animated sprite.
+struct Animation {
+    frames: Vec<&'static str>,
+    frame_millis: u64,
+}
+
+/// The animated sprites listed in the texture coordinates file, each of
+/// which is drawn as one of its frames depending on the time.
+pub struct Animations {
+    animations: HashMap<&'static str, Animation>,
+}
+
+impl Animations {
+    /// Creates and returns a new instance of the Animations struct, reading
+    /// the animations from the texture coordinates file.  Animations whose
+    /// frames aren't in the texture atlas are left out.
+    pub fn load() -> Animations {
+        let coords = TextureMapper::load_coords();
+        let mut animations = HashMap::<&'static str, Animation>::new();
+        for (key, animation) in &coords.animations {
+            if animation.frames.is_empty() || animation.frame_millis == 0 {
+                warn!("The animation for {:?} has no frames or no frame time.", key);
+                continue;
+            }
+            let missing = animation.frames.iter().find(|f| !coords.map.contains_key(*f));
+            if let Some(missing) = missing {
+                warn!(
+                    "The animation for {:?} has a frame, {:?}, that isn't in the atlas.",
+                    key,
+                    missing
+                );
+                continue;
+            }
+            animations.insert(
+                intern_key(key),
+                Animation {
+                    frames: animation.frames.iter().map(|f| intern_key(f)).collect(),
+                    frame_millis: animation.frame_millis,
+                },
+            );
+        }
+        Animations { animations: animations }
+    }
+
+    /// Returns the key of the frame to draw for the sprite at the provided
+    /// time, or the sprite's own key if it isn't animated.  The phase, such
+    /// as one worked out from the sprite's position, shifts the animation
+    /// along by some number of frames so that sprites next to each other
+    /// don't all move in step.
+    pub fn frame(&self, key: &'static str, millis: u64, phase: u64) -> &'static str {
+        match self.animations.get(key) {
+            Some(animation) => {
+                let count = animation.frames.len() as u64;
+                let index = (millis / animation.frame_millis + phase) % count;
+                animation.frames[index as usize]
+            }
+            None => key,
+        }
+    }
+}
+
+/// Returns a phase for an animation that differs between neighboring
+/// positions on the map.
+pub fn phase_at(position: [i32; 2]) -> u64 {
+    (position[0].wrapping_mul(7) ^ position[1].wrapping_mul(13)) as u32 as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn animations() -> Animations {
+        let mut animations = HashMap::<&'static str, Animation>::new();
+        animations.insert(
+            "fluid",
+            Animation {
+                frames: vec!["fluid_0", "fluid_1", "fluid_2"],
+                frame_millis: 100,
+            },
+        );
+        Animations { animations: animations }
+    }
+
+    #[test]
+    fn frames_advance_with_time() {
+        let animations = animations();
+        assert_eq!(animations.frame("fluid", 0, 0), "fluid_0");
+        assert_eq!(animations.frame("fluid", 99, 0), "fluid_0");
+        assert_eq!(animations.frame("fluid", 100, 0), "fluid_1");
+        assert_eq!(animations.frame("fluid", 250, 0), "fluid_2");
+        assert_eq!(animations.frame("fluid", 300, 0), "fluid_0");
+    }
+
+    #[test]
+    fn phase_shifts_the_frame() {
+        let animations = animations();
+        assert_eq!(animations.frame("fluid", 0, 1), "fluid_1");
+        assert_eq!(animations.frame("fluid", 100, 2), "fluid_0");
+    }
+
+    #[test]
+    fn sprites_without_animations_are_drawn_as_they_are() {
+        assert_eq!(animations().frame("wall_stone", 1234, 5), "wall_stone");
+    }
+
+    #[test]
+    fn neighbors_have_different_phases() {
+        let origin = phase_at([10, 10]);
+        assert_ne!(origin, phase_at([11, 10]));
+        assert_ne!(origin, phase_at([10, 11]));
+        assert_eq!(origin, phase_at([10, 10]));
+    }
+
+    #[test]
+    fn stopped_clocks_keep_their_time() {
+        assert_eq!(AnimationClock::stopped(250).millis(), 250);
+    }
+}
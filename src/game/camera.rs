@@ -0,0 +1,155 @@
+use settings::CameraOptions;
+
+/// The width and height, in texels, of each sprite in the texture atlas.
+const SPRITE_SIZE: f64 = 16.0;
+
+/// Decides which part of the map is shown on the screen.  Tiles are always
+/// drawn at the same size for a given zoom level, however large the screen
+/// is, and the camera follows the player once they leave the deadzone in the
+/// middle of the screen.
+#[derive(Debug, Clone)]
+pub struct Camera {
+    settings: CameraOptions,
+    zoom: usize,
+
+    /// Whether tiles are kept to whole multiples of the sprite size, drawn
+    /// on whole pixels.
+    pixel_perfect: bool,
+
+    /// The map position, in tiles, shown at the top-left corner of the screen.
+    origin: [f64; 2],
+}
+
+impl Camera {
+    /// Creates and returns a new instance of the Camera struct.  The
+    /// options must have a positive tile size and at least one zoom level.
+    pub fn new(settings: CameraOptions) -> Camera {
+        let zoom = settings.default_zoom.min(settings.zoom_levels.len() - 1);
+        Camera {
+            settings: settings,
+            zoom: zoom,
+            pixel_perfect: false,
+            origin: [0.0; 2],
+        }
+    }
+
+    /// Returns the options that the camera frames the map by.
+    pub fn options(&self) -> &CameraOptions {
+        &self.settings
+    }
+
+    /// Returns the width and height of a tile on the screen.
+    pub fn tile_size(&self) -> f64 {
+        let size = self.settings.tile_size * self.settings.zoom_levels[self.zoom];
+        if self.pixel_perfect {
+            (size / SPRITE_SIZE).round().max(1.0) * SPRITE_SIZE
+        } else {
+            size
+        }
+    }
+
+    /// Returns whether tiles are kept to whole multiples of the sprite size
+    /// and drawn on whole pixels.
+    pub fn pixel_perfect(&self) -> bool {
+        self.pixel_perfect
+    }
+
+    /// Sets whether tiles are kept to whole multiples of the sprite size and
+    /// drawn on whole pixels, so that sprites aren't blurred or unevenly
+    /// stretched.
+    pub fn set_pixel_perfect(&mut self, pixel_perfect: bool) {
+        self.pixel_perfect = pixel_perfect;
+    }
+
+    /// Zooms in by one level, if the camera isn't already as close as it goes.
+    pub fn zoom_in(&mut self) {
+        if self.zoom + 1 < self.settings.zoom_levels.len() {
+            self.zoom += 1;
+        }
+    }
+
+    /// Zooms out by one level, if the camera isn't already as far as it goes.
+    pub fn zoom_out(&mut self) {
+        self.zoom = self.zoom.saturating_sub(1);
+    }
+
+    /// Moves the camera so that the target is within the deadzone, then keeps
+    /// it from showing anything beyond the edges of the map.  Maps smaller
+    /// than the screen are centered on it.
+    pub fn follow(&mut self, target: [i32; 2], screen_size: [f64; 2], map_size: [usize; 2]) {
+        let tile_size = self.tile_size();
+        for axis in 0..2 {
+            let view = screen_size[axis] / tile_size;
+            let map = map_size[axis] as f64;
+            let deadzone = self.settings.deadzone[axis].min(view);
+            let target = target[axis] as f64 + 0.5;
+
+            // keep the target within the deadzone around the middle of the view
+            let low = self.origin[axis] + (view - deadzone) / 2.0;
+            let high = low + deadzone;
+            if target < low {
+                self.origin[axis] -= low - target;
+            } else if target > high {
+                self.origin[axis] += target - high;
+            }
+
+            self.origin[axis] = if map <= view {
+                (map - view) / 2.0
+            } else {
+                self.origin[axis].max(0.0).min(map - view)
+            };
+        }
+    }
+
+    /// Moves the camera straight to the target, centering it on the screen
+    /// as far as the edges of the map allow.
+    pub fn center_on(&mut self, target: [i32; 2], screen_size: [f64; 2], map_size: [usize; 2]) {
+        let tile_size = self.tile_size();
+        for axis in 0..2 {
+            let view = screen_size[axis] / tile_size;
+            self.origin[axis] = target[axis] as f64 + 0.5 - view / 2.0;
+        }
+        self.follow(target, screen_size, map_size);
+    }
+
+    /// Returns the point on the screen where the provided map position,
+    /// measured in tiles, is drawn.
+    pub fn to_screen(&self, position: [f64; 2]) -> [f64; 2] {
+        let tile_size = self.tile_size();
+        let point = [
+            (position[0] - self.origin[0]) * tile_size,
+            (position[1] - self.origin[1]) * tile_size,
+        ];
+        if self.pixel_perfect {
+            [point[0].round(), point[1].round()]
+        } else {
+            point
+        }
+    }
+
+    /// Returns the rectangle on the screen covered by the tile at the
+    /// provided map position.
+    pub fn tile_rect(&self, position: [i32; 2]) -> [f64; 4] {
+        let corner = self.to_screen([position[0] as f64, position[1] as f64]);
+        let tile_size = self.tile_size();
+        [corner[0], corner[1], tile_size, tile_size]
+    }
+
+    /// Returns the map position of the tile under the provided point on the
+    /// screen.
+    pub fn to_map(&self, point: [f64; 2]) -> [i32; 2] {
+        let tile_size = self.tile_size();
+        [
+            (point[0] / tile_size + self.origin[0]).floor() as i32,
+            (point[1] / tile_size + self.origin[1]).floor() as i32,
+        ]
+    }
+
+    /// Returns the first and last map positions (inclusive) of the tiles that
+    /// are at least partly on a screen of the provided size.
+    pub fn visible_tiles(&self, screen_size: [f64; 2]) -> ([i32; 2], [i32; 2]) {
+        let first = self.to_map([0.0, 0.0]);
+        let last = self.to_map([screen_size[0] - 0.001, screen_size[1] - 0.001]);
+        (first, last)
+    }
+}
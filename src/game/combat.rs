@@ -0,0 +1,79 @@
+/// The health and fighting ability of an actor.
+#[derive(Debug, Clone)]
+pub struct Vitals {
+    /// The actor's current health.  The actor dies when this reaches zero.
+    pub health: i32,
+
+    /// The most health that the actor can have.
+    pub max_health: i32,
+
+    /// The amount of damage the actor deals when it attacks.
+    pub attack: i32,
+
+    /// The amount of damage the actor ignores when it is attacked.
+    pub defense: i32,
+}
+
+impl Vitals {
+    /// Creates and returns a new instance of the Vitals struct, at full health.
+    pub fn new(max_health: i32, attack: i32, defense: i32) -> Vitals {
+        Vitals {
+            health: max_health,
+            max_health: max_health,
+            attack: attack,
+            defense: defense,
+        }
+    }
+
+    /// Reduces health by the damage dealt by an attack of the given strength,
+    /// returning the amount of damage taken.  Every attack deals at least
+    /// one point of damage.
+    pub fn take_hit(&mut self, attack: i32) -> i32 {
+        let damage = (attack - self.defense).max(1);
+        self.health -= damage;
+        damage
+    }
+
+    /// Reduces health by the provided amount, ignoring defense.
+    pub fn lose_health(&mut self, amount: i32) {
+        self.health -= amount;
+    }
+
+    /// Restores health by the provided amount, up to the actor's maximum.
+    pub fn heal(&mut self, amount: i32) {
+        self.health = (self.health + amount).min(self.max_health);
+    }
+
+    /// Returns a value indicating whether the actor has run out of health.
+    pub fn is_dead(&self) -> bool {
+        self.health <= 0
+    }
+}
+
+/// A way of attacking from a distance, such as a sling or a thrown knife.
+#[derive(Debug, Clone)]
+pub struct RangedAttack {
+    /// The name of the weapon or item used to make the attack.
+    pub name: String,
+
+    /// The farthest that the projectile can travel, in tiles.
+    pub range: u32,
+
+    /// The amount of damage the projectile deals when it hits.
+    pub attack: i32,
+
+    /// The color used to draw the projectile in flight.
+    pub color: [f32; 4],
+}
+
+impl RangedAttack {
+    /// Creates and returns a new instance of the RangedAttack struct.
+    pub fn new(name: &str, range: u32, attack: i32, color: [f32; 4]) -> RangedAttack {
+        RangedAttack {
+            name: String::from(name),
+            range: range,
+            attack: attack,
+            color: color,
+        }
+    }
+}
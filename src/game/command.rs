@@ -0,0 +1,102 @@
+use super::actor::ActorType;
+use super::MovementDirection;
+
+/// The number of ticks waited when the player asks to wait for a while.
+pub const LONG_WAIT_TURNS: u32 = 10;
+
+/// The ranged weapons that the player can take aim with.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Weapon {
+    /// The player's sling, which never runs out of stones.
+    Sling,
+
+    /// A throwing knife, used up when thrown.
+    Knife,
+}
+
+/// Something that the player has asked the game to do.  Commands may come
+/// from the keyboard, the mouse, or anything else able to drive the game,
+/// and are carried out by the GameController.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Command {
+    /// Move a single step in the contained direction, attacking whoever is
+    /// standing there.
+    Move(MovementDirection),
+
+    /// Let a tick pass without doing anything.
+    Wait,
+
+    /// Pick up whatever is lying at the player's feet.
+    Pickup,
+
+    /// Start choosing a target for the contained weapon.
+    Aim(Weapon),
+
+    /// Move the aiming cursor a single step in the contained direction.
+    MoveCursor(MovementDirection),
+
+    /// Move the aiming cursor to the next visible enemy.
+    CycleTarget,
+
+    /// Launch the projectile being aimed at whatever the cursor is on.
+    Release,
+
+    /// Stop aiming without attacking.
+    CancelAim,
+
+    /// Spawn an actor of the contained type.
+    Spawn(ActorType),
+
+    /// Spawn a randomly chosen scripted actor.
+    SpawnRandom,
+
+    /// Show or hide the message log.
+    ToggleMessages,
+
+    /// Start exploring automatically.
+    Explore,
+
+    /// Head toward the contained position, or attack whoever is there if
+    /// they are standing next to the player.
+    TravelTo([i32; 2]),
+
+    /// Keep moving in the contained direction until something interesting
+    /// happens or something gets in the way.
+    Run(MovementDirection),
+
+    /// Wait until healed, or until something interesting happens.
+    Rest,
+
+    /// Wait for the contained number of ticks, or until something
+    /// interesting happens.
+    WaitTurns(u32),
+}
+
+impl Command {
+    /// Returns a value indicating whether the command is only meaningful
+    /// while the player is choosing a target.
+    pub fn is_aiming(&self) -> bool {
+        match *self {
+            Command::MoveCursor(_) |
+            Command::CycleTarget |
+            Command::Release |
+            Command::CancelAim => true,
+            _ => false,
+        }
+    }
+
+    /// Returns a value indicating whether the command is written to replays.
+    /// Commands that only act by giving other commands, such as travelling
+    /// somewhere, are left out so that their steps aren't taken twice.
+    pub fn is_recorded(&self) -> bool {
+        match *self {
+            Command::SpawnRandom |
+            Command::Explore |
+            Command::TravelTo(_) |
+            Command::Run(_) |
+            Command::Rest |
+            Command::WaitTurns(_) => false,
+            _ => true,
+        }
+    }
+}
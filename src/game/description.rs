@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use find_folder::Search;
+use serde_json;
+use super::Tile;
+
+const DESCRIPTIONS_DIR: &'static str = "assets/";
+const DESCRIPTIONS_FILE: &'static str = "descriptions.json";
+
+/// The text shown when examining something that has no description.
+const DEFAULT_DESCRIPTION: &'static str = "You see nothing special.";
+
+/// Descriptions of the things that the player can examine, keyed by name.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct Descriptions {
+    tiles: HashMap<String, String>,
+    actors: HashMap<String, String>,
+    entities: HashMap<String, String>,
+    items: HashMap<String, String>,
+}
+
+/// What the player learns by examining a position in the map.
+#[derive(Debug, Clone)]
+pub struct Examination {
+    /// The position being examined.
+    pub position: [i32; 2],
+
+    /// Lines of text describing what is at the position, most important first.
+    pub lines: Vec<String>,
+}
+
+impl Descriptions {
+    /// Creates and returns a new instance of the Descriptions struct, reading
+    /// its contents from the assets folder.
+    pub fn new() -> Descriptions {
+        Descriptions::load().unwrap_or_else(|why| {
+            error!("{}  Nothing will have a description.", why);
+            Descriptions::default()
+        })
+    }
+
+    /// Returns the description of the provided tile.
+    pub fn tile(&self, tile: &Tile) -> &str {
+        Descriptions::lookup(&self.tiles, tile.description_key())
+    }
+
+    /// Returns the description of the actor type with the provided name.
+    pub fn actor(&self, name: &str) -> &str {
+        Descriptions::lookup(&self.actors, name)
+    }
+
+    /// Returns the description of the entity with the provided name.
+    pub fn entity(&self, name: &str) -> &str {
+        Descriptions::lookup(&self.entities, name)
+    }
+
+    /// Returns the description of the item with the provided name.
+    pub fn item(&self, name: &str) -> &str {
+        Descriptions::lookup(&self.items, name)
+    }
+
+    fn lookup<'a>(descriptions: &'a HashMap<String, String>, key: &str) -> &'a str {
+        descriptions.get(key).map_or(
+            DEFAULT_DESCRIPTION,
+            |d| d.as_str(),
+        )
+    }
+
+    fn load() -> Result<Descriptions, String> {
+        let assets = Search::ParentsThenKids(3, 3)
+            .for_folder(DESCRIPTIONS_DIR)
+            .map_err(|why| format!("Unable to find assets folder: {:?}", why))?;
+        let file = File::open(assets.join(DESCRIPTIONS_FILE)).map_err(|why| {
+            format!("Unable to open descriptions file: {:?}", why)
+        })?;
+        serde_json::from_reader(BufReader::new(file)).map_err(|why| {
+            format!("Unable to parse descriptions file: {}", why)
+        })
+    }
+}
@@ -8,5 +8,9 @@ use super::actor::ActorInfo;
 /// Examples include static props and areas of effect.
 pub trait Entity: mopa::Any + Movable + Positioned + Drawable {
     fn on_create(&mut self, map: &Map, actors: &[ActorInfo]);
+
+    /// Returns the name used to describe the entity, and to look up its
+    /// description.
+    fn name(&self) -> String;
 }
 mopafy!(Entity);
@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use find_folder::Search;
+use serde_json;
+
+const FACTIONS_DIR: &'static str = "assets/";
+const FACTIONS_FILE: &'static str = "factions.json";
+
+/// The name of the faction that the player belongs to.
+pub const PLAYER_FACTION: &'static str = "player";
+
+/// The player's starting reputation with factions they are allied with.
+const ALLY_REPUTATION: i32 = 20;
+
+/// The player's starting reputation with factions that are hostile to them.
+const HOSTILE_REPUTATION: i32 = -20;
+
+/// The reputation at or above which a faction is allied with the player.
+const ALLY_THRESHOLD: i32 = 10;
+
+/// The reputation at or below which a faction is hostile to the player.
+const HOSTILE_THRESHOLD: i32 = -5;
+
+/// The reputation lost with a faction whenever the player attacks one of its members.
+const ATTACK_PENALTY: i32 = 10;
+
+/// Describes how the members of two factions feel about each other.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Relationship {
+    /// The factions help each other.
+    Ally,
+
+    /// The factions leave each other alone.
+    Neutral,
+
+    /// The factions attack each other on sight.
+    Hostile,
+}
+
+/// A single entry in the relationship matrix.
+#[derive(Deserialize)]
+struct RelationshipEntry {
+    factions: [String; 2],
+    relationship: Relationship,
+}
+
+/// The contents of the factions file.
+#[derive(Deserialize)]
+struct FactionSettings {
+    default_relationship: Relationship,
+    relationships: Vec<RelationshipEntry>,
+}
+
+/// Stores how each faction feels about the others, as well as the player's
+/// reputation with each faction.
+pub struct Factions {
+    default_relationship: Relationship,
+    relationships: HashMap<(String, String), Relationship>,
+    reputation: HashMap<String, i32>,
+}
+
+impl Factions {
+    /// Creates and returns a new instance of the Factions struct, reading the
+    /// relationship matrix from the assets folder.
+    pub fn new() -> Factions {
+        let settings = Factions::load_settings().unwrap_or_else(|why| {
+            error!("{}  All factions will be neutral.", why);
+            FactionSettings {
+                default_relationship: Relationship::Neutral,
+                relationships: Vec::new(),
+            }
+        });
+        Factions::from_settings(settings)
+    }
+
+    fn from_settings(settings: FactionSettings) -> Factions {
+        let mut factions = Factions {
+            default_relationship: settings.default_relationship,
+            relationships: HashMap::<(String, String), Relationship>::new(),
+            reputation: HashMap::<String, i32>::new(),
+        };
+        for entry in settings.relationships {
+            let (a, b) = (entry.factions[0].clone(), entry.factions[1].clone());
+            if a == PLAYER_FACTION {
+                factions.set_initial_reputation(b, entry.relationship);
+            } else if b == PLAYER_FACTION {
+                factions.set_initial_reputation(a, entry.relationship);
+            } else {
+                factions.relationships.insert((a.clone(), b.clone()), entry.relationship);
+                factions.relationships.insert((b, a), entry.relationship);
+            }
+        }
+        factions
+    }
+
+    /// Returns how the members of the two named factions feel about each other.
+    pub fn relationship(&self, a: &str, b: &str) -> Relationship {
+        if a == b {
+            Relationship::Ally
+        } else if a == PLAYER_FACTION {
+            self.player_relationship(b)
+        } else if b == PLAYER_FACTION {
+            self.player_relationship(a)
+        } else {
+            self.relationships
+                .get(&(String::from(a), String::from(b)))
+                .cloned()
+                .unwrap_or(self.default_relationship)
+        }
+    }
+
+    /// Returns the player's reputation with the named faction.
+    pub fn reputation(&self, faction: &str) -> i32 {
+        match self.reputation.get(faction) {
+            Some(reputation) => *reputation,
+            None => Factions::reputation_for(self.default_relationship),
+        }
+    }
+
+    /// Lowers the player's reputation with the named faction in response to
+    /// the player attacking one of its members.  Returns the new relationship
+    /// if it changed as a result.
+    pub fn player_attacked(&mut self, faction: &str) -> Option<Relationship> {
+        if faction == PLAYER_FACTION {
+            return None;
+        }
+        let before = self.player_relationship(faction);
+        let reputation = self.reputation(faction) - ATTACK_PENALTY;
+        self.reputation.insert(String::from(faction), reputation);
+        let after = self.player_relationship(faction);
+        if before != after { Some(after) } else { None }
+    }
+
+    fn player_relationship(&self, faction: &str) -> Relationship {
+        let reputation = self.reputation(faction);
+        if reputation >= ALLY_THRESHOLD {
+            Relationship::Ally
+        } else if reputation <= HOSTILE_THRESHOLD {
+            Relationship::Hostile
+        } else {
+            Relationship::Neutral
+        }
+    }
+
+    fn set_initial_reputation(&mut self, faction: String, relationship: Relationship) {
+        self.reputation.insert(
+            faction,
+            Factions::reputation_for(relationship),
+        );
+    }
+
+    fn reputation_for(relationship: Relationship) -> i32 {
+        match relationship {
+            Relationship::Ally => ALLY_REPUTATION,
+            Relationship::Neutral => 0,
+            Relationship::Hostile => HOSTILE_REPUTATION,
+        }
+    }
+
+    fn load_settings() -> Result<FactionSettings, String> {
+        let assets = Search::ParentsThenKids(3, 3)
+            .for_folder(FACTIONS_DIR)
+            .map_err(|why| format!("Unable to find assets folder: {:?}", why))?;
+        let file = File::open(assets.join(FACTIONS_FILE)).map_err(|why| {
+            format!("Unable to open factions file: {:?}", why)
+        })?;
+        serde_json::from_reader(BufReader::new(file)).map_err(|why| {
+            format!("Unable to parse factions file: {}", why)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn factions() -> Factions {
+        let settings = serde_json::from_str(
+            r#"{
+                "default_relationship": "Neutral",
+                "relationships": [
+                    { "factions": ["soldiers", "bandits"], "relationship": "Hostile" },
+                    { "factions": ["player", "soldiers"], "relationship": "Ally" },
+                    { "factions": ["bandits", "player"], "relationship": "Hostile" }
+                ]
+            }"#,
+        ).unwrap();
+        Factions::from_settings(settings)
+    }
+
+    #[test]
+    fn relationships_are_symmetric() {
+        let factions = factions();
+        assert_eq!(factions.relationship("soldiers", "bandits"), Relationship::Hostile);
+        assert_eq!(factions.relationship("bandits", "soldiers"), Relationship::Hostile);
+        assert_eq!(factions.relationship("player", "soldiers"), Relationship::Ally);
+        assert_eq!(factions.relationship("soldiers", "player"), Relationship::Ally);
+        assert_eq!(factions.relationship("bandits", "player"), Relationship::Hostile);
+    }
+
+    #[test]
+    fn unlisted_factions_use_the_default() {
+        let factions = factions();
+        assert_eq!(factions.relationship("soldiers", "merchants"), Relationship::Neutral);
+        assert_eq!(factions.relationship("player", "merchants"), Relationship::Neutral);
+        assert_eq!(factions.relationship("merchants", "merchants"), Relationship::Ally);
+        assert_eq!(factions.reputation("merchants"), 0);
+    }
+
+    #[test]
+    fn attacking_a_faction_sours_it() {
+        let mut factions = factions();
+        assert_eq!(factions.reputation("soldiers"), ALLY_REPUTATION);
+
+        // the first attack isn't enough to lose an ally
+        assert_eq!(factions.player_attacked("soldiers"), None);
+        assert_eq!(factions.player_attacked("soldiers"), Some(Relationship::Neutral));
+        assert_eq!(factions.player_attacked("soldiers"), Some(Relationship::Hostile));
+        assert_eq!(factions.player_attacked("soldiers"), None);
+        assert_eq!(factions.relationship("player", "soldiers"), Relationship::Hostile);
+
+        // other factions feel the same about each other as before
+        assert_eq!(factions.relationship("soldiers", "bandits"), Relationship::Hostile);
+    }
+
+    #[test]
+    fn attacking_the_player_faction_changes_nothing() {
+        let mut factions = factions();
+        assert_eq!(factions.player_attacked(PLAYER_FACTION), None);
+        assert_eq!(factions.relationship(PLAYER_FACTION, PLAYER_FACTION), Relationship::Ally);
+    }
+}
@@ -0,0 +1,487 @@
+//! Things that the player spends many turns doing, such as travelling,
+//! exploring, running, resting, and waiting, one step at a time.
+
+use std::collections::{HashSet, VecDeque};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+use game::{self, Command, Message, MovementDirection};
+use game::message::MessageType;
+use super::GameController;
+
+/// The kinds of things that the player can spend many turns doing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ActivityKind {
+    /// Following a route to a chosen destination.
+    Travel,
+
+    /// Exploring, planning a new route whenever the current one has been
+    /// followed.
+    Explore,
+
+    /// Running in the contained direction until something gets in the way.
+    Run(MovementDirection),
+
+    /// Resting until healed.
+    Rest,
+
+    /// Waiting for the contained number of turns.
+    Wait(u32),
+}
+
+impl ActivityKind {
+    /// Returns the time between each step of the activity, given the time
+    /// between each step the player takes while travelling.  Staying put
+    /// happens as quickly as possible, since there is nothing to watch.
+    fn step_interval(&self, travel_step_millis: u64) -> Duration {
+        match *self {
+            ActivityKind::Rest |
+            ActivityKind::Wait(_) => Duration::from_millis(0),
+            _ => Duration::from_millis(travel_step_millis),
+        }
+    }
+}
+
+/// Something that the player is doing over many turns, one step at a time,
+/// until they are done or something interrupts them.
+pub struct Activity {
+    kind: ActivityKind,
+
+    /// The route being followed, when travelling or exploring.
+    path: VecDeque<[i32; 2]>,
+
+    /// The direction to step off the edge of the map in once the path has
+    /// been followed, if the route leads into the neighboring map.
+    exit: Option<MovementDirection>,
+
+    /// The direction in which the player last stepped off the edge of a map.
+    last_exit: Option<MovementDirection>,
+
+    /// The enemies that were already in view when the activity began.
+    known_enemies: HashSet<Uuid>,
+
+    /// The items that were already in view when the activity began.
+    known_items: HashSet<Uuid>,
+
+    /// The number of messages that had been received when the activity began.
+    message_count: usize,
+
+    last_step: Instant,
+}
+
+impl GameController {
+    /// Attacks whoever is at the target if they are next to the player, or
+    /// otherwise sets off toward it.
+    pub(super) fn travel_to(&mut self, target: [i32; 2]) {
+        if self.player_is_aiming() {
+            return;
+        }
+        let origin = self.current_player_position();
+        if target == origin {
+            return;
+        }
+
+        // clicking on someone next to the player attacks them
+        let actors = self.visible_actor_info();
+        if actors.iter().any(|a| a.position == target) &&
+            game::chebyshev_distance(origin, target) == 1
+        {
+            if let Some(dir) = game::direction_toward(origin, target) {
+                self.execute(Command::Move(dir));
+            }
+            return;
+        }
+
+        // otherwise head there, but only through places the player knows about
+        if !self.explored.contains(&target) {
+            return;
+        }
+        let path = {
+            let explored = &self.explored;
+            self.state.map.find_path(origin, target, |p| {
+                explored.contains(&p) && (p == target || !actors.iter().any(|a| a.position == p))
+            })
+        };
+        if let Some(path) = path {
+            self.begin_activity(ActivityKind::Travel, path);
+        }
+    }
+
+    /// Starts exploring, unless there are enemies in view.
+    pub(super) fn auto_explore(&mut self) {
+        if self.player_is_aiming() {
+            return;
+        }
+        if self.visible_enemies().is_empty() {
+            self.begin_activity(ActivityKind::Explore, Vec::new());
+        } else {
+            self.state.messages.push_back(Message {
+                contents: String::from("You can't explore with enemies in view."),
+                message_type: MessageType::Normal,
+            });
+        }
+    }
+
+    /// Starts running in the provided direction.
+    pub(super) fn run(&mut self, dir: MovementDirection) {
+        if !self.player_is_aiming() {
+            self.begin_activity(ActivityKind::Run(dir), Vec::new());
+        }
+    }
+
+    /// Starts resting until the player is healed, unless there are enemies
+    /// in view.
+    pub(super) fn rest(&mut self) {
+        if self.player_is_aiming() {
+            return;
+        }
+        let refusal = if !self.visible_enemies().is_empty() {
+            Some("You can't rest with enemies in view.")
+        } else if self.player_is_healed() {
+            Some("You are already at full health.")
+        } else {
+            None
+        };
+        match refusal {
+            Some(refusal) => {
+                self.state.messages.push_back(Message {
+                    contents: String::from(refusal),
+                    message_type: MessageType::Normal,
+                });
+            }
+            None => self.begin_activity(ActivityKind::Rest, Vec::new()),
+        }
+    }
+
+    /// Starts waiting for the provided number of turns, unless there are
+    /// enemies in view.
+    pub(super) fn wait_turns(&mut self, turns: u32) {
+        if self.player_is_aiming() || turns == 0 {
+            return;
+        }
+        if self.visible_enemies().is_empty() {
+            self.begin_activity(ActivityKind::Wait(turns), Vec::new());
+        } else {
+            self.state.messages.push_back(Message {
+                contents: String::from("You can't wait with enemies in view."),
+                message_type: MessageType::Normal,
+            });
+        }
+    }
+
+    fn begin_activity(&mut self, kind: ActivityKind, path: Vec<[i32; 2]>) {
+        self.activity = Some(Activity {
+            kind: kind,
+            path: path.into_iter().collect(),
+            exit: None,
+            last_exit: None,
+            known_enemies: HashSet::<Uuid>::new(),
+            known_items: HashSet::<Uuid>::new(),
+            message_count: 0,
+            last_step: Instant::now() - kind.step_interval(self.options.travel_step_millis),
+        });
+        self.reset_interruptions();
+    }
+
+    /// Takes note of everything that the player already knows about, so that
+    /// only new enemies, items, and messages interrupt their activity.
+    fn reset_interruptions(&mut self) {
+        let enemies = self.visible_enemies();
+        let items = self.visible_items();
+        let message_count = self.state.messages.len();
+        if let Some(ref mut activity) = self.activity {
+            activity.known_enemies = enemies;
+            activity.known_items = items;
+            activity.message_count = message_count;
+        }
+    }
+
+    /// Carries on exploring in the map that the player has just arrived in,
+    /// or stops whatever else they were doing.
+    pub(super) fn continue_into_new_map(&mut self) {
+        match self.activity.take() {
+            Some(mut activity) => {
+                if activity.kind == ActivityKind::Explore {
+                    activity.path.clear();
+                    activity.exit = None;
+                    self.activity = Some(activity);
+                    self.reset_interruptions();
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Carries on with whatever the player is doing when it is time for them
+    /// to take another step, given whether a key or button was pressed and
+    /// whether the game is being updated.  Returns a value indicating whether
+    /// the input was used up by the activity.
+    pub(super) fn update_activity(&mut self, pressed: bool, updating: bool) -> bool {
+        if self.activity.is_none() {
+            return false;
+        }
+
+        // any key or button stops the activity
+        if pressed {
+            self.activity = None;
+            return true;
+        }
+
+        let ready = match self.activity {
+            Some(ref activity) => {
+                let interval = activity.kind.step_interval(self.options.travel_step_millis);
+                updating && activity.last_step.elapsed() >= interval
+            }
+            None => false,
+        };
+        if ready {
+            self.activity_step();
+        }
+        false
+    }
+
+    /// Takes the next step of the player's activity, stopping if an enemy,
+    /// item, or message has appeared.
+    fn activity_step(&mut self) {
+        let visible_enemies = self.visible_enemies();
+        let visible_items = self.visible_items();
+        let message_count = self.state.messages.len();
+        let (interrupted, kind) = match self.activity.as_mut() {
+            Some(activity) => {
+                activity.last_step = Instant::now();
+                (
+                    visible_enemies.iter().any(|id| !activity.known_enemies.contains(id)) ||
+                        visible_items.iter().any(|id| !activity.known_items.contains(id)) ||
+                        message_count != activity.message_count,
+                    activity.kind,
+                )
+            }
+            None => return,
+        };
+        if interrupted {
+            self.activity = None;
+            return;
+        }
+
+        match kind {
+            ActivityKind::Travel |
+            ActivityKind::Explore => self.travel_step(),
+            ActivityKind::Run(dir) => self.run_step(dir),
+            ActivityKind::Rest |
+            ActivityKind::Wait(_) => self.wait_step(),
+        }
+    }
+
+    /// Takes the next step along the player's route, stopping if anything
+    /// has gotten in the way.
+    fn travel_step(&mut self) {
+        let needs_route = self.activity.as_ref().map_or(false, |a| {
+            a.kind == ActivityKind::Explore && a.path.is_empty() && a.exit.is_none()
+        });
+        if needs_route {
+            self.plan_exploration();
+        }
+
+        let origin = self.current_player_position();
+        let step = match self.activity.as_mut() {
+            Some(travel) => {
+                match travel.path.pop_front() {
+                    Some(next) if game::chebyshev_distance(origin, next) == 1 => {
+                        game::direction_toward(origin, next).map(|dir| (dir, Some(next)))
+                    }
+                    Some(_) => None,
+                    None => {
+                        // step off the edge of the map into the next one over
+                        travel.last_exit = travel.exit;
+                        travel.exit.take().map(|dir| (dir, None))
+                    }
+                }
+            }
+            None => return,
+        };
+
+        match step {
+            Some((dir, expected)) => {
+                self.execute(Command::Move(dir));
+                let finished = self.activity.as_ref().map_or(true, |t| {
+                    t.kind != ActivityKind::Explore && t.path.is_empty()
+                });
+                let blocked = expected.map_or(false, |next| self.current_player_position() != next);
+                if finished || blocked {
+                    self.activity = None;
+                }
+            }
+            None => {
+                self.activity = None;
+            }
+        }
+    }
+
+    /// Takes another step in the direction that the player is running,
+    /// stopping once something is in the way.  Running never attacks.
+    fn run_step(&mut self, dir: MovementDirection) {
+        let origin = self.current_player_position();
+        let destination = game::map_direction_to_position(origin, &dir, 1);
+        let occupied = self.visible_actor_info().iter().any(|a| a.position == destination);
+        let blocked = match self.state.map.get_at(destination) {
+            Some(tile) => !tile.passable() || occupied,
+            // stepping off the edge leads into the next map, where running stops
+            None => false,
+        };
+        if blocked {
+            self.activity = None;
+            return;
+        }
+
+        self.execute(Command::Move(dir));
+        if self.current_player_position() != destination {
+            self.activity = None;
+        }
+    }
+
+    /// Lets another turn pass while the player rests or waits, stopping once
+    /// they are healed or have waited long enough.
+    fn wait_step(&mut self) {
+        self.execute(Command::Wait);
+        let done = match self.activity.as_mut() {
+            Some(activity) => {
+                match activity.kind {
+                    ActivityKind::Wait(ref mut turns) => {
+                        *turns -= 1;
+                        *turns == 0
+                    }
+                    _ => false,
+                }
+            }
+            None => return,
+        };
+        let rested = self.activity.as_ref().map_or(false, |a| a.kind == ActivityKind::Rest) &&
+            self.player_is_healed();
+        if rested {
+            self.state.messages.push_back(Message {
+                contents: String::from("You feel rested."),
+                message_type: MessageType::Normal,
+            });
+        }
+        if done || rested {
+            self.activity = None;
+        }
+    }
+
+    /// Plans a route to the nearest place that the player has yet to see, or
+    /// failing that, to an edge of the map so that they can explore the
+    /// neighboring one.  Stops exploring if there is nowhere left to go.
+    fn plan_exploration(&mut self) {
+        let origin = self.current_player_position();
+        let occupied: Vec<[i32; 2]> = self.visible_actor_info().iter().map(|a| a.position).collect();
+        let (width, height) = (self.state.map.width() as i32, self.state.map.height() as i32);
+        let last_exit = self.activity.as_ref().and_then(|t| t.last_exit);
+
+        let route = {
+            let explored = &self.explored;
+            let map = &self.state.map;
+            let can_enter = |p: [i32; 2]| explored.contains(&p) && !occupied.contains(&p);
+            let unexplored_nearby = |p: [i32; 2]| {
+                (-1..2).any(|dx| {
+                    (-1..2).any(|dy| {
+                        let neighbor = [p[0] + dx, p[1] + dy];
+                        map.get_at(neighbor).is_some() && !explored.contains(&neighbor)
+                    })
+                })
+            };
+            // leave by any edge other than the one the player came in through
+            let exit_from = |p: [i32; 2]| {
+                GameController::exit_direction(p, width, height).filter(|dir| {
+                    Some(dir.opposite()) != last_exit
+                })
+            };
+
+            match map.path_to_nearest(origin, &can_enter, &unexplored_nearby) {
+                Some(path) => Some((path, None)),
+                None => {
+                    match exit_from(origin) {
+                        Some(dir) => Some((Vec::new(), Some(dir))),
+                        None => {
+                            map.path_to_nearest(origin, &can_enter, |p| exit_from(p).is_some())
+                                .map(|path| {
+                                    let exit = path.last().and_then(|p| exit_from(*p));
+                                    (path, exit)
+                                })
+                        }
+                    }
+                }
+            }
+        };
+
+        match route {
+            Some((path, exit)) => {
+                if let Some(ref mut activity) = self.activity {
+                    activity.path = path.into_iter().collect();
+                    activity.exit = exit;
+                }
+            }
+            None => {
+                self.activity = None;
+                self.state.messages.push_back(Message {
+                    contents: String::from("There is nowhere left to explore."),
+                    message_type: MessageType::Normal,
+                });
+            }
+        }
+    }
+
+    /// Returns the direction that leads off the edge of the map from the
+    /// provided position, if it is on an edge.
+    fn exit_direction(position: [i32; 2], width: i32, height: i32) -> Option<MovementDirection> {
+        if position[0] == 0 {
+            Some(MovementDirection::Left)
+        } else if position[0] == width - 1 {
+            Some(MovementDirection::Right)
+        } else if position[1] == 0 {
+            Some(MovementDirection::Up)
+        } else if position[1] == height - 1 {
+            Some(MovementDirection::Down)
+        } else {
+            None
+        }
+    }
+
+    /// Returns a value indicating whether the player is at full health.
+    fn player_is_healed(&self) -> bool {
+        self.state
+            .actors
+            .get(&self.state.player_id)
+            .and_then(|p| p.vitals())
+            .map_or(true, |v| v.health >= v.max_health)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::cross_east_edge;
+
+    #[test]
+    fn exploring_carries_on_into_the_next_map() {
+        let mut controller = GameController::with_seed(42);
+        controller.begin_activity(ActivityKind::Explore, Vec::new());
+        if let Some(ref mut activity) = controller.activity {
+            activity.last_exit = Some(MovementDirection::Right);
+        }
+        cross_east_edge(&mut controller);
+        assert!(controller.activity.as_ref().map_or(false, |a| a.kind == ActivityKind::Explore));
+
+        // leave the newcomers behind, so that only the map can stop the player
+        let player_id = controller.state.player_id;
+        controller.state.actors.retain(|id, _| *id == player_id);
+        let entry = controller.current_player_position();
+        for _ in 0..3 {
+            controller.travel_step();
+            controller.advance();
+            assert!(controller.activity.is_some(), "exploring stopped at {:?}", entry);
+        }
+        assert!(controller.current_player_position() != entry);
+        assert!(!controller.state.messages.iter().any(|m| {
+            m.contents == "There is nowhere left to explore."
+        }));
+    }
+}
@@ -0,0 +1,319 @@
+//! Attacks in melee and from a distance, and the damage, status effects,
+//! and deaths that follow from them.
+
+use uuid::Uuid;
+use game::{self, Message, Noise};
+use game::actor::ActorInfo;
+use game::combat::RangedAttack;
+use game::faction::Relationship;
+use game::message::MessageType;
+use game::projectile::{self, ProjectileAnimation};
+use game::status_effect::{EffectEvent, EffectKind, StatusEffect};
+use status::ControllerStatus;
+use super::{GameController, Visibility};
+
+const COMBAT_LOUDNESS: u32 = 10;
+
+impl GameController {
+    /// Has one actor attack another in melee, removing the target if it dies.
+    pub(super) fn resolve_attack(&mut self, attacker_id: Uuid, target_id: Uuid) {
+        let (attacker, attack, effects) = match self.state.actors.get(&attacker_id) {
+            Some(actor) => {
+                match actor.vitals() {
+                    Some(vitals) => {
+                        (ActorInfo::new(actor.as_ref()), vitals.attack, actor.attack_effects())
+                    }
+                    None => return,
+                }
+            }
+            None => return,
+        };
+
+        // the target may have moved out of reach since the attack was decided upon
+        match self.state.actors.get(&target_id) {
+            Some(target) if game::chebyshev_distance(
+                attacker.position,
+                target.current_position(),
+            ) <= 1 => {}
+            _ => return,
+        }
+
+        self.strike(&attacker, attack, effects, target_id, None);
+    }
+
+    /// Has an actor launch a projectile toward the target position, striking
+    /// whoever is in its way.
+    pub(super) fn resolve_shot(
+        &mut self,
+        shooter_id: Uuid,
+        target: [i32; 2],
+        attack: RangedAttack,
+    ) {
+        let shooter = match self.state.actors.get(&shooter_id) {
+            Some(actor) => ActorInfo::new(actor.as_ref()),
+            None => return,
+        };
+        let others: Vec<ActorInfo> = self.state
+            .actors
+            .values()
+            .filter(|a| a.id() != shooter_id)
+            .map(|a| ActorInfo::new(a.as_ref()))
+            .collect();
+
+        let path = projectile::trace(
+            &self.state.map,
+            shooter.position,
+            target,
+            attack.range,
+            &others,
+        );
+        self.projectiles.push(ProjectileAnimation::new(
+            shooter.position,
+            &path,
+            attack.color,
+        ));
+
+        match path.hit {
+            Some(target_id) => {
+                self.strike(
+                    &shooter,
+                    attack.attack,
+                    Vec::new(),
+                    target_id,
+                    Some(attack.name.as_str()),
+                )
+            }
+            None => {
+                if shooter.id == self.state.player_id {
+                    self.state.messages.push_back(Message {
+                        contents: format!("Your {} hits nothing.", attack.name),
+                        message_type: MessageType::Background,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Deals damage to the target of an attack and tells the player about it,
+    /// removing the target if it dies.  `weapon` names the projectile used,
+    /// if the attack was made from a distance.
+    fn strike(
+        &mut self,
+        attacker: &ActorInfo,
+        attack: i32,
+        effects: Vec<StatusEffect>,
+        target_id: Uuid,
+        weapon: Option<&str>,
+    ) {
+        let (target, damage, killed, effect_events) = match self.state.actors.get_mut(&target_id) {
+            Some(actor) => {
+                let (damage, killed) = match actor.vitals_mut() {
+                    Some(vitals) => (vitals.take_hit(attack), vitals.is_dead()),
+                    None => return,
+                };
+                actor.on_attacked(attacker, damage);
+
+                // the attacker's weapon may leave a lasting mark
+                let mut effect_events = Vec::<EffectEvent>::new();
+                if !killed {
+                    if let Some(target_effects) = actor.status_effects_mut() {
+                        for effect in effects {
+                            effect_events.extend(target_effects.apply(effect));
+                        }
+                    }
+                }
+                (ActorInfo::new(actor.as_ref()), damage, killed, effect_events)
+            }
+            None => return,
+        };
+
+        let player_id = self.state.player_id;
+        let (subject, verb) = match weapon {
+            Some(weapon) if attacker.id == player_id => (format!("Your {}", weapon), "hits"),
+            Some(weapon) => {
+                (
+                    format!("{}'s {}", GameController::describe(attacker, true), weapon),
+                    "hits",
+                )
+            }
+            None if attacker.id == player_id => (GameController::describe(attacker, true), "hit"),
+            None => (GameController::describe(attacker, true), "hits"),
+        };
+        self.state.messages.push_back(Message {
+            contents: format!(
+                "{} {} {} for {} damage.",
+                subject,
+                verb,
+                GameController::describe(&target, false),
+                damage
+            ),
+            message_type: if target.id == player_id {
+                MessageType::Danger
+            } else {
+                MessageType::Normal
+            },
+        });
+        self.report_effects(&target, &effect_events);
+        self.record_hit(&target, damage);
+        self.noises.push(Noise::new(
+            target.position,
+            COMBAT_LOUDNESS,
+            Some(attacker.id),
+            "the sounds of fighting",
+        ));
+
+        // attacking a faction's members makes the faction like the player less
+        if attacker.id == player_id {
+            if let Some(relationship) = self.state.factions.player_attacked(
+                target.faction.as_str(),
+            )
+            {
+                let (feeling, message_type) = match relationship {
+                    Relationship::Ally => ("friendly", MessageType::Benefit),
+                    Relationship::Neutral => ("indifferent", MessageType::Normal),
+                    Relationship::Hostile => ("hostile", MessageType::Danger),
+                };
+                self.state.messages.push_back(Message {
+                    contents: format!("The {} are now {} toward you.", target.faction, feeling),
+                    message_type: message_type,
+                });
+            }
+        }
+
+        if killed {
+            self.kill(target_id);
+        }
+    }
+
+    /// Advances every actor's status effects by one tick, dealing any damage
+    /// they cause.
+    pub(super) fn tick_status_effects(&mut self) {
+        let mut reports = Vec::<(ActorInfo, Vec<EffectEvent>)>::new();
+        let mut hits = Vec::<(ActorInfo, i32)>::new();
+        let mut dead = Vec::<Uuid>::new();
+
+        for actor in self.state.actors.values_mut() {
+            let events = match actor.status_effects_mut() {
+                Some(effects) => effects.tick(),
+                None => continue,
+            };
+            if events.is_empty() {
+                continue;
+            }
+
+            let damage: i32 = events
+                .iter()
+                .map(|e| match *e {
+                    EffectEvent::Damaged(_, amount) => amount,
+                    _ => 0,
+                })
+                .sum();
+            if damage > 0 {
+                if let Some(vitals) = actor.vitals_mut() {
+                    vitals.lose_health(damage);
+                    if vitals.is_dead() {
+                        dead.push(actor.id());
+                    }
+                }
+                hits.push((ActorInfo::new(actor.as_ref()), damage));
+            }
+            reports.push((ActorInfo::new(actor.as_ref()), events));
+        }
+
+        for (info, events) in reports {
+            self.report_effects(&info, &events);
+        }
+        for (info, damage) in hits {
+            self.record_hit(&info, damage);
+        }
+        for id in dead {
+            self.kill(id);
+        }
+    }
+
+    /// Tells the player about changes to the status effects of the player,
+    /// or of any actor they can see.
+    fn report_effects(&mut self, actor: &ActorInfo, events: &[EffectEvent]) {
+        let is_player = actor.id == self.state.player_id;
+        if !is_player {
+            if let Visibility::Invisible = self.within_player_view(actor.position) {
+                return;
+            }
+        }
+
+        let subject = GameController::describe(actor, true);
+        let verb = if is_player { "are" } else { "is" };
+        for event in events {
+            let (contents, message_type) = match *event {
+                EffectEvent::Began(kind) => {
+                    (
+                        format!("{} {} {}.", subject, verb, kind.adjective()),
+                        GameController::effect_message_type(kind, is_player, true),
+                    )
+                }
+                EffectEvent::Ended(kind) => {
+                    (
+                        format!("{} {} no longer {}.", subject, verb, kind.adjective()),
+                        GameController::effect_message_type(kind, is_player, false),
+                    )
+                }
+                EffectEvent::Damaged(kind, amount) if is_player => {
+                    (
+                        format!("You take {} damage from {}.", amount, kind.noun()),
+                        MessageType::Danger,
+                    )
+                }
+                EffectEvent::Damaged(_, _) => continue,
+            };
+            self.state.messages.push_back(Message {
+                contents: contents,
+                message_type: message_type,
+            });
+        }
+    }
+
+    /// Returns the type of message used to report an effect beginning or
+    /// ending, depending on whether it is good news for the player.
+    fn effect_message_type(kind: EffectKind, is_player: bool, began: bool) -> MessageType {
+        if !is_player {
+            return MessageType::Normal;
+        }
+        let beneficial = kind == EffectKind::Hasted;
+        if beneficial == began {
+            MessageType::Benefit
+        } else {
+            MessageType::Danger
+        }
+    }
+
+    /// Removes a dead actor from the game, or ends the game if it was the
+    /// player.
+    fn kill(&mut self, id: Uuid) {
+        if id == self.state.player_id {
+            self.state.messages.push_back(Message {
+                contents: String::from("You have died."),
+                message_type: MessageType::Danger,
+            });
+            info!("The player has died, quitting now...");
+            self.status = Some(ControllerStatus::Quit);
+            return;
+        }
+
+        let actor_info: Vec<ActorInfo> = self.state
+            .actors
+            .values()
+            .map(|a| ActorInfo::new(a.as_ref()))
+            .collect();
+        if let Some(mut actor) = self.state.actors.remove(&id) {
+            actor.on_remove(&actor_info);
+            if let Some(messages) = actor.messages() {
+                self.state.messages.append(messages);
+            }
+            let description = GameController::describe(&ActorInfo::new(actor.as_ref()), true);
+            self.state.messages.push_back(Message {
+                contents: format!("{} dies.", description),
+                message_type: MessageType::Normal,
+            });
+        }
+    }
+}
@@ -0,0 +1,1151 @@
+mod activity;
+mod fighting;
+mod turn_events;
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::mem;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::Instant;
+use rand::{self, Rng};
+use uuid::Uuid;
+use piston::input::{Button, MouseButton, GenericEvent};
+use input::Control;
+use render::Viewport;
+use settings::{CameraOptions, GameplayOptions};
+use status::ControllerStatus;
+use super::{Command, Drawable, Movable, Positioned, GameState, Map, MapBuilder,
+            MovementDirection, Noise, SpriteInfo};
+use super::actor;
+use super::actor::{Actor, ActorStatus, ActorType, ActorInfo};
+use super::actors::player::Player;
+use super::actors::scripted_behavior;
+use super::faction::{Relationship, PLAYER_FACTION};
+use super::message::{Message, MessageType};
+use super::perception;
+use super::rng;
+use super::spawner::Spawner;
+use super::camera::Camera;
+use super::combat::RangedAttack;
+use super::description::{Descriptions, Examination};
+use super::projectile::{self, ProjectileAnimation, ProjectilePath};
+use super::replay::{RecordedCommand, Recorder};
+use super::status_effect::EffectKind;
+use super::tween::TurnEvent;
+use self::activity::Activity;
+
+const SPRITE_KEY_VOID: &'static str = "void";
+
+/// The most sets of controls kept waiting while the last turn is animated.
+const MAX_QUEUED_CONTROLS: usize = 3;
+
+/// The number of half-ticks that pass for each tick of player activity,
+/// so that hasted players can act twice per tick.
+const HALF_TICKS_PER_TICK: u32 = 2;
+
+/// Stores and updates the game's current state.
+pub struct GameController {
+    pub player_position: [i32; 2],
+    state: GameState,
+    status: Option<ControllerStatus>,
+    map_builder: MapBuilder,
+    spawner: Spawner,
+    actions: VecDeque<Action>,
+    noises: Vec<Noise>,
+    ticks_to_perform: u32,
+    half_ticks: u32,
+    tick: u64,
+    projectiles: Vec<ProjectileAnimation>,
+    descriptions: Descriptions,
+    examine_cursor: Option<[i32; 2]>,
+    viewport: Viewport,
+    camera: Camera,
+    mouse_position: Option<[f64; 2]>,
+    explored: HashSet<[i32; 2]>,
+    activity: Option<Activity>,
+    seed: usize,
+    recorder: Option<Recorder>,
+    input_enabled: bool,
+    options: GameplayOptions,
+    turn_events: VecDeque<(u64, TurnEvent)>,
+    last_turn_event: u64,
+    last_positions: HashMap<Uuid, [i32; 2]>,
+    animating_until: Option<Instant>,
+    queued_controls: VecDeque<Vec<Control>>,
+}
+
+enum Action {
+    Spawn(Box<Actor>),
+    LoadMapAtRelativeOffset([i32; 2]),
+    Attack(Uuid, Uuid),
+    Shoot(Uuid, [i32; 2], RangedAttack),
+}
+
+enum Visibility {
+    Full,
+    Half,
+    Invisible,
+}
+
+impl GameController {
+    /// Creates and returns an instance of the GameController struct with
+    /// a default state.
+    pub fn new() -> GameController {
+        GameController::with_seed(rng::random_seed())
+    }
+
+    /// Creates and returns an instance of the GameController struct whose
+    /// world, and everything that happens in it, is determined by the
+    /// provided seed and the commands it is given.
+    pub fn with_seed(seed: usize) -> GameController {
+        rng::reseed(seed);
+        let mut map_builder = MapBuilder::with_seed(seed);
+        let mut controller =
+            GameController::new_with(GameState::new(&mut map_builder), map_builder);
+        controller.seed = seed;
+        controller
+    }
+
+    /// Creates and returns an instance of the GameController struct with
+    /// its `state` field containing the provided GameState instance.
+    pub fn new_with(state: GameState, map_builder: MapBuilder) -> GameController {
+        let mut controller = GameController {
+            player_position: [-1, -1],
+            state: state,
+            status: None,
+            map_builder: map_builder,
+            spawner: Spawner::new(),
+            actions: VecDeque::<Action>::new(),
+            noises: Vec::<Noise>::new(),
+            ticks_to_perform: 0,
+            half_ticks: 0,
+            tick: 0,
+            projectiles: Vec::<ProjectileAnimation>::new(),
+            descriptions: Descriptions::new(),
+            examine_cursor: None,
+            viewport: Viewport::unscaled([1.0, 1.0]),
+            camera: Camera::new(CameraOptions::default()),
+            mouse_position: None,
+            explored: HashSet::<[i32; 2]>::new(),
+            activity: None,
+            seed: 0,
+            recorder: None,
+            input_enabled: true,
+            options: GameplayOptions::default(),
+            turn_events: VecDeque::new(),
+            last_turn_event: 0,
+            last_positions: HashMap::new(),
+            animating_until: None,
+            queued_controls: VecDeque::new(),
+        };
+        controller.populate_map();
+        controller.player_position = controller.current_player_position();
+        controller.update_explored();
+        controller
+    }
+
+    /// Returns the sprite key for the tile at the specified position
+    pub fn tile_sprite_at(&self, position: [i32; 2]) -> Result<Vec<SpriteInfo>, String> {
+        if let Some(tile) = self.state.map.get_at(position) {
+            Ok(self.get_sprite_at_distance(
+                tile.current_position(),
+                tile.sprite_components(),
+            ))
+        } else {
+            Err(format!(
+                "Unable to gather sprite key for tile at {:?}",
+                position
+            ))
+        }
+    }
+
+    /// Starts writing every command given from now on to a replay file at
+    /// the provided path.
+    pub fn record_to(&mut self, path: &Path) -> Result<(), String> {
+        self.recorder = Some(Recorder::create(path, self.seed)?);
+        info!("Recording to {:?} with seed {}", path, self.seed);
+        Ok(())
+    }
+
+    /// Sets whether the controller responds to the keyboard and mouse.  Input
+    /// is disabled while a replay is being played.
+    pub fn set_input_enabled(&mut self, enabled: bool) {
+        self.input_enabled = enabled;
+        if !enabled {
+            self.activity = None;
+            self.examine_cursor = None;
+            self.queued_controls.clear();
+        }
+    }
+
+    /// Tells the controller how large the window is, when the game fills it
+    /// without being scaled.
+    pub fn set_window_size(&mut self, width: u32, height: u32) {
+        self.set_viewport(Viewport::unscaled([width as f64, height as f64]));
+    }
+
+    /// Tells the controller where the game's screen is within the window, so
+    /// that positions in the window can be matched up with positions in the
+    /// map.
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        self.viewport = viewport;
+        self.center_camera();
+    }
+
+    /// Changes how the camera frames the map.  The camera starts over at
+    /// its default zoom, unless the options are the ones it already has.
+    pub fn set_camera_options(&mut self, options: CameraOptions) {
+        if *self.camera.options() == options {
+            return;
+        }
+        let pixel_perfect = self.camera.pixel_perfect();
+        self.camera = Camera::new(options);
+        self.camera.set_pixel_perfect(pixel_perfect);
+        self.center_camera();
+    }
+
+    /// Sets whether tiles are drawn at whole multiples of the sprite size.
+    pub fn set_pixel_perfect(&mut self, pixel_perfect: bool) {
+        self.camera.set_pixel_perfect(pixel_perfect);
+        self.center_camera();
+    }
+
+    /// Changes how the game plays, such as how far the player can see.
+    pub fn set_gameplay_options(&mut self, options: GameplayOptions) {
+        self.options = options;
+        if !self.options.animations {
+            self.turn_events.clear();
+            self.last_positions.clear();
+            self.animating_until = None;
+        }
+        self.update_explored();
+    }
+
+    /// Returns the width and height of the current map, in tiles.
+    pub fn map_size(&self) -> [usize; 2] {
+        [self.state.map.width(), self.state.map.height()]
+    }
+
+    /// Returns the camera that decides which part of the map is on screen.
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    /// Returns the number of ticks that have passed since the game began.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Indicates to the view whether or not it should display the message
+    /// queue to the player
+    pub fn should_show_messages(&self) -> bool {
+        self.state.show_messages
+    }
+
+    /// Performs game logic routines and alters the state of the controller
+    /// based on events received by the window and the controls that they
+    /// triggered.
+    pub fn update<E>(&mut self, event: &E, controls: &[Control])
+    where
+        E: GenericEvent,
+    {
+        if let Some(scroll) = event.mouse_scroll_args() {
+            if scroll[1] > 0.0 {
+                self.camera.zoom_in();
+            } else if scroll[1] < 0.0 {
+                self.camera.zoom_out();
+            }
+        }
+        self.update_zoom(controls);
+        self.projectiles.retain(|p| !p.finished());
+
+        // nothing happens in the world while the player is looking around
+        let mouse = event.mouse_cursor_args().map(|p| self.viewport.window_to_screen(p));
+        if self.update_examine(mouse, controls) {
+            return;
+        }
+
+        let pressed = event.press_args().is_some();
+        let updating = event.update_args().is_some();
+        if self.input_enabled && !self.update_activity(pressed, updating) &&
+            !self.update_mouse(event)
+        {
+            self.handle_controls(controls);
+        }
+        self.advance();
+    }
+
+    /// Performs game logic for frontends without a window, and therefore
+    /// without events.  Each call either handles a single key press, given
+    /// the controls bound to it, or lets time pass when `pressed` is `None`.
+    pub fn update_without_window(&mut self, pressed: Option<&[Control]>) {
+        self.projectiles.retain(|p| !p.finished());
+
+        let controls = pressed.unwrap_or(&[]);
+        self.update_zoom(controls);
+        if self.update_examine(None, controls) {
+            return;
+        }
+
+        if self.input_enabled && !self.update_activity(pressed.is_some(), pressed.is_none()) {
+            self.handle_controls(controls);
+        }
+        self.advance();
+    }
+
+    /// Zooms the camera in or out when asked to.
+    fn update_zoom(&mut self, controls: &[Control]) {
+        for control in controls {
+            match *control {
+                Control::ZoomIn => self.camera.zoom_in(),
+                Control::ZoomOut => self.camera.zoom_out(),
+                _ => {}
+            }
+        }
+    }
+
+    /// Moves the camera along behind the player.
+    fn follow_player(&mut self) {
+        let player_position = self.current_player_position();
+        let screen_size = self.viewport.screen_size();
+        let map_size = self.map_size();
+        self.camera.follow(player_position, screen_size, map_size);
+    }
+
+    /// Moves the camera straight to the player, such as when they arrive in
+    /// a new map.
+    fn center_camera(&mut self) {
+        let player_position = self.current_player_position();
+        let screen_size = self.viewport.screen_size();
+        let map_size = self.map_size();
+        self.camera.center_on(player_position, screen_size, map_size);
+    }
+
+    /// Carries out the commands that the provided controls stand for.  While
+    /// the last turn is still being animated, the controls wait their turn
+    /// and are carried out once it is done.
+    fn handle_controls(&mut self, controls: &[Control]) {
+        if !controls.is_empty() && self.queued_controls.len() < MAX_QUEUED_CONTROLS {
+            self.queued_controls.push_back(controls.to_vec());
+        }
+        while !self.is_animating() {
+            let controls = match self.queued_controls.pop_front() {
+                Some(controls) => controls,
+                None => break,
+            };
+            for control in controls {
+                if let Some(command) = control.command(self.player_is_aiming()) {
+                    self.execute(command);
+                    self.advance();
+                }
+            }
+        }
+    }
+
+    /// Carries out a command from a replay, as it was carried out when the
+    /// replay was recorded.
+    pub fn play(&mut self, recorded: &RecordedCommand) {
+        if recorded.tick != self.tick {
+            warn!(
+                "Replay out of sync: {:?} was recorded on tick {}, but is being played on tick {}",
+                recorded.command,
+                recorded.tick,
+                self.tick
+            );
+        }
+        self.execute(recorded.command.clone());
+        self.advance();
+    }
+
+    /// Lets the world respond to whatever the player has done, performing
+    /// their actions and then running as many ticks as they have used up.
+    fn advance(&mut self) {
+        // the player's own actions happen before the rest of the world responds
+        self.perform_actions();
+
+        if self.ticks_to_perform > 0 {
+            scripted_behavior::reload_if_changed();
+            for _ in 0..self.ticks_to_perform {
+                self.tick += 1;
+                self.tick_status_effects();
+                self.update_actors();
+                self.perform_actions();
+                self.respawn();
+            }
+            self.ticks_to_perform = 0;
+            self.update_explored();
+        }
+        self.record_moves();
+        self.follow_player();
+    }
+
+    /// Carries out a command given by the player, recording it if the session
+    /// is being recorded.  Commands that span many turns, such as travelling
+    /// or resting, only begin an activity here, which takes its steps during
+    /// later calls to `update`.  Commands from the keyboard and mouse don't
+    /// reach this until any running activity has stopped and the last turn
+    /// has finished animating; until then they wait in a queue.  The rest of
+    /// the world responds once the controller next advances, which `update`
+    /// and `play` do straight after carrying out a command.
+    pub fn execute(&mut self, command: Command) {
+        if command.is_recorded() {
+            if let Some(ref mut recorder) = self.recorder {
+                recorder.record(self.tick, &command);
+            }
+        }
+
+        match command {
+            Command::Pickup => self.pick_up(),
+            Command::Spawn(actor_type) => {
+                match actor::create(&actor_type) {
+                    Ok(spawned) => self.actions.push_back(Action::Spawn(spawned)),
+                    Err(why) => error!("{}", why),
+                }
+            }
+            Command::SpawnRandom => {
+                // the choice is recorded as a regular spawn, so it needn't come
+                // from the game's own random number generator
+                let id = rand::thread_rng().choose(&scripted_behavior::ids()).cloned();
+                if let Some(id) = id {
+                    self.execute(Command::Spawn(ActorType::Scripted(id)));
+                }
+            }
+            Command::ToggleMessages => {
+                self.state.show_messages = !self.state.show_messages;
+            }
+            Command::Explore => self.auto_explore(),
+            Command::TravelTo(target) => self.travel_to(target),
+            Command::Run(dir) => self.run(dir),
+            Command::Rest => self.rest(),
+            Command::WaitTurns(turns) => self.wait_turns(turns),
+            command => {
+                self.control_player(|player, map, actors| player.perform(&command, map, actors));
+            }
+        }
+    }
+
+    /// Returns a reference to the current status of the controller, indicating
+    /// whether it needs to affect the program flow in some way.
+    pub fn get_status(&mut self) -> Option<ControllerStatus> {
+        let s = self.status.clone();
+        self.status = None;
+        s
+    }
+
+    /// Returns a collection of messages
+    pub fn get_messages(&self, count: usize) -> Vec<Message> {
+        self.state
+            .messages
+            .clone()
+            .into_iter()
+            .rev()
+            .take(count)
+            .collect()
+    }
+
+    /// Returns the position and color of each projectile currently in flight
+    /// within view of the player, in fractional tiles.
+    pub fn projectiles(&self) -> Vec<([f64; 2], [f32; 4])> {
+        self.projectiles
+            .iter()
+            .filter_map(|p| p.position().map(|position| (position, p.color())))
+            .filter(|&(position, _)| {
+                self.visible_to_player([position[0].round() as i32, position[1].round() as i32])
+            })
+            .collect()
+    }
+
+    /// Returns the trail of fading positions left behind each projectile in
+    /// flight within view of the player, in fractional tiles, along with the
+    /// projectile's color.  There are none while animations are turned off.
+    pub fn projectile_trails(&self) -> Vec<(Vec<[f64; 2]>, [f32; 4])> {
+        if !self.options.animations {
+            return Vec::new();
+        }
+        self.projectiles
+            .iter()
+            .map(|p| {
+                let trail = p.trail()
+                    .into_iter()
+                    .filter(|position| {
+                        self.visible_to_player(
+                            [position[0].round() as i32, position[1].round() as i32],
+                        )
+                    })
+                    .collect();
+                (trail, p.color())
+            })
+            .collect()
+    }
+
+    /// Returns the events worth animating that happened after the one with
+    /// the provided number, each along with its own number.
+    pub fn turn_events_after(&self, seen: u64) -> Vec<(u64, TurnEvent)> {
+        self.turn_events
+            .iter()
+            .filter(|&&(number, _)| number > seen)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns a value indicating whether the player is aiming or looking
+    /// around, either of which the Cancel control puts an end to.
+    pub fn is_using_cursor(&self) -> bool {
+        self.examine_cursor.is_some() || self.player_is_aiming()
+    }
+
+    /// Returns the position of the tile under the mouse cursor, if it is over
+    /// the map and the player is not busy looking around.
+    pub fn hovered_tile(&self) -> Option<[i32; 2]> {
+        if self.examine_cursor.is_some() {
+            return None;
+        }
+        self.mouse_position
+            .map(|position| self.camera.to_map(position))
+            .filter(|position| self.state.map.get_at(*position).is_some())
+    }
+
+    /// Returns a description of whatever is at the position the player is
+    /// examining, if the player is looking around.
+    pub fn examination(&self) -> Option<Examination> {
+        let position = match self.examine_cursor {
+            Some(position) => position,
+            None => return None,
+        };
+        let tile = match self.state.map.get_at(position) {
+            Some(tile) => tile,
+            None => return None,
+        };
+
+        let mut lines = Vec::<String>::new();
+        if !self.visible_to_player(position) {
+            lines.push(String::from("You can't see what is there."));
+            return Some(Examination {
+                position: position,
+                lines: lines,
+            });
+        }
+
+        for actor in self.state.actors.values().filter(
+            |a| a.current_position() == position,
+        )
+        {
+            let info = ActorInfo::new(actor.as_ref());
+            lines.push(format!(
+                "{}: {}",
+                GameController::describe(&info, true),
+                self.descriptions.actor(info.actor_type.name().as_str())
+            ));
+
+            let mut condition = Vec::<&str>::new();
+            if let Some(vitals) = actor.vitals() {
+                let health = vitals.health as f64 / vitals.max_health.max(1) as f64;
+                if health <= 0.25 {
+                    condition.push("badly wounded");
+                } else if health <= 0.5 {
+                    condition.push("wounded");
+                } else if health < 1.0 {
+                    condition.push("lightly wounded");
+                }
+            }
+            if let Some(effects) = actor.status_effects() {
+                condition.extend(effects.active().iter().map(|e| e.kind.adjective()));
+            }
+            if !condition.is_empty() {
+                lines.push(format!(
+                    "{} {}.",
+                    if info.id == self.state.player_id { "You are" } else { "It is" },
+                    condition.join(", ")
+                ));
+            }
+        }
+        for entity in self.state.entities.values().filter(
+            |e| e.current_position() == position,
+        )
+        {
+            let name = entity.name();
+            lines.push(format!("{}: {}", name, self.descriptions.entity(name.as_str())));
+        }
+        for item in self.state.items.values().filter(|i| i.position == position) {
+            lines.push(format!(
+                "{}: {}",
+                item.name,
+                self.descriptions.item(item.name.as_str())
+            ));
+        }
+        lines.push(String::from(self.descriptions.tile(tile)));
+
+        Some(Examination {
+            position: position,
+            lines: lines,
+        })
+    }
+
+    /// Returns the position that the player is aiming at, along with the path
+    /// that a projectile would take to get there, if the player is choosing
+    /// a target for a ranged attack.
+    pub fn targeting(&self) -> Option<([i32; 2], ProjectilePath)> {
+        let player = match self.state.actors.get(&self.state.player_id) {
+            Some(player) => player,
+            None => return None,
+        };
+        let (cursor, range) = match player.downcast_ref::<Player>().and_then(|p| p.aim()) {
+            Some(aim) => aim,
+            None => return None,
+        };
+        let others: Vec<ActorInfo> = self.state
+            .actors
+            .values()
+            .filter(|a| a.id() != self.state.player_id)
+            .map(|a| ActorInfo::new(a.as_ref()))
+            .filter(|a| self.visible_to_player(a.position))
+            .collect();
+        let path = projectile::trace(
+            &self.state.map,
+            player.current_position(),
+            cursor,
+            range,
+            &others,
+        );
+        Some((cursor, path))
+    }
+
+    /// Returns the ID, sprites, and position of every actor, with the sprites
+    /// of those the player can't see replaced as they are for tiles.
+    pub fn actor_sprites(&self) -> Vec<(Uuid, SpriteInfo, [i32; 2])> {
+        let mut sprite_positions = Vec::<(Uuid, SpriteInfo, [i32; 2])>::new();
+        for actor in self.state.actors.values() {
+            let sprites =
+                self.get_sprite_at_distance(actor.current_position(), actor.sprite_components());
+            for sprite in sprites {
+                sprite_positions.push((actor.id(), sprite, actor.current_position()));
+            }
+        }
+        sprite_positions
+    }
+
+    fn perform_actions(&mut self) {
+        let actions: Vec<Action> = self.actions.drain(..).collect();
+        for action in actions {
+            match action {
+                Action::Spawn(mut actor) => {
+                    let player_position = self.current_player_position();
+                    let occupied = self.occupied_positions();
+                    if self.spawner.place(
+                        actor.as_mut(),
+                        &self.state.map,
+                        player_position,
+                        &occupied,
+                    )
+                    {
+                        self.state.actors.insert(actor.id(), actor);
+                    } else {
+                        warn!("No valid position found to spawn {:?}", actor.actor_type());
+                    }
+                }
+                Action::Attack(attacker, target) => {
+                    self.resolve_attack(attacker, target);
+                }
+                Action::Shoot(shooter, target, attack) => {
+                    self.resolve_shot(shooter, target, attack);
+                }
+                Action::LoadMapAtRelativeOffset(offset) => {
+                    self.state.map = Rc::new(self.map_builder.create_offset(offset));
+                    self.enter_map(offset);
+
+                    // actors other than the player are left behind in the previous map
+                    let player_id = self.state.player_id;
+                    self.state.actors.retain(|id, _| *id == player_id);
+                    self.populate_map();
+                    self.explored.clear();
+                    self.update_explored();
+                    self.center_camera();
+
+                    self.continue_into_new_map();
+                }
+            }
+        }
+    }
+
+    /// Fills the current map with its initial set of actors.
+    fn populate_map(&mut self) {
+        let player_position = self.current_player_position();
+        let occupied = self.occupied_positions();
+        let spawned = self.spawner.populate(
+            &self.state.map,
+            self.map_builder.biome(),
+            self.map_builder.depth(),
+            player_position,
+            &occupied,
+        );
+        for actor in spawned {
+            self.state.actors.insert(actor.id(), actor);
+        }
+    }
+
+    /// Moves the player onto the edge of the current map that they crossed
+    /// into it from, after travelling the provided offset.  Along the other
+    /// axis they keep their position, but are kept on the map in case it is
+    /// smaller than the one they left.
+    fn enter_map(&mut self, offset: [i32; 2]) {
+        let map_size = self.map_size();
+        let last = [map_size[0] as i32 - 1, map_size[1] as i32 - 1];
+        let player_id = self.state.player_id;
+        if let Some(player) = self.state.actors.get_mut(&player_id) {
+            let position = player.current_position();
+            let entry = |axis: usize| if offset[axis] < 0 {
+                last[axis]
+            } else if offset[axis] > 0 {
+                0
+            } else {
+                position[axis].max(0).min(last[axis])
+            };
+            let (x, y) = (entry(0), entry(1));
+            player.set_x(x);
+            player.set_y(y);
+        }
+        self.player_position = self.current_player_position();
+    }
+
+    /// Gives the spawner a chance to add a new actor to the map.
+    fn respawn(&mut self) {
+        let player_position = self.current_player_position();
+        let occupied = self.occupied_positions();
+        if let Some(actor) = self.spawner.tick(
+            &self.state.map,
+            self.map_builder.biome(),
+            self.map_builder.depth(),
+            player_position,
+            &occupied,
+        )
+        {
+            self.state.actors.insert(actor.id(), actor);
+        }
+    }
+
+    fn current_player_position(&self) -> [i32; 2] {
+        match self.state.actors.get(&self.state.player_id) {
+            Some(player) => player.current_position(),
+            None => self.player_position,
+        }
+    }
+
+    fn occupied_positions(&self) -> Vec<[i32; 2]> {
+        self.state
+            .actors
+            .values()
+            .map(|a| a.current_position())
+            .collect()
+    }
+
+    /// Handles input for looking around the map, returning a value indicating
+    /// whether the event was used up by it.
+    fn update_examine(&mut self, mouse: Option<[f64; 2]>, controls: &[Control]) -> bool {
+        let mut cursor = match self.examine_cursor {
+            Some(cursor) => cursor,
+            None => {
+                if controls.contains(&Control::Look) {
+                    self.examine_cursor = Some(self.current_player_position());
+                    return true;
+                }
+                return false;
+            }
+        };
+
+        if let Some(position) = mouse {
+            cursor = self.camera.to_map(position);
+        }
+        for control in controls {
+            match *control {
+                Control::Look | Control::Cancel => {
+                    self.examine_cursor = None;
+                    return true;
+                }
+                _ => {
+                    if let Some(dir) = control.direction() {
+                        cursor = super::map_direction_to_position(cursor, &dir, 1);
+                    }
+                }
+            }
+        }
+
+        // keep the cursor within the map
+        cursor[0] = cursor[0].max(0).min(self.state.map.width() as i32 - 1);
+        cursor[1] = cursor[1].max(0).min(self.state.map.height() as i32 - 1);
+        self.examine_cursor = Some(cursor);
+        true
+    }
+
+    /// Handles mouse input, returning a value indicating whether the event
+    /// was used up by it.
+    fn update_mouse<E: GenericEvent>(&mut self, event: &E) -> bool {
+        if let Some(position) = event.mouse_cursor_args() {
+            self.mouse_position = Some(self.viewport.window_to_screen(position));
+            return false;
+        }
+        match (event.press_args(), self.hovered_tile()) {
+            (Some(Button::Mouse(MouseButton::Left)), Some(target)) => {
+                self.execute(Command::TravelTo(target));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Hands whatever is lying at the player's feet over to them.
+    fn pick_up(&mut self) {
+        if self.player_is_aiming() {
+            return;
+        }
+        let position = self.current_player_position();
+        let found = self.state
+            .items
+            .iter()
+            .find(|&(_, item)| item.position == position)
+            .map(|(id, _)| *id);
+        let item = match found.and_then(|id| self.state.items.remove(&id)) {
+            Some(item) => item,
+            None => {
+                self.state.messages.push_back(Message {
+                    contents: String::from("There is nothing here to pick up."),
+                    message_type: MessageType::Normal,
+                });
+                return;
+            }
+        };
+
+        let mut left = None;
+        self.control_player(|player, _, _| left = player.pick_up(item));
+        if let (Some(id), Some(item)) = (found, left) {
+            self.state.items.insert(id, item);
+        }
+    }
+
+    /// Lets the player react to something, passing it the map and the actors
+    /// it can see, then collects whatever the player decided to do.
+    fn control_player<F>(&mut self, control: F)
+    where
+        F: FnOnce(&mut Player, &Map, &[ActorInfo]),
+    {
+        let id = self.state.player_id.clone();
+        let map = Rc::clone(&self.state.map);
+        let actors = self.visible_actor_info();
+        let mut half_ticks_to_add = 0;
+        let mut player_position = [-1; 2];
+        let mut player_status = None;
+
+        match self.get_downcasted_actor::<Player>(&id) {
+            Ok(player) => {
+                control(player, &map, &actors);
+                player_position = player.current_position();
+                player_status = player.status();
+                if let Some(count) = player.ticks() {
+                    // slowed players take twice as long to act, and hasted players half as long
+                    let cost = match player.status_effects() {
+                        Some(effects) if effects.has(EffectKind::Hasted) => 1,
+                        Some(effects) if effects.has(EffectKind::Slowed) => 4,
+                        _ => HALF_TICKS_PER_TICK,
+                    };
+                    half_ticks_to_add = count * cost;
+                }
+            }
+            Err(why) => {
+                error!("{}", why);
+            }
+        };
+
+        self.half_ticks += half_ticks_to_add;
+        self.ticks_to_perform += self.half_ticks / HALF_TICKS_PER_TICK;
+        self.half_ticks %= HALF_TICKS_PER_TICK;
+        if let Some(status) = player_status {
+            self.process_status(id, status);
+        }
+        self.player_position = player_position;
+    }
+
+    /// Returns information about every actor that the player can see,
+    /// relative to the player.
+    fn visible_actor_info(&self) -> Vec<ActorInfo> {
+        self.actor_info_for(PLAYER_FACTION)
+            .into_iter()
+            .filter(|a| self.visible_to_player(a.position))
+            .collect()
+    }
+
+    /// Returns the IDs of every enemy that the player can see.
+    fn visible_enemies(&self) -> HashSet<Uuid> {
+        self.visible_actor_info()
+            .into_iter()
+            .filter(|a| a.relationship == Relationship::Hostile)
+            .map(|a| a.id)
+            .collect()
+    }
+
+    /// Returns the IDs of every item that the player can see.
+    fn visible_items(&self) -> HashSet<Uuid> {
+        self.state
+            .items
+            .iter()
+            .filter(|&(_, item)| self.visible_to_player(item.position))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    fn player_is_aiming(&self) -> bool {
+        self.state
+            .actors
+            .get(&self.state.player_id)
+            .and_then(|p| p.downcast_ref::<Player>())
+            .map_or(false, |p| p.aim().is_some())
+    }
+
+    /// Remembers every tile that the player can currently see.
+    fn update_explored(&mut self) {
+        let origin = self.current_player_position();
+        let radius = self.player_sight_radius() as i32;
+        for x in (origin[0] - radius)..(origin[0] + radius + 1) {
+            for y in (origin[1] - radius)..(origin[1] + radius + 1) {
+                let position = [x, y];
+                if self.state.map.get_at(position).is_some() && self.visible_to_player(position) {
+                    self.explored.insert(position);
+                }
+            }
+        }
+    }
+
+    fn get_downcasted_actor<A: Actor>(&mut self, id: &Uuid) -> Result<&mut A, String> {
+        if let Some(actor) = self.state.actors.get_mut(id) {
+            if let Some(concrete_actor) = actor.downcast_mut::<A>() {
+                Ok(concrete_actor)
+            } else {
+                Err(format!("Could not cast actor with ID {} from ", id))
+            }
+        } else {
+            Err(format!("Could not find actor with ID {}", id))
+        }
+    }
+
+    fn update_actors(&mut self) {
+        // work out how far each noise made since the last tick has travelled
+        let noises = mem::replace(&mut self.noises, Vec::<Noise>::new());
+        let volumes: Vec<HashMap<[i32; 2], u32>> =
+            noises.iter().map(|n| n.propagate(&self.state.map)).collect();
+        self.report_noises(&noises, &volumes);
+
+        // build cache of actor info
+        let mut actor_info = Vec::<ActorInfo>::new();
+        for actor in self.state.actors.values() {
+            actor_info.push(ActorInfo::new(actor.as_ref()));
+        }
+
+        // update actors, holding on to their requests until everyone has had a turn
+        let mut statuses = Vec::<(Uuid, ActorStatus)>::new();
+        for actor in &mut self.state.actors.values_mut() {
+            // let the actor hear any noises that reached it
+            for (noise, noise_volumes) in noises.iter().zip(&volumes) {
+                if noise.source == Some(actor.id()) {
+                    continue;
+                }
+                if let Some(volume) = noise_volumes.get(&actor.current_position()) {
+                    actor.on_hear(noise, *volume);
+                }
+            }
+
+            // slowed actors sometimes miss their turn, while hasted actors get two
+            let turns = actor.status_effects().map_or(1, |e| e.actions_during(self.tick));
+            for _ in 0..turns {
+                // update, telling the actor how everyone else feels about it
+                let faction = actor.faction();
+                let relative_info: Vec<ActorInfo> = actor_info
+                    .iter()
+                    .map(|i| i.relative_to(faction.as_str(), &self.state.factions))
+                    .collect();
+                actor.on_update(&self.state.map, &relative_info);
+
+                // retrieve messages
+                if let Some(messages) = actor.messages() {
+                    self.state.messages.append(messages);
+                }
+
+                // retrieve noises, to be heard during the next tick
+                if let Some(made) = actor.noises() {
+                    self.noises.extend(made.drain(..));
+                }
+
+                // retrieve status
+                if let Some(status) = actor.status() {
+                    statuses.push((actor.id(), status));
+                }
+            }
+        }
+
+        for (id, status) in statuses {
+            self.process_status(id, status);
+        }
+    }
+
+    /// Carries out the request made by an actor through its status.
+    fn process_status(&mut self, id: Uuid, status: ActorStatus) {
+        match status {
+            ActorStatus::LoadMapAtRelativeOffset(offset) => {
+                self.actions.push_back(Action::LoadMapAtRelativeOffset(offset));
+            }
+            ActorStatus::Attack(target) => {
+                self.actions.push_back(Action::Attack(id, target));
+            }
+            ActorStatus::Shoot(target, attack) => {
+                self.actions.push_back(Action::Shoot(id, target, attack));
+            }
+            ActorStatus::Quit => {
+                self.status = Some(ControllerStatus::Quit);
+            }
+        }
+    }
+
+    /// Returns information about every actor, relative to members of the
+    /// provided faction.
+    fn actor_info_for(&self, faction: &str) -> Vec<ActorInfo> {
+        self.state
+            .actors
+            .values()
+            .map(|a| {
+                ActorInfo::new(a.as_ref()).relative_to(faction, &self.state.factions)
+            })
+            .collect()
+    }
+
+    /// Describes an actor as it should be referred to in a message.
+    fn describe(actor: &ActorInfo, capitalize: bool) -> String {
+        let description = if actor.actor_type == ActorType::Player {
+            actor.name.clone()
+        } else {
+            format!("the {}", actor.name)
+        };
+        if capitalize {
+            let mut chars = description.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => description,
+            }
+        } else {
+            description
+        }
+    }
+
+    /// Tells the player about noises that reached them from somewhere they
+    /// cannot see.
+    fn report_noises(&mut self, noises: &[Noise], volumes: &[HashMap<[i32; 2], u32>]) {
+        let player_id = self.state.player_id;
+        let player_position = self.current_player_position();
+        let mut reported = HashSet::<String>::new();
+
+        for (noise, noise_volumes) in noises.iter().zip(volumes) {
+            if noise.source == Some(player_id) ||
+                !noise_volumes.contains_key(&player_position) ||
+                reported.contains(&noise.description)
+            {
+                continue;
+            }
+            if let Visibility::Invisible = self.within_player_view(noise.position) {
+                reported.insert(noise.description.clone());
+                self.state.messages.push_back(Message {
+                    contents: format!("You hear {}.", noise.description),
+                    message_type: MessageType::Background,
+                });
+            }
+        }
+    }
+
+    fn get_sprite_at_distance(&self, position: [i32; 2], sprite: SpriteInfo) -> Vec<SpriteInfo> {
+        match self.within_player_view(position) {
+            Visibility::Full => vec!(sprite),
+            Visibility::Half => vec!(
+                sprite,
+                SpriteInfo { key: SPRITE_KEY_VOID, color: [0.0, 0.0, 0.0, 0.5] },            
+            ),
+            Visibility::Invisible => vec!(
+                SpriteInfo { key: SPRITE_KEY_VOID, color: [0.0; 4] },
+            ),
+        }
+    }
+
+    fn within_player_view(&self, position: [i32; 2]) -> Visibility {
+        let (unobscured, distance) =
+            perception::ray_visible(&self.state.map, self.player_position, position);
+        let max_distance = self.player_sight_radius();
+
+        if !unobscured || distance > max_distance {
+            Visibility::Invisible
+        } else if distance > max_distance.saturating_sub(self.options.visibility_falloff) &&
+                   distance <= max_distance {
+            Visibility::Half
+        } else {
+            Visibility::Full
+        }
+    }
+
+    /// Returns a value indicating whether the player can see the position.
+    fn visible_to_player(&self, position: [i32; 2]) -> bool {
+        match self.within_player_view(position) {
+            Visibility::Invisible => false,
+            _ => true,
+        }
+    }
+
+    /// Returns how far the player can currently see.
+    fn player_sight_radius(&self) -> u32 {
+        match self.state.actors.get(&self.state.player_id).and_then(|p| p.status_effects()) {
+            Some(effects) => effects.sight_radius(self.options.view_distance),
+            None => self.options.view_distance,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game;
+
+    /// Puts the player on a passable tile at the east edge of the map, with
+    /// nobody else around, and has them step off it into the next map over.
+    pub(super) fn cross_east_edge(controller: &mut GameController) {
+        let last = controller.state.map.width() as i32 - 1;
+        let y = (0..controller.state.map.height() as i32)
+            .find(|y| {
+                controller.state.map.get_at([last, *y]).map_or(false, |t| t.passable())
+            })
+            .expect("The east edge of the map has nowhere to stand");
+
+        let player_id = controller.state.player_id;
+        controller.state.actors.retain(|id, _| *id == player_id);
+        if let Some(player) = controller.state.actors.get_mut(&player_id) {
+            player.set_x(last);
+            player.set_y(y);
+        }
+        controller.player_position = [last, y];
+        controller.update_explored();
+
+        controller.execute(Command::Move(MovementDirection::Right));
+        controller.advance();
+    }
+
+    #[test]
+    fn crossing_an_edge_explores_around_the_player() {
+        let mut controller = GameController::with_seed(42);
+        cross_east_edge(&mut controller);
+
+        let position = controller.current_player_position();
+        assert_eq!(position[0], 0);
+        assert_eq!(controller.player_position, position);
+        assert!(controller.explored.contains(&position));
+
+        let radius = controller.player_sight_radius() as i32;
+        assert!(
+            controller.explored.iter().all(|p| {
+                game::chebyshev_distance(*p, position) <= radius
+            }),
+            "tiles out of sight of {:?} were explored",
+            position
+        );
+    }
+}
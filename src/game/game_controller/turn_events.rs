@@ -0,0 +1,82 @@
+//! What happened during each turn, kept for the view to animate.
+
+use std::collections::HashMap;
+use std::mem;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+use game;
+use game::actor::ActorInfo;
+use game::tween::{TurnEvent, TWEEN_MILLIS};
+use super::GameController;
+
+/// The most turn events kept for the view to animate.  Older ones are
+/// dropped, since they would have finished animating long ago.
+const MAX_TURN_EVENTS: usize = 64;
+
+impl GameController {
+    /// Returns a value indicating whether the view is still animating what
+    /// happened during the last turn.
+    pub(super) fn is_animating(&self) -> bool {
+        self.animating_until.map_or(false, |until| Instant::now() < until)
+    }
+
+    /// Keeps a turn event for the view to animate, if animations are turned
+    /// on, and holds back input until it has been animated.
+    fn record_turn_event(&mut self, event: TurnEvent) {
+        if !self.options.animations {
+            return;
+        }
+        self.last_turn_event += 1;
+        self.turn_events.push_back((self.last_turn_event, event));
+        while self.turn_events.len() > MAX_TURN_EVENTS {
+            self.turn_events.pop_front();
+        }
+        self.animating_until = Some(Instant::now() + Duration::from_millis(TWEEN_MILLIS));
+    }
+
+    /// Records every actor that has stepped to a neighboring tile within view
+    /// of the player since the last time this was called, so that the view
+    /// can slide them there.  Actors that jumped farther, such as into a new
+    /// map, are left to appear where they landed.
+    pub(super) fn record_moves(&mut self) {
+        if !self.options.animations {
+            return;
+        }
+        let positions: HashMap<Uuid, [i32; 2]> = self.state
+            .actors
+            .values()
+            .map(|a| (a.id(), a.current_position()))
+            .collect();
+        let last_positions = mem::replace(&mut self.last_positions, positions);
+
+        let mut moves = Vec::<TurnEvent>::new();
+        for (id, to) in &self.last_positions {
+            if let Some(from) = last_positions.get(id) {
+                if from != to && game::chebyshev_distance(*from, *to) <= 1 &&
+                    (self.visible_to_player(*from) || self.visible_to_player(*to))
+                {
+                    moves.push(TurnEvent::Moved {
+                        actor: *id,
+                        from: *from,
+                        to: *to,
+                    });
+                }
+            }
+        }
+        for event in moves {
+            self.record_turn_event(event);
+        }
+    }
+
+    /// Records that an actor was hurt, if the player can see it, so that the
+    /// view can flash it and show the damage it took.
+    pub(super) fn record_hit(&mut self, actor: &ActorInfo, damage: i32) {
+        if damage > 0 && self.visible_to_player(actor.position) {
+            self.record_turn_event(TurnEvent::Hit {
+                actor: actor.id,
+                position: actor.position,
+                damage: damage,
+            });
+        }
+    }
+}
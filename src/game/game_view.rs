@@ -1,99 +1,287 @@
-use graphics::{self, Context};
-use opengl_graphics::GlGraphics;
 use hyphenation::{self, Language};
 use textwrap::Wrapper;
+use render::Renderer;
+use super::animation::{self, AnimationClock, Animations};
 use super::message::*;
-use super::{MAP_WIDTH, MAP_HEIGHT, GameController, TextureMapper};
-use text_renderer::{FontSize, TextRenderer};
+use super::tween::Tweens;
+use super::GameController;
+use text_renderer::FontSize;
 
 const MESSAGE_LEFT_PAD: f64 = 10.0;
 const MESSAGE_VERTICAL_ADJUSTMENT: f64 = -6.0;
 const TEXT_WRAP_WIDTH: usize = 32;
 const MESSAGE_DISPLAY_WIDTH: f64 = 250.0;
+const TARGET_PATH_COLOR: [f32; 4] = [1.0, 0.85, 0.2, 0.25];
+const TARGET_CURSOR_COLOR: [f32; 4] = [1.0, 0.85, 0.2, 0.9];
+const TARGET_HIT_COLOR: [f32; 4] = [1.0, 0.2, 0.2, 0.4];
+const TARGET_CURSOR_THICKNESS: f64 = 1.5;
+const PROJECTILE_SCALE: f64 = 0.3;
+const TRAIL_FADE: f32 = 0.6;
+const TRAIL_SHRINK: f64 = 0.8;
+const HIT_FLASH_COLOR: [f32; 4] = [1.0, 0.25, 0.25, 1.0];
+const DAMAGE_NUMBER_COLOR: [f32; 4] = [1.0, 0.9, 0.3, 1.0];
+const HOVER_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.15];
+const EXAMINE_CURSOR_COLOR: [f32; 4] = [0.4, 0.8, 1.0, 0.9];
+const EXAMINE_PANEL_COLOR: [f32; 4] = [0.05, 0.05, 0.1, 0.9];
+const EXAMINE_TEXT_COLOR: [f32; 4] = [0.9, 0.9, 0.9, 1.0];
+const EXAMINE_PANEL_WIDTH: f64 = 300.0;
+const EXAMINE_PANEL_PAD: f64 = 8.0;
 
 /// Renders information about the game's current state to the screen.
 pub struct GameView {
-    tm: TextureMapper,
-    text_renderer: &'static TextRenderer,
     text_wrapper: Wrapper<'static>,
+    animations: Animations,
+    clock: AnimationClock,
+    tweens: Tweens,
 }
 
 impl GameView {
     /// Creates and returns a new instance of the GameView struct.
-    pub fn new(txt: &'static TextRenderer) -> GameView {
+    pub fn new() -> GameView {
+        GameView::with_clock(AnimationClock::new())
+    }
+
+    /// Creates and returns a new instance of the GameView struct that
+    /// animates by the provided clock.
+    pub fn with_clock(clock: AnimationClock) -> GameView {
         let corpus = hyphenation::load(Language::English_US).unwrap();
         GameView {
-            tm: TextureMapper::new(),
-            text_renderer: txt,
             text_wrapper: Wrapper::new(TEXT_WRAP_WIDTH).word_splitter(Box::new(corpus)),
+            animations: Animations::load(),
+            clock: clock,
+            tweens: Tweens::new(),
         }
     }
 
-    /// Renders visuals onto the window based on the state of the provided controller instance.
-    pub fn draw(&mut self, controller: &GameController, c: &Context, g: &mut GlGraphics) {
-        let screen_rect = c.viewport
-            .unwrap_or_else(|| panic!("Could not get the viewport!"))
-            .rect;
+    /// Renders visuals with the provided renderer based on the state of the
+    /// provided controller instance.
+    pub fn draw(&mut self, controller: &GameController, r: &mut Renderer) {
+        let screen_size = r.size();
+
+        // start animating whatever has happened since the last frame
+        let events = controller.turn_events_after(self.tweens.seen());
+        self.tweens.update(events, self.clock.millis());
+
+        self.draw_tiles(screen_size, controller, r);
+        self.draw_actors(screen_size, controller, r);
+        self.draw_hover(screen_size, controller, r);
+        self.draw_targeting(screen_size, controller, r);
+        self.draw_projectiles(screen_size, controller, r);
+        self.draw_damage_numbers(screen_size, controller, r);
+        self.draw_messages(screen_size, controller, r);
+        self.draw_examination(screen_size, controller, r);
+    }
+
+    fn draw_examination(
+        &mut self,
+        screen_size: [f64; 2],
+        controller: &GameController,
+        r: &mut Renderer,
+    ) {
+        let examination = match controller.examination() {
+            Some(examination) => examination,
+            None => return,
+        };
+        let screen_w = screen_size[0];
+        let screen_h = screen_size[1];
+        let camera = controller.camera();
+        let cursor = camera.tile_rect(examination.position);
+
+        r.draw_border(
+            cursor,
+            EXAMINE_CURSOR_COLOR,
+            TARGET_CURSOR_THICKNESS,
+        );
+
+        let mut lines = Vec::<String>::new();
+        for text in &examination.lines {
+            lines.extend(
+                self.text_wrapper
+                    .fill(text.as_str())
+                    .split('\n')
+                    .map(String::from),
+            );
+        }
+
+        // keep the panel along the bottom of the screen, away from the cursor
+        let line_height = r.line_height(FontSize::Size18);
+        let panel_h = line_height * lines.len() as f64 + EXAMINE_PANEL_PAD * 2.0;
+        let panel_x = if cursor[0] < screen_w / 2.0 {
+            screen_w - EXAMINE_PANEL_WIDTH
+        } else {
+            0.0
+        };
+        let panel_y = screen_h - panel_h;
 
-        self.draw_tiles(screen_rect, controller, c, g);
-        self.draw_actors(screen_rect, controller, c, g);
-        self.draw_messages(screen_rect, controller, c, g);
+        r.draw_rect(
+            [panel_x, panel_y, EXAMINE_PANEL_WIDTH, panel_h],
+            EXAMINE_PANEL_COLOR,
+        );
+        for (index, line) in lines.iter().enumerate() {
+            r.draw_text(
+                line.as_str(),
+                [
+                    panel_x + EXAMINE_PANEL_PAD,
+                    panel_y + EXAMINE_PANEL_PAD + line_height * index as f64,
+                ],
+                FontSize::Size18,
+                EXAMINE_TEXT_COLOR,
+            );
+        }
     }
 
     fn draw_actors(
         &mut self,
-        screen_rect: [i32; 4],
+        _screen_size: [f64; 2],
+        controller: &GameController,
+        r: &mut Renderer,
+    ) {
+        let camera = controller.camera();
+        let tile_size = camera.tile_size();
+        let millis = self.clock.millis();
+
+        // draw actors (this includes the player), each idling at its own pace,
+        // sliding toward the tile they are on, and flashing if just hurt
+        for (id, sprite, position) in controller.actor_sprites() {
+            let key = self.animations.frame(sprite.key, millis, animation::phase_at(position));
+            let corner = camera.to_screen(self.tweens.position(&id, position, millis));
+            let flash = self.tweens.flash(&id, millis);
+            let mut color = sprite.color;
+            for (channel, flash_channel) in color.iter_mut().zip(&HIT_FLASH_COLOR).take(3) {
+                *channel += (flash_channel - *channel) * flash;
+            }
+            r.draw_sprite(key, [corner[0], corner[1], tile_size, tile_size], color);
+        }
+    }
+
+    fn draw_damage_numbers(
+        &mut self,
+        _screen_size: [f64; 2],
+        controller: &GameController,
+        r: &mut Renderer,
+    ) {
+        let camera = controller.camera();
+        let tile_size = camera.tile_size();
+
+        for (text, position, opacity) in self.tweens.numbers(self.clock.millis()) {
+            let corner = camera.to_screen(position);
+            let mut color = DAMAGE_NUMBER_COLOR;
+            color[3] *= opacity;
+            r.draw_text(
+                text,
+                [corner[0] + tile_size / 4.0, corner[1] - tile_size / 4.0],
+                FontSize::Size18,
+                color,
+            );
+        }
+    }
+
+    fn draw_hover(
+        &mut self,
+        _screen_size: [f64; 2],
+        controller: &GameController,
+        r: &mut Renderer,
+    ) {
+        if let Some(position) = controller.hovered_tile() {
+            r.draw_rect(controller.camera().tile_rect(position), HOVER_COLOR);
+        }
+    }
+
+    fn draw_targeting(
+        &mut self,
+        _screen_size: [f64; 2],
+        controller: &GameController,
+        r: &mut Renderer,
+    ) {
+        let camera = controller.camera();
+
+        if let Some((cursor, path)) = controller.targeting() {
+            // shade the tiles that the projectile would pass through, marking
+            // the last one red if it would strike someone there
+            for (index, tile) in path.tiles.iter().enumerate() {
+                let color = if path.hit.is_some() && index == path.tiles.len() - 1 {
+                    TARGET_HIT_COLOR
+                } else {
+                    TARGET_PATH_COLOR
+                };
+                r.draw_rect(camera.tile_rect(*tile), color);
+            }
+
+            r.draw_border(
+                camera.tile_rect(cursor),
+                TARGET_CURSOR_COLOR,
+                TARGET_CURSOR_THICKNESS,
+            );
+        }
+    }
+
+    fn draw_projectiles(
+        &mut self,
+        _screen_size: [f64; 2],
         controller: &GameController,
-        c: &Context,
-        g: &mut GlGraphics,
+        r: &mut Renderer,
     ) {
-        let tile_w = screen_rect[2] as f64 / MAP_WIDTH as f64;
-        let tile_h = screen_rect[3] as f64 / MAP_HEIGHT as f64;
+        let camera = controller.camera();
+        let tile_size = camera.tile_size();
+        let size = tile_size * PROJECTILE_SCALE;
 
-        // draw actors (this includes the player)
-        for (sprite, position) in controller.actor_sprites() {
-            self.tm.draw_at(
+        // each copy in a trail is smaller and fainter than the one before it
+        for (trail, color) in controller.projectile_trails() {
+            let mut color = color;
+            let mut size = size;
+            for position in trail {
+                color[3] *= TRAIL_FADE;
+                size *= TRAIL_SHRINK;
+                let corner = camera.to_screen(position);
+                r.draw_ellipse(
+                    [
+                        corner[0] + (tile_size - size) / 2.0,
+                        corner[1] + (tile_size - size) / 2.0,
+                        size,
+                        size,
+                    ],
+                    color,
+                );
+            }
+        }
+
+        for (position, color) in controller.projectiles() {
+            let corner = camera.to_screen(position);
+            r.draw_ellipse(
                 [
-                    position[0] as f64 * tile_w,
-                    position[1] as f64 * tile_h,
-                    tile_w,
-                    tile_h,
+                    corner[0] + (tile_size - size) / 2.0,
+                    corner[1] + (tile_size - size) / 2.0,
+                    size,
+                    size,
                 ],
-                sprite.key,
-                sprite.color,
-                c.transform,
-                g,
+                color,
             );
         }
     }
 
     fn draw_tiles(
         &mut self,
-        screen_rect: [i32; 4],
+        screen_size: [f64; 2],
         controller: &GameController,
-        c: &Context,
-        g: &mut GlGraphics,
+        r: &mut Renderer,
     ) {
-        let tile_w = screen_rect[2] as f64 / MAP_WIDTH as f64;
-        let tile_h = screen_rect[3] as f64 / MAP_HEIGHT as f64;
+        let camera = controller.camera();
+        let map_size = controller.map_size();
+        let (first, last) = camera.visible_tiles(screen_size);
+        let millis = self.clock.millis();
 
-        // draw tiles
-        for x in 0..MAP_WIDTH {
-            for y in 0..MAP_HEIGHT {
-                match controller.tile_sprite_at([x as i32, y as i32]) {
+        // draw only the tiles on the map that the camera can see
+        for x in first[0].max(0)..=last[0].min(map_size[0] as i32 - 1) {
+            for y in first[1].max(0)..=last[1].min(map_size[1] as i32 - 1) {
+                match controller.tile_sprite_at([x, y]) {
                     Ok(sprites) => {
+                        let rect = camera.tile_rect([x, y]);
+                        let phase = animation::phase_at([x, y]);
                         for sprite in sprites {
-                            self.tm.draw_at(
-                                [
-                                    x as f64 * tile_w,
-                                    y as f64 * tile_h,
-                                    tile_w + 1.0,
-                                    tile_h + 1.0,
-                                ],
-                                sprite.key,
+                            r.draw_sprite(
+                                self.animations.frame(sprite.key, millis, phase),
+                                [rect[0], rect[1], rect[2] + 1.0, rect[3] + 1.0],
                                 sprite.color,
-                                c.transform,
-                                g,
                             );
                         }
                     }
@@ -105,31 +293,27 @@ impl GameView {
 
     fn draw_messages(
         &mut self,
-        screen_rect: [i32; 4],
+        screen_size: [f64; 2],
         controller: &GameController,
-        c: &Context,
-        g: &mut GlGraphics,
+        r: &mut Renderer,
     ) {
         if controller.should_show_messages() {
-            let screen_w = screen_rect[2] as f64;
-            let screen_h = screen_rect[3] as f64;
-            let tile_w = screen_rect[2] as f64 / MAP_WIDTH as f64;
+            let screen_w = screen_size[0];
+            let screen_h = screen_size[1];
 
-            let line_height = self.text_renderer.line_height(FontSize::Size24) as f64;
+            let line_height = r.line_height(FontSize::Size24);
 
             // check which side of the screen the player is on,
             // and adjust the position of message box accordingly
             let mut left_adjust = 0.0;
-            let player_x = controller.player_position[0] as f64;
-            if player_x * tile_w < screen_w / 2.0 {
+            let player_x = controller.camera().tile_rect(controller.player_position)[0];
+            if player_x < screen_w / 2.0 {
                 left_adjust = screen_w - MESSAGE_DISPLAY_WIDTH;
             }
 
-            graphics::rectangle(
-                [0.1, 0.1, 0.1, 0.9],
+            r.draw_rect(
                 [left_adjust, 0.0, MESSAGE_DISPLAY_WIDTH, screen_h],
-                c.transform,
-                g,
+                [0.1, 0.1, 0.1, 0.9],
             );
 
             // draw messages
@@ -156,14 +340,7 @@ impl GameView {
                             MESSAGE_VERTICAL_ADJUSTMENT,
                     ];
 
-                    self.text_renderer.draw_at(
-                        position,
-                        String::from(line),
-                        c.transform,
-                        FontSize::Size18,
-                        color,
-                        g,
-                    );
+                    r.draw_text(line, position, FontSize::Size18, color);
 
                     lines += 1;
                     if lines >= line_count {
@@ -2,4 +2,10 @@
 ////
 /// An item in this case is anything that can be used and/or examined,
 /// and is feasible to store on one's person.
-pub struct Item {}
+pub struct Item {
+    /// The name used to describe the item, and to look up its description.
+    pub name: String,
+
+    /// The position of the item in the map.
+    pub position: [i32; 2],
+}
@@ -1,8 +1,8 @@
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::marker::{Sync, Send};
 use ndarray::{Axis, Array2};
 use ndarray_parallel::prelude::*;
 use super::tile::{Tile, TileType};
-use super::{MAP_WIDTH, MAP_HEIGHT};
 
 /// Generates and stores information about the game's current
 /// arrangement of tiles and entities.  This ultimately makes
@@ -14,15 +14,16 @@ pub struct Map {
 }
 
 impl Map {
-    /// Creates and returns a new instance of the Map struct.
-    pub fn new() -> Map {
-        Map { tiles: Map::create_tiles() }
+    /// Creates and returns a new instance of the Map struct, with the
+    /// provided number of tiles across and down.
+    pub fn new(width: usize, height: usize) -> Map {
+        Map { tiles: Map::create_tiles(width, height) }
     }
 
     /// Returns a reference to the element at the specified offset
     pub fn get_at(&self, position: [i32; 2]) -> Option<&Tile> {
         let (x, y) = (position[0], position[1]);
-        if x >= 0 && x < MAP_WIDTH && y >= 0 && y < MAP_HEIGHT {
+        if x >= 0 && x < self.width() as i32 && y >= 0 && y < self.height() as i32 {
             Some(&self.tiles[[x as usize, y as usize]])
         } else {
             None
@@ -32,7 +33,7 @@ impl Map {
     // /// Sets the type of a tile at the specified position.
     // pub fn set_at(&mut self, position: [i32; 2], tile_type: TileType) {
     //     let (x, y) = (position[0], position[1]);
-    //     if x >= 0 && x < MAP_WIDTH && y >= 0 && y < MAP_HEIGHT {
+    //     if x >= 0 && x < self.width() as i32 && y >= 0 && y < self.height() as i32 {
     //         self.tiles[[x as usize, y as usize]].tile_type = tile_type;
     //     } else {
     //         warn!(
@@ -51,6 +52,94 @@ impl Map {
         self.tiles.par_iter_mut().for_each(|t| op(&m, t));
     }
 
+    /// Returns the positions of every passable tile that can be walked to from
+    /// the provided origin, in order of increasing distance.  The origin itself
+    /// is not included.
+    pub fn reachable_from(&self, origin: [i32; 2]) -> Vec<[i32; 2]> {
+        let mut reachable = Vec::<[i32; 2]>::new();
+        let mut visited = HashSet::<[i32; 2]>::new();
+        let mut frontier = VecDeque::<[i32; 2]>::new();
+        visited.insert(origin);
+        frontier.push_back(origin);
+
+        while let Some(position) = frontier.pop_front() {
+            for dx in -1..2 {
+                for dy in -1..2 {
+                    let neighbor = [position[0] + dx, position[1] + dy];
+                    if visited.contains(&neighbor) {
+                        continue;
+                    }
+                    if let Some(tile) = self.get_at(neighbor) {
+                        if tile.passable() {
+                            visited.insert(neighbor);
+                            frontier.push_back(neighbor);
+                            reachable.push(neighbor);
+                        }
+                    }
+                }
+            }
+        }
+        reachable
+    }
+
+    /// Returns the shortest walkable path from the origin to the goal, not
+    /// including the origin, if there is one.  Only passable tiles for which
+    /// `can_enter` returns `true` are walked through.
+    pub fn find_path<F>(&self, origin: [i32; 2], goal: [i32; 2], can_enter: F) -> Option<Vec<[i32; 2]>>
+    where
+        F: Fn([i32; 2]) -> bool,
+    {
+        if origin == goal {
+            return Some(Vec::new());
+        }
+        self.path_to_nearest(origin, can_enter, |p| p == goal)
+    }
+
+    /// Returns the shortest walkable path from the origin to the nearest
+    /// position, other than the origin, for which `is_goal` returns `true`.
+    /// Only passable tiles for which `can_enter` returns `true` are walked
+    /// through.
+    pub fn path_to_nearest<F, G>(&self, origin: [i32; 2], can_enter: F, is_goal: G) -> Option<Vec<[i32; 2]>>
+    where
+        F: Fn([i32; 2]) -> bool,
+        G: Fn([i32; 2]) -> bool,
+    {
+        let mut came_from = HashMap::<[i32; 2], [i32; 2]>::new();
+        let mut frontier = VecDeque::<[i32; 2]>::new();
+        came_from.insert(origin, origin);
+        frontier.push_back(origin);
+
+        while let Some(position) = frontier.pop_front() {
+            if position != origin && is_goal(position) {
+                // walk back along the route to recover the path
+                let mut path = vec![position];
+                let mut current = position;
+                while let Some(previous) = came_from.get(&current).cloned() {
+                    if previous == origin {
+                        break;
+                    }
+                    path.push(previous);
+                    current = previous;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for dx in -1..2 {
+                for dy in -1..2 {
+                    let neighbor = [position[0] + dx, position[1] + dy];
+                    if came_from.contains_key(&neighbor) || !can_enter(neighbor) {
+                        continue;
+                    }
+                    if self.get_at(neighbor).map_or(false, |t| t.passable()) {
+                        came_from.insert(neighbor, position);
+                        frontier.push_back(neighbor);
+                    }
+                }
+            }
+        }
+        None
+    }
+
     /// Returns the width, in number of tiles, of the map.
     pub fn width(&self) -> usize {
         self.tiles.len_of(Axis(0))
@@ -61,14 +150,105 @@ impl Map {
         self.tiles.len_of(Axis(1))
     }
 
-    fn create_tiles() -> Array2<Tile> {
-        let mut tiles =
-            Array2::<Tile>::from_elem((MAP_WIDTH as usize, MAP_HEIGHT as usize), Tile::new());
-        for x in 0..MAP_WIDTH {
-            for y in 0..MAP_HEIGHT {
-                tiles[[x as usize, y as usize]].position = [x, y];
+    /// Writes the map out as text, one line per row of tiles, using each
+    /// tile's symbol.
+    pub fn to_ascii(&self) -> String {
+        let mut text = String::with_capacity((self.width() + 1) * self.height());
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                text.push(self.tiles[[x, y]].symbol());
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    fn create_tiles(width: usize, height: usize) -> Array2<Tile> {
+        let mut tiles = Array2::<Tile>::from_elem((width, height), Tile::new());
+        for x in 0..width {
+            for y in 0..height {
+                tiles[[x, y]].position = [x as i32, y as i32];
             }
         }
         tiles
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tile::{WallOrientation, WallType};
+
+    /// Returns an open map with walls at the provided positions.
+    fn map_with_walls(width: usize, height: usize, walls: Vec<[i32; 2]>) -> Map {
+        let mut map = Map::new(width, height);
+        map.mut_parallel(move |_, t| if walls.contains(&t.position) {
+            t.tile_type = TileType::Wall(WallOrientation::Top, WallType::Stone);
+        });
+        map
+    }
+
+    fn assert_walkable(map: &Map, origin: [i32; 2], path: &[[i32; 2]]) {
+        let mut previous = origin;
+        for step in path {
+            assert!(map.get_at(*step).map_or(false, |t| t.passable()), "{:?} is blocked", step);
+            let distance = (step[0] - previous[0]).abs().max((step[1] - previous[1]).abs());
+            assert_eq!(distance, 1, "{:?} doesn't follow {:?}", step, previous);
+            previous = *step;
+        }
+    }
+
+    #[test]
+    fn find_path_goes_around_walls() {
+        let map = map_with_walls(5, 5, vec![[2, 0], [2, 1], [2, 2], [2, 3]]);
+        let path = map.find_path([0, 0], [4, 0], |_| true).unwrap();
+        assert_walkable(&map, [0, 0], &path);
+        assert_eq!(path.len(), 8);
+        assert_eq!(path.last(), Some(&[4, 0]));
+        assert!(path.contains(&[2, 4]));
+    }
+
+    #[test]
+    fn find_path_to_origin_is_empty() {
+        let map = Map::new(3, 3);
+        assert_eq!(map.find_path([1, 1], [1, 1], |_| true), Some(Vec::new()));
+    }
+
+    #[test]
+    fn find_path_fails_when_walled_off() {
+        let map = map_with_walls(5, 5, vec![[2, 0], [2, 1], [2, 2], [2, 3], [2, 4]]);
+        assert_eq!(map.find_path([0, 0], [4, 0], |_| true), None);
+    }
+
+    #[test]
+    fn find_path_only_enters_allowed_tiles() {
+        let map = Map::new(5, 3);
+        let path = map.find_path([0, 1], [4, 1], |p| p[1] != 1 || p[0] == 4).unwrap();
+        assert_walkable(&map, [0, 1], &path);
+        assert!(path[..path.len() - 1].iter().all(|p| p[1] != 1));
+        assert_eq!(map.find_path([0, 1], [4, 1], |p| p[0] < 3), None);
+    }
+
+    #[test]
+    fn path_to_nearest_finds_the_closest_goal() {
+        let map = Map::new(8, 8);
+        let path = map.path_to_nearest([4, 4], |_| true, |p| p[0] == 0 || p[0] == 6).unwrap();
+        assert_walkable(&map, [4, 4], &path);
+        assert_eq!(path.len(), 2);
+        assert_eq!(path.last().map(|p| p[0]), Some(6));
+    }
+
+    #[test]
+    fn path_to_nearest_skips_the_origin() {
+        let map = Map::new(3, 3);
+        let path = map.path_to_nearest([1, 1], |_| true, |_| true).unwrap();
+        assert_eq!(path.len(), 1);
+        assert_ne!(path[0], [1, 1]);
+    }
+
+    #[test]
+    fn path_to_nearest_fails_without_a_goal() {
+        let map = Map::new(4, 4);
+        assert_eq!(map.path_to_nearest([0, 0], |_| true, |p| p[0] > 10), None);
+    }
+}
@@ -1,10 +1,31 @@
-use rand;
+use rand::{Rng, SeedableRng, StdRng};
 use std::time::SystemTime;
 use noise::{Seedable, RangeFunction, Worley, NoiseModule};
 use super::map::Map;
 use super::tile::{TileType, FloorType, WallOrientation, WallType, DEFAULT_WALL_TYPE};
+use super::{MAP_WIDTH, MAP_HEIGHT};
 
 const NOISE_SCALE: f32 = 0.2;
+const FLOODED_BIOME_CHANCE: u32 = 4;
+const FLOOD_THRESHOLD: f32 = 0.04;
+const MIN_MAP_WIDTH: usize = 40;
+const MAX_MAP_WIDTH: usize = 72;
+const MIN_MAP_HEIGHT: usize = 24;
+const MAX_MAP_HEIGHT: usize = 40;
+
+// keeps the rolls for map sizes apart from the rolls for biomes
+const WIDTH_SALT: usize = 0x5749_4454;
+const HEIGHT_SALT: usize = 0x4845_4947;
+
+/// The broad categories of terrain that a map can be generated as.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Biome {
+    /// Dry, open caves.
+    Caverns,
+
+    /// Caves whose edges are filled with pools of water.
+    Flooded,
+}
 
 /// Builds maps.
 pub struct MapBuilder {
@@ -13,33 +34,48 @@ pub struct MapBuilder {
 }
 
 impl MapBuilder {
-    /// Creates and returns a new instance of the MapBuilder struct.
-    pub fn new() -> MapBuilder {
+    /// Creates and returns a new instance of the MapBuilder struct, which
+    /// always builds the same maps for the same seed.
+    pub fn with_seed(seed: usize) -> MapBuilder {
         MapBuilder {
-            seed: rand::random::<usize>(),
+            seed: seed,
             current_offset: [0; 2],
         }
     }
 
-    /// Generates a new map.
+    /// Generates the map that the game starts in.
     pub fn create(&mut self) -> Map {
         self.create_offset([0, 0])
     }
 
-    /// Creates a new map at the specified world offset.
+    /// Creates a new map at the specified world offset, relative to the
+    /// current one.  Its size is chosen by `size`.
     pub fn create_offset(&mut self, offset: [i32; 2]) -> Map {
+        let size = [
+            self.column_width(self.current_offset[0] + offset[0]),
+            self.row_height(self.current_offset[1] + offset[1]),
+        ];
+        self.create_offset_sized(offset, size)
+    }
+
+    /// Creates a new map at the specified world offset, relative to the
+    /// current one, with the provided width and height in tiles, such as for
+    /// a small vault or a large cavern.  A map sized differently from the
+    /// one `size` would choose keeps the edge facing the starting map where
+    /// it would otherwise be, so that it lines up with its neighbor on that
+    /// side.
+    pub fn create_offset_sized(&mut self, offset: [i32; 2], size: [usize; 2]) -> Map {
         // increment the current offset by the values in the one provided
         self.current_offset = [
             self.current_offset[0] + offset[0],
             self.current_offset[1] + offset[1],
         ];
 
-        let (offset_x, offset_y) = (self.current_offset[0] as f32, self.current_offset[1] as f32);
+        let origin = self.origin(size);
+        let flooded = self.biome() == Biome::Flooded;
 
         let timer = SystemTime::now();
-        let mut map = Map::new();
-
-        let (width, height) = (map.width() as f32, map.height() as f32);
+        let mut map = Map::new(size[0], size[1]);
 
         // generate dungeon layout
         let dungeon_noise = Worley::<f32>::new().set_seed(self.seed);
@@ -48,13 +84,15 @@ impl MapBuilder {
             let (x, y) = (t.position[0] as f32, t.position[1] as f32);
             let noise_value = dungeon_noise.get(
                 [
-                    (((width - 1.0) * offset_x) + x) * NOISE_SCALE,
-                    (((height - 1.0) * offset_y) + y) * NOISE_SCALE,
+                    (origin[0] + x) * NOISE_SCALE,
+                    (origin[1] + y) * NOISE_SCALE,
                     1.0,
                 ],
             );
             if noise_value < 0.001 {
                 t.tile_type = TileType::Wall(WallOrientation::Top, WallType::Stone);
+            } else if flooded && noise_value < FLOOD_THRESHOLD {
+                t.tile_type = TileType::Floor(FloorType::Water);
             } else {
                 t.tile_type = TileType::Floor(FloorType::Dirt);
             }
@@ -88,4 +126,191 @@ impl MapBuilder {
         }
         map
     }
+
+    /// Returns how far the current map is from the one the game started in,
+    /// measured in number of maps.
+    pub fn depth(&self) -> u32 {
+        self.current_offset[0].abs().max(self.current_offset[1].abs()) as u32
+    }
+
+    /// Returns the biome of the current map.  The starting map is always
+    /// made up of caverns; the biomes of the rest are determined by the seed.
+    pub fn biome(&self) -> Biome {
+        if self.current_offset == [0, 0] {
+            return Biome::Caverns;
+        }
+
+        let seed = [
+            self.seed,
+            self.current_offset[0] as usize,
+            self.current_offset[1] as usize,
+        ];
+        let mut rng: StdRng = SeedableRng::from_seed(&seed[..]);
+        if rng.gen_weighted_bool(FLOODED_BIOME_CHANCE) {
+            Biome::Flooded
+        } else {
+            Biome::Caverns
+        }
+    }
+
+    /// Returns the width and height, in tiles, of the current map, unless it
+    /// was given a size of its own by `create_offset_sized`.  The starting
+    /// map is always `MAP_WIDTH` by `MAP_HEIGHT`; the rest are sized
+    /// by the seed.  Every map in the same column is as wide as the others,
+    /// and every map in the same row as tall, so that the edges of
+    /// neighboring maps line up.
+    pub fn size(&self) -> [usize; 2] {
+        [
+            self.column_width(self.current_offset[0]),
+            self.row_height(self.current_offset[1]),
+        ]
+    }
+
+    fn column_width(&self, column: i32) -> usize {
+        if column == 0 {
+            return MAP_WIDTH;
+        }
+        let seed = [self.seed, column as usize, WIDTH_SALT];
+        let mut rng: StdRng = SeedableRng::from_seed(&seed[..]);
+        rng.gen_range(MIN_MAP_WIDTH, MAX_MAP_WIDTH + 1)
+    }
+
+    fn row_height(&self, row: i32) -> usize {
+        if row == 0 {
+            return MAP_HEIGHT;
+        }
+        let seed = [self.seed, row as usize, HEIGHT_SALT];
+        let mut rng: StdRng = SeedableRng::from_seed(&seed[..]);
+        rng.gen_range(MIN_MAP_HEIGHT, MAX_MAP_HEIGHT + 1)
+    }
+
+    /// Returns where the top left tile of the current map lies in the world,
+    /// in tiles, given its size.  Neighboring maps share the tiles along
+    /// their common edge, which is where the player crosses from one to the
+    /// other.
+    fn origin(&self, size: [usize; 2]) -> [f32; 2] {
+        let mut origin = [
+            span(self.current_offset[0], |c| self.column_width(c)),
+            span(self.current_offset[1], |r| self.row_height(r)),
+        ];
+
+        // maps west or north of the start face it with their far edge
+        let seeded = self.size();
+        for axis in 0..2 {
+            if self.current_offset[axis] < 0 {
+                origin[axis] += seeded[axis] as f32 - size[axis] as f32;
+            }
+        }
+        origin
+    }
+}
+
+/// Returns how far, in tiles, the column or row at the provided index starts
+/// from the starting map's, given the size of each.
+fn span<F: Fn(i32) -> usize>(index: i32, size: F) -> f32 {
+    let distance: usize = if index >= 0 {
+        (0..index).map(|i| size(i) - 1).sum()
+    } else {
+        (index..0).map(|i| size(i) - 1).sum()
+    };
+    if index >= 0 {
+        distance as f32
+    } else {
+        -(distance as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_wall(map: &Map, position: [i32; 2]) -> bool {
+        match map.get_at(position).map(|t| &t.tile_type) {
+            Some(&TileType::Wall(_, _)) => true,
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn starting_map_has_default_size() {
+        let map = MapBuilder::with_seed(42).create();
+        assert_eq!([map.width(), map.height()], [MAP_WIDTH, MAP_HEIGHT]);
+    }
+
+    #[test]
+    fn sizes_stay_within_bounds() {
+        for seed in 0..20 {
+            let mut builder = MapBuilder::with_seed(seed);
+            for _ in 0..4 {
+                let map = builder.create_offset([1, 1]);
+                assert_eq!([map.width(), map.height()], builder.size());
+                assert!(map.width() >= MIN_MAP_WIDTH && map.width() <= MAX_MAP_WIDTH);
+                assert!(map.height() >= MIN_MAP_HEIGHT && map.height() <= MAX_MAP_HEIGHT);
+            }
+        }
+    }
+
+    #[test]
+    fn neighbors_share_their_edges() {
+        for seed in 0..5 {
+            let mut builder = MapBuilder::with_seed(seed);
+            let start = builder.create();
+            let east = builder.create_offset([1, 0]);
+            assert_eq!(start.height(), east.height());
+            let last = start.width() as i32 - 1;
+            for y in 0..start.height() as i32 {
+                assert_eq!(is_wall(&start, [last, y]), is_wall(&east, [0, y]));
+            }
+
+            let south = builder.create_offset([0, 1]);
+            assert_eq!(east.width(), south.width());
+            let last = east.height() as i32 - 1;
+            for x in 0..east.width() as i32 {
+                assert_eq!(is_wall(&east, [x, last]), is_wall(&south, [x, 0]));
+            }
+
+            let west = builder.create_offset([-1, 0]);
+            assert_eq!(south.height(), west.height());
+            let last = west.width() as i32 - 1;
+            for y in 0..west.height() as i32 {
+                assert_eq!(is_wall(&west, [last, y]), is_wall(&south, [0, y]));
+            }
+        }
+    }
+    #[test]
+    fn explicit_sizes_are_honored() {
+        let mut builder = MapBuilder::with_seed(42);
+        let vault = builder.create_offset_sized([1, 0], [12, 8]);
+        assert_eq!([vault.width(), vault.height()], [12, 8]);
+        let cavern = builder.create_offset_sized([1, 0], [120, 70]);
+        assert_eq!([cavern.width(), cavern.height()], [120, 70]);
+    }
+
+    #[test]
+    fn sized_maps_share_their_edges() {
+        for seed in 0..5 {
+            let mut builder = MapBuilder::with_seed(seed);
+            let start = builder.create();
+            let last = start.width() as i32 - 1;
+
+            // a small vault to the east starts where the start map ends
+            let vault = builder.create_offset_sized([1, 0], [12, 8]);
+            for y in 0..vault.height() as i32 {
+                assert_eq!(is_wall(&start, [last, y]), is_wall(&vault, [0, y]));
+            }
+
+            // a large cavern to the west ends where the start map begins
+            builder.create_offset([-1, 0]);
+            let cavern = builder.create_offset_sized([-1, 0], [120, 70]);
+            let last = cavern.width() as i32 - 1;
+            for y in 0..start.height() as i32 {
+                assert_eq!(is_wall(&cavern, [last, y]), is_wall(&start, [0, y]));
+            }
+
+            // and sized maps leave the seeded sizes of the rest alone
+            let back = builder.create_offset([1, 0]);
+            assert_eq!([back.width(), back.height()], [start.width(), start.height()]);
+            assert_eq!(back.to_ascii(), start.to_ascii());
+        }
+    }
 }
@@ -12,7 +12,7 @@ pub const MESSAGE_COLOR_BACKGROUND: [f32; 4] = [0.5, 0.5, 0.5, 1.0];
 
 /// Represents different styles of message, indicating how text should
 /// be displayed.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum MessageType {
     Normal,
     Danger,
@@ -21,7 +21,7 @@ pub enum MessageType {
 }
 
 /// A message that can be displayed to the player.
-#[derive(Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
     /// The contents of the message
     pub contents: String,
@@ -4,7 +4,13 @@
 
 mod actor;
 mod actors;
+mod animation;
+mod camera;
+mod combat;
+mod command;
+mod description;
 mod entity;
+mod faction;
 mod game_controller;
 mod game_view;
 mod item;
@@ -13,30 +19,51 @@ mod tile;
 mod map_builder;
 mod texture_mapper;
 mod message;
+mod noise;
+mod perception;
+mod projectile;
+mod replay;
+mod rng;
 mod sprite_info;
+mod script;
+pub mod snapshot;
+mod spawner;
+mod status_effect;
+mod tween;
 
 use std::collections::{HashMap, VecDeque};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::BuildHasherDefault;
+use std::rc::Rc;
 use mopa;
 pub use uuid::Uuid;
 pub use self::game_controller::GameController;
 pub use self::game_view::GameView;
-pub use self::actor::Actor;
+pub use self::command::{Command, Weapon, LONG_WAIT_TURNS};
+pub use self::replay::{Replay, ReplayPlayer};
+pub use self::actor::{Actor, ActorType};
 pub use self::entity::Entity;
 pub use self::item::Item;
 pub use self::map::Map;
 pub use self::tile::Tile;
-pub use self::map_builder::MapBuilder;
+pub use self::map_builder::{Biome, MapBuilder};
 pub use self::texture_mapper::TextureMapper;
 pub use self::message::Message;
+pub use self::noise::Noise;
 pub use self::sprite_info::SpriteInfo;
 
-/// The width of any given map in number of tiles.
-pub const MAP_WIDTH: i32 = 56;
+/// The width, in number of tiles, of the map the game starts in.
+pub const MAP_WIDTH: usize = 56;
 
-/// The height of any given map in number of tiles.
-pub const MAP_HEIGHT: i32 = 32;
+/// The height, in number of tiles, of the map the game starts in.
+pub const MAP_HEIGHT: usize = 32;
+
+/// A map keyed by ID.  Unlike a regular HashMap, it is always iterated in
+/// the same order given the same IDs, so that sessions can be replayed.
+pub type IdMap<V> = HashMap<Uuid, V, BuildHasherDefault<DefaultHasher>>;
 
 /// The range of directions of possible movement.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum MovementDirection {
     /// Indicates that the subject should move to the north.
     Up,
@@ -63,6 +90,23 @@ pub enum MovementDirection {
     DownRight,
 }
 
+impl MovementDirection {
+    /// Returns the direction pointing the opposite way.
+    pub fn opposite(&self) -> MovementDirection {
+        use self::MovementDirection::*;
+        match *self {
+            Up => Down,
+            Down => Up,
+            Left => Right,
+            Right => Left,
+            UpLeft => DownRight,
+            UpRight => DownLeft,
+            DownLeft => UpRight,
+            DownRight => UpLeft,
+        }
+    }
+}
+
 /// The possible results of an attempt by a Movable implementor to
 /// move in a particular direction.
 pub enum MovementResult {
@@ -114,23 +158,27 @@ pub struct GameState {
     /// The ID of the actor instance that represents the current player.
     pub player_id: Uuid,
 
-    /// Describes the space in which the game's elements take place.
-    pub map: Map,
+    /// Describes the space in which the game's elements take place.  It is
+    /// shared with the scripts of scripted actors while they update.
+    pub map: Rc<Map>,
 
     /// The Actors (enemies, NPCs, etc.) currently in the map.
-    pub actors: HashMap<Uuid, Box<Actor>>,
+    pub actors: IdMap<Box<Actor>>,
 
     /// The Entities (interactive objects, terrain, etc.) currently in the map.
-    pub entities: HashMap<Uuid, Box<Entity>>,
+    pub entities: IdMap<Box<Entity>>,
 
     /// The items currently present in the map.
-    pub items: HashMap<Uuid, Item>,
+    pub items: IdMap<Item>,
 
     /// A queue of messages stored for display to the player.
     pub messages: VecDeque<Message>,
 
     /// Indicates whether or not to show the message queue to the player.
     pub show_messages: bool,
+
+    /// How the game's factions feel about each other and the player.
+    pub factions: faction::Factions,
 }
 
 /// Helper function for mapping a starting position to a new position given a direction
@@ -165,6 +213,32 @@ fn map_direction_to_position(
     ]
 }
 
+/// Helper function for finding the direction that leads most directly from
+/// one position toward another.  Returns `None` if the positions are the same.
+fn direction_toward(origin: [i32; 2], target: [i32; 2]) -> Option<MovementDirection> {
+    use self::MovementDirection::*;
+    match (
+        (target[0] - origin[0]).signum(),
+        (target[1] - origin[1]).signum(),
+    ) {
+        (0, -1) => Some(Up),
+        (0, 1) => Some(Down),
+        (-1, 0) => Some(Left),
+        (1, 0) => Some(Right),
+        (-1, -1) => Some(UpLeft),
+        (1, -1) => Some(UpRight),
+        (-1, 1) => Some(DownLeft),
+        (1, 1) => Some(DownRight),
+        _ => None,
+    }
+}
+
+/// Helper function returning the number of moves (including diagonal ones)
+/// needed to get from one position to another on an empty map.
+fn chebyshev_distance(a: [i32; 2], b: [i32; 2]) -> i32 {
+    (a[0] - b[0]).abs().max((a[1] - b[1]).abs())
+}
+
 pub fn try_move<M: Movable>(
     subject: &M,
     map: &Map,
@@ -193,31 +267,29 @@ impl GameState {
     /// Creates and returns a new instance of the GameState struct.
     pub fn new(map_builder: &mut MapBuilder) -> GameState {
         GameState {
-            player_id: Uuid::new_v4(),
-            map: map_builder.create(),
-            actors: HashMap::<Uuid, Box<Actor>>::new(),
-            entities: HashMap::<Uuid, Box<Entity>>::new(),
-            items: HashMap::<Uuid, Item>::new(),
+            player_id: rng::new_id(),
+            map: Rc::new(map_builder.create()),
+            actors: IdMap::<Box<Actor>>::default(),
+            entities: IdMap::<Box<Entity>>::default(),
+            items: IdMap::<Item>::default(),
             messages: VecDeque::<Message>::new(),
             show_messages: true,
+            factions: faction::Factions::new(),
         }.add_player()
     }
 
     fn add_player(mut self) -> GameState {
-        use rand;
         use rand::distributions::{IndependentSample, Range};
         use self::actor::ActorType;
         use self::tile::{FloorType, TileType};
 
-        let mut player = actor::create(&ActorType::Player);
+        let mut player = actor::create(&ActorType::Player).expect("Unable to create the player");
 
-        let mut rng = rand::thread_rng();
         let range_x = Range::<i32>::new(0, self.map.width() as i32);
         let range_y = Range::<i32>::new(0, self.map.height() as i32);
 
         loop {
-            let x = range_x.ind_sample(&mut rng);
-            let y = range_y.ind_sample(&mut rng);
+            let (x, y) = rng::with(|rng| (range_x.ind_sample(rng), range_y.ind_sample(rng)));
             player.set_x(x);
             player.set_y(y);
 
@@ -0,0 +1,83 @@
+use std::collections::{BinaryHeap, HashMap};
+use uuid::Uuid;
+use super::tile::TileType;
+use super::Map;
+
+/// The loudness lost by a sound for each open tile that it travels across.
+const OPEN_ATTENUATION: u32 = 1;
+
+/// The loudness lost by a sound when it passes through a wall.
+const WALL_ATTENUATION: u32 = 6;
+
+/// A sound made somewhere in the map, which nearby actors may be able to hear.
+#[derive(Debug, Clone)]
+pub struct Noise {
+    /// Where the sound was made.
+    pub position: [i32; 2],
+
+    /// How loud the sound is where it was made.
+    pub loudness: u32,
+
+    /// The ID of the actor that made the sound, if any.
+    pub source: Option<Uuid>,
+
+    /// A description of the sound, used to tell the player what they heard.
+    pub description: String,
+}
+
+impl Noise {
+    /// Creates and returns a new instance of the Noise struct.
+    pub fn new(position: [i32; 2], loudness: u32, source: Option<Uuid>, description: &str) -> Noise {
+        Noise {
+            position: position,
+            loudness: loudness,
+            source: source,
+            description: String::from(description),
+        }
+    }
+
+    /// Spreads the sound outward from where it was made, returning how loud
+    /// it is at each tile it reaches.  Sound loses a little loudness for every
+    /// tile it crosses, and a lot for every wall it passes through.
+    pub fn propagate(&self, map: &Map) -> HashMap<[i32; 2], u32> {
+        let mut volumes = HashMap::<[i32; 2], u32>::new();
+        let mut frontier = BinaryHeap::<(u32, [i32; 2])>::new();
+
+        if map.get_at(self.position).is_none() || self.loudness == 0 {
+            return volumes;
+        }
+        volumes.insert(self.position, self.loudness);
+        frontier.push((self.loudness, self.position));
+
+        // always continue from the loudest tile, so that each tile is
+        // finalized with the loudest volume that can reach it
+        while let Some((volume, position)) = frontier.pop() {
+            if volumes.get(&position).map_or(false, |v| *v > volume) {
+                continue;
+            }
+            for dx in -1..2 {
+                for dy in -1..2 {
+                    let neighbor = [position[0] + dx, position[1] + dy];
+                    let attenuation = match map.get_at(neighbor) {
+                        Some(tile) => {
+                            match tile.tile_type {
+                                TileType::Wall(_, _) => WALL_ATTENUATION,
+                                _ => OPEN_ATTENUATION,
+                            }
+                        }
+                        None => continue,
+                    };
+                    if volume <= attenuation {
+                        continue;
+                    }
+                    let neighbor_volume = volume - attenuation;
+                    if volumes.get(&neighbor).map_or(true, |v| *v < neighbor_volume) {
+                        volumes.insert(neighbor, neighbor_volume);
+                        frontier.push((neighbor_volume, neighbor));
+                    }
+                }
+            }
+        }
+        volumes
+    }
+}
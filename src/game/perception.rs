@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use bresenham::Bresenham;
+use uuid::Uuid;
+use super::actor::ActorInfo;
+use super::noise::Noise;
+use super::tile::TileType;
+use super::Map;
+
+/// The number of ticks that an actor remembers where it last perceived
+/// another actor.
+const MEMORY_DURATION: u32 = 30;
+
+/// The last place that an actor was seen or heard.
+struct Sighting {
+    position: [i32; 2],
+    age: u32,
+}
+
+/// Keeps track of what an actor is able to see and hear, and remembers where
+/// it last perceived the other actors in the map.
+pub struct Perception {
+    /// The greatest distance, in tiles, that the actor can see.
+    pub sight_radius: u32,
+
+    /// The greatest distance, in tiles, that the actor can hear.
+    pub hearing_radius: u32,
+
+    memory: HashMap<Uuid, Sighting>,
+}
+
+impl Perception {
+    /// Creates and returns a new instance of the Perception struct.
+    pub fn new(sight_radius: u32, hearing_radius: u32) -> Perception {
+        Perception {
+            sight_radius: sight_radius,
+            hearing_radius: hearing_radius,
+            memory: HashMap::<Uuid, Sighting>::new(),
+        }
+    }
+
+    /// Returns a value indicating whether the target is within sight of an
+    /// actor at the origin.
+    pub fn can_see(&self, map: &Map, origin: [i32; 2], target: [i32; 2]) -> bool {
+        let (unobscured, distance) = ray_visible(map, origin, target);
+        unobscured && distance <= self.sight_radius
+    }
+
+    /// Returns the actors that can be seen from the origin, remembering their
+    /// positions and forgetting any sightings that have grown too old.  The
+    /// actor standing at the origin is not included.
+    pub fn observe(&mut self, map: &Map, origin: [i32; 2], actors: &[ActorInfo]) -> Vec<ActorInfo> {
+        for sighting in self.memory.values_mut() {
+            sighting.age += 1;
+        }
+        self.memory.retain(|_, s| s.age <= MEMORY_DURATION);
+
+        let visible: Vec<ActorInfo> = actors
+            .iter()
+            .filter(|a| {
+                a.position != origin && self.can_see(map, origin, a.position)
+            })
+            .cloned()
+            .collect();
+
+        for actor in &visible {
+            self.remember(actor.id, actor.position);
+        }
+        visible
+    }
+
+    /// Attempts to hear the provided noise, which has reached the listener's
+    /// position with the given volume, remembering where the noise came from
+    /// if it was heard.  Returns a value indicating whether the noise was heard.
+    pub fn hear(&mut self, listener: [i32; 2], noise: &Noise, volume: u32) -> bool {
+        let distance = super::chebyshev_distance(listener, noise.position) as u32;
+        let heard = volume > 0 && distance <= self.hearing_radius;
+        if heard {
+            if let Some(source) = noise.source {
+                self.remember(source, noise.position);
+            }
+        }
+        heard
+    }
+
+    /// Returns the position at which the actor with the provided ID was last
+    /// seen or heard, if it is still remembered.
+    pub fn last_known_position(&self, id: &Uuid) -> Option<[i32; 2]> {
+        self.memory.get(id).map(|s| s.position)
+    }
+
+    /// Forgets where the actor with the provided ID was last perceived.
+    pub fn forget(&mut self, id: &Uuid) {
+        self.memory.remove(id);
+    }
+
+    /// Remembers that the actor with the provided ID was perceived at the
+    /// provided position.
+    pub fn remember(&mut self, id: Uuid, position: [i32; 2]) {
+        self.memory.insert(
+            id,
+            Sighting {
+                position: position,
+                age: 0,
+            },
+        );
+    }
+}
+
+/// Returns a tuple indicating both whether the view from the origin to the
+/// target is uninterrupted, and also the distance between the two.
+pub fn ray_visible(map: &Map, origin: [i32; 2], target: [i32; 2]) -> (bool, u32) {
+    let origin_point = (origin[0], origin[1]);
+    let target_point = (target[0], target[1]);
+
+    if origin == target {
+        return (true, 0);
+    }
+
+    // first iteration will be on starting position, and will increment this to 0.
+    let mut distance = -1i32;
+
+    let mut ray = Bresenham::new(origin_point, target_point);
+
+    while let Some(coords) = ray.next() {
+        distance += 1;
+        if let Some(ray_node) = map.get_at([coords.0, coords.1]) {
+            match ray_node.tile_type {
+                // visibility means not being blocked by a wall
+                TileType::Wall(_, _) => {
+                    return (false, distance as u32);
+                }
+                _ => {}
+            }
+        }
+    }
+    (true, distance as u32)
+}
@@ -0,0 +1,149 @@
+use std::time::Instant;
+use bresenham::Bresenham;
+use uuid::Uuid;
+use super::actor::ActorInfo;
+use super::tile::TileType;
+use super::Map;
+
+/// How quickly projectiles are animated, in tiles per second.
+const TILES_PER_SECOND: f64 = 30.0;
+
+/// How many fading copies of a projectile are drawn behind it.
+const TRAIL_LENGTH: usize = 4;
+
+/// How far apart, in tiles, the copies of a projectile in its trail are.
+const TRAIL_SPACING: f64 = 0.35;
+
+/// The route taken by a projectile, and whoever it struck.
+#[derive(Debug, Clone)]
+pub struct ProjectilePath {
+    /// The tiles that the projectile passed through, not including the
+    /// tile it was launched from.
+    pub tiles: Vec<[i32; 2]>,
+
+    /// The ID of the actor that the projectile struck, if any.
+    pub hit: Option<Uuid>,
+}
+
+/// Traces the path of a projectile launched from the origin toward the
+/// target.  The projectile keeps flying past the target until it has
+/// travelled `range` tiles, hits a wall, or strikes an actor.
+pub fn trace(
+    map: &Map,
+    origin: [i32; 2],
+    target: [i32; 2],
+    range: u32,
+    actors: &[ActorInfo],
+) -> ProjectilePath {
+    let mut path = ProjectilePath {
+        tiles: Vec::new(),
+        hit: None,
+    };
+    if origin == target || range == 0 {
+        return path;
+    }
+
+    // extend the line past the target so that the projectile can fly beyond it
+    let delta = [target[0] - origin[0], target[1] - origin[1]];
+    let scale = range as i32 / delta[0].abs().max(delta[1].abs()) + 1;
+    let end = [origin[0] + delta[0] * scale, origin[1] + delta[1] * scale];
+
+    for point in Bresenham::new((origin[0], origin[1]), (end[0], end[1]))
+        .skip(1)
+        .take(range as usize)
+    {
+        let position = [point.0, point.1];
+        match map.get_at(position).map(|t| t.tile_type.clone()) {
+            Some(TileType::Wall(_, _)) |
+            Some(TileType::Empty) |
+            None => break,
+            _ => {}
+        }
+        path.tiles.push(position);
+        if let Some(actor) = actors.iter().find(|a| a.position == position) {
+            path.hit = Some(actor.id);
+            break;
+        }
+    }
+    path
+}
+
+/// A projectile in flight, kept around until it has finished being drawn.
+pub struct ProjectileAnimation {
+    origin: [i32; 2],
+    tiles: Vec<[i32; 2]>,
+    color: [f32; 4],
+    started: Instant,
+}
+
+impl ProjectileAnimation {
+    /// Creates and returns a new instance of the ProjectileAnimation struct,
+    /// beginning now.
+    pub fn new(origin: [i32; 2], path: &ProjectilePath, color: [f32; 4]) -> ProjectileAnimation {
+        ProjectileAnimation {
+            origin: origin,
+            tiles: path.tiles.clone(),
+            color: color,
+            started: Instant::now(),
+        }
+    }
+
+    /// Returns the color that the projectile should be drawn with.
+    pub fn color(&self) -> [f32; 4] {
+        self.color
+    }
+
+    /// Returns the position of the projectile, in fractional tiles, or
+    /// `None` if it has reached the end of its path.
+    pub fn position(&self) -> Option<[f64; 2]> {
+        self.position_at(self.progress())
+    }
+
+    /// Returns the positions, in fractional tiles, that the projectile passed
+    /// through just before reaching its current position, nearest first.
+    pub fn trail(&self) -> Vec<[f64; 2]> {
+        let progress = self.progress();
+        if progress.floor() as usize >= self.tiles.len() {
+            return Vec::new();
+        }
+        (1..=TRAIL_LENGTH)
+            .map(|i| progress - i as f64 * TRAIL_SPACING)
+            .take_while(|p| *p >= 0.0)
+            .filter_map(|p| self.position_at(p))
+            .collect()
+    }
+
+    /// Returns how many tiles the projectile has travelled so far.
+    fn progress(&self) -> f64 {
+        let elapsed = self.started.elapsed();
+        let seconds = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 / 1_000_000_000.0;
+        seconds * TILES_PER_SECOND
+    }
+
+    /// Returns the position of the projectile once it has travelled the
+    /// provided number of tiles, or `None` if that is past the end of its
+    /// path.
+    fn position_at(&self, progress: f64) -> Option<[f64; 2]> {
+        let index = progress.floor() as usize;
+        if index >= self.tiles.len() {
+            return None;
+        }
+        let from = if index == 0 {
+            self.origin
+        } else {
+            self.tiles[index - 1]
+        };
+        let to = self.tiles[index];
+        let fraction = progress - progress.floor();
+        Some([
+            from[0] as f64 + (to[0] - from[0]) as f64 * fraction,
+            from[1] as f64 + (to[1] - from[1]) as f64 * fraction,
+        ])
+    }
+
+    /// Returns a value indicating whether the projectile has reached the
+    /// end of its path.
+    pub fn finished(&self) -> bool {
+        self.position().is_none()
+    }
+}
@@ -0,0 +1,335 @@
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use piston::input::GenericEvent;
+use serde_json;
+use input::Control;
+use super::{Command, GameController};
+
+/// Identifies the layout of replay files, so that old ones can be recognized.
+const REPLAY_VERSION: u32 = 1;
+
+/// The time between each command played back at normal speed.
+const PLAYBACK_STEP_MILLIS: u64 = 150;
+
+/// The number of commands played back with each update while fast-forwarding.
+const FAST_FORWARD_COMMANDS: usize = 10;
+
+/// The first line of a replay file, describing the session that it recorded.
+#[derive(Serialize, Deserialize)]
+struct Header {
+    version: u32,
+    seed: usize,
+}
+
+/// A command given during a recorded session, and the tick that it was
+/// given on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedCommand {
+    /// The number of ticks that had passed when the command was given.
+    pub tick: u64,
+
+    /// The command that was given.
+    pub command: Command,
+}
+
+/// Writes the commands given during a session to a replay file as they
+/// happen, so that nothing is lost if the game crashes.
+////
+/// Replay files hold one JSON value per line: a header holding the seed,
+/// followed by each recorded command.
+pub struct Recorder {
+    writer: BufWriter<File>,
+}
+
+impl Recorder {
+    /// Creates a new replay file at the provided path for a session begun
+    /// with the provided seed, replacing any file already there.
+    pub fn create(path: &Path, seed: usize) -> Result<Recorder, String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|why| {
+                format!("Unable to create replay folder: {:?}", why)
+            })?;
+        }
+        let file = File::create(path).map_err(|why| {
+            format!("Unable to create replay file: {:?}", why)
+        })?;
+        let mut recorder = Recorder { writer: BufWriter::new(file) };
+        recorder.write(&Header {
+            version: REPLAY_VERSION,
+            seed: seed,
+        })?;
+        Ok(recorder)
+    }
+
+    /// Adds a command to the replay file.
+    pub fn record(&mut self, tick: u64, command: &Command) {
+        let recorded = RecordedCommand {
+            tick: tick,
+            command: command.clone(),
+        };
+        if let Err(why) = self.write(&recorded) {
+            error!("{}", why);
+        }
+    }
+
+    fn write<T: ::serde::Serialize>(&mut self, value: &T) -> Result<(), String> {
+        serde_json::to_writer(&mut self.writer, value)
+            .map_err(|why| format!("Unable to write to replay file: {}", why))?;
+        self.writer
+            .write_all(b"\n")
+            .and_then(|_| self.writer.flush())
+            .map_err(|why| format!("Unable to write to replay file: {:?}", why))
+    }
+}
+
+/// A session read back from a replay file.
+pub struct Replay {
+    /// The seed that the session began with.
+    pub seed: usize,
+
+    /// The commands given during the session, in order.
+    pub commands: Vec<RecordedCommand>,
+}
+
+impl Replay {
+    /// Reads the replay file at the provided path.
+    pub fn load(path: &Path) -> Result<Replay, String> {
+        let file = File::open(path).map_err(|why| {
+            format!("Unable to open replay file: {:?}", why)
+        })?;
+        let mut lines = BufReader::new(file).lines();
+
+        let header: Header = match lines.next() {
+            Some(Ok(line)) => {
+                serde_json::from_str(line.as_str()).map_err(|why| {
+                    format!("Unable to parse replay header: {}", why)
+                })?
+            }
+            _ => return Err(String::from("The replay file is empty.")),
+        };
+        if header.version != REPLAY_VERSION {
+            return Err(format!(
+                "The replay file is version {}, but only version {} can be played.",
+                header.version,
+                REPLAY_VERSION
+            ));
+        }
+
+        let mut commands = Vec::<RecordedCommand>::new();
+        for (number, line) in lines.enumerate() {
+            let line = line.map_err(|why| format!("Unable to read replay file: {:?}", why))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            // a crash can leave the last line half-written, so stop there
+            match serde_json::from_str(line.as_str()) {
+                Ok(recorded) => commands.push(recorded),
+                Err(why) => {
+                    warn!("Replay stops at line {}: {}", number + 2, why);
+                    break;
+                }
+            }
+        }
+
+        Ok(Replay {
+            seed: header.seed,
+            commands: commands,
+        })
+    }
+}
+
+/// Feeds the commands from a replay to a GameController, with controls for
+/// pausing, stepping through one command at a time, and fast-forwarding.
+pub struct ReplayPlayer {
+    commands: VecDeque<RecordedCommand>,
+    paused: bool,
+    fast_forward: bool,
+    steps: usize,
+    last_step: Instant,
+}
+
+impl ReplayPlayer {
+    /// Creates and returns a new instance of the ReplayPlayer struct, ready
+    /// to play the provided replay from the beginning.
+    pub fn new(replay: Replay) -> ReplayPlayer {
+        ReplayPlayer {
+            commands: replay.commands.into_iter().collect(),
+            paused: false,
+            fast_forward: false,
+            steps: 0,
+            last_step: Instant::now(),
+        }
+    }
+
+    /// Responds to the controls for pausing, stepping, and fast-forwarding.
+    pub fn control(&mut self, controls: &[Control]) {
+        for control in controls {
+            match *control {
+                Control::PauseReplay => self.paused = !self.paused,
+                Control::StepReplay => {
+                    self.paused = true;
+                    self.steps += 1;
+                }
+                Control::FastForwardReplay => self.fast_forward = !self.fast_forward,
+                _ => {}
+            }
+        }
+    }
+
+    /// Plays whichever commands are due on each update event.
+    pub fn update<E: GenericEvent>(&mut self, event: &E, controller: &mut GameController) {
+        if event.update_args().is_some() {
+            self.play_due(controller);
+        }
+    }
+
+    /// Plays whichever commands are due, for frontends without events.
+    pub fn play_due(&mut self, controller: &mut GameController) {
+        let count = if self.steps > 0 {
+            self.steps
+        } else if self.paused {
+            0
+        } else if self.fast_forward {
+            FAST_FORWARD_COMMANDS
+        } else if self.last_step.elapsed() >= Duration::from_millis(PLAYBACK_STEP_MILLIS) {
+            1
+        } else {
+            0
+        };
+        self.steps = 0;
+        if count > 0 {
+            self.last_step = Instant::now();
+            self.play(count, controller);
+        }
+    }
+
+    /// Plays the next command, returning `false` if there are none left.
+    pub fn play_next(&mut self, controller: &mut GameController) -> bool {
+        match self.commands.pop_front() {
+            Some(recorded) => {
+                controller.play(&recorded);
+                if self.commands.is_empty() {
+                    info!("The replay has finished.");
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn play(&mut self, count: usize, controller: &mut GameController) {
+        for _ in 0..count {
+            if !self.play_next(controller) {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs::OpenOptions;
+    use std::path::PathBuf;
+    use std::process;
+    use super::*;
+    use super::super::MovementDirection;
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("replay-test-{}-{}.jsonl", process::id(), name))
+    }
+
+    fn commands() -> Vec<Command> {
+        vec![
+            Command::Move(MovementDirection::Up),
+            Command::Wait,
+            Command::Move(MovementDirection::DownLeft),
+            Command::Rest,
+            Command::WaitTurns(3),
+        ]
+    }
+
+    #[test]
+    fn recorded_commands_load_back() {
+        let path = temp_path("round-trip");
+        {
+            let mut recorder = Recorder::create(&path, 1234).unwrap();
+            for (tick, command) in commands().iter().enumerate() {
+                recorder.record(tick as u64 * 2, command);
+            }
+        }
+        let replay = Replay::load(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        assert_eq!(replay.seed, 1234);
+        let loaded: Vec<Command> = replay.commands.iter().map(|r| r.command.clone()).collect();
+        assert_eq!(loaded, commands());
+        let ticks: Vec<u64> = replay.commands.iter().map(|r| r.tick).collect();
+        assert_eq!(ticks, vec![0, 2, 4, 6, 8]);
+    }
+
+    #[test]
+    fn half_written_lines_end_the_replay() {
+        let path = temp_path("half-written");
+        {
+            let mut recorder = Recorder::create(&path, 1).unwrap();
+            recorder.record(0, &Command::Wait);
+        }
+        OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .and_then(|mut f| f.write_all(b"{\"tick\":1,\"comm"))
+            .unwrap();
+        let replay = Replay::load(&path).unwrap();
+        fs::remove_file(&path).ok();
+        assert_eq!(replay.commands.len(), 1);
+    }
+
+    #[test]
+    fn other_versions_are_refused() {
+        let path = temp_path("version");
+        File::create(&path)
+            .and_then(|mut f| f.write_all(b"{\"version\":999,\"seed\":1}\n"))
+            .unwrap();
+        let result = Replay::load(&path);
+        fs::remove_file(&path).ok();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn replays_repeat_the_session() {
+        let path = temp_path("session");
+        let mut original = GameController::with_seed(99);
+        original.record_to(&path).unwrap();
+        let session = vec![
+            Command::Move(MovementDirection::Up),
+            Command::Wait,
+            Command::Move(MovementDirection::Right),
+            Command::Move(MovementDirection::DownLeft),
+            Command::Wait,
+        ];
+        for command in session {
+            let tick = original.tick();
+            original.play(&RecordedCommand {
+                tick: tick,
+                command: command,
+            });
+        }
+        let replay = Replay::load(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        let mut repeated = GameController::with_seed(replay.seed);
+        for recorded in &replay.commands {
+            repeated.play(recorded);
+        }
+        assert_eq!(repeated.tick(), original.tick());
+        assert_eq!(repeated.player_position, original.player_position);
+        let messages = |c: &GameController| -> Vec<String> {
+            c.get_messages(20).into_iter().map(|m| m.contents).collect()
+        };
+        assert_eq!(messages(&repeated), messages(&original));
+    }
+}
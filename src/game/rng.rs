@@ -0,0 +1,39 @@
+use std::cell::RefCell;
+use rand::{self, Rng, SeedableRng, StdRng};
+use uuid::Uuid;
+
+thread_local! {
+    /// The source of every random decision made by the game.  Seeding it
+    /// makes a session repeatable, given the same commands.
+    static GAME_RNG: RefCell<StdRng> = RefCell::new(StdRng::new().expect("Unable to create RNG"));
+}
+
+/// Returns a seed chosen at random, for sessions that don't need to repeat
+/// an earlier one.
+pub fn random_seed() -> usize {
+    rand::random::<usize>()
+}
+
+/// Restarts the game's random number generator from the provided seed.
+pub fn reseed(seed: usize) {
+    GAME_RNG.with(|rng| *rng.borrow_mut() = SeedableRng::from_seed(&[seed][..]));
+}
+
+/// Calls the provided function with the game's random number generator.
+/// The function must not call back into this module.
+pub fn with<F, T>(f: F) -> T
+where
+    F: FnOnce(&mut StdRng) -> T,
+{
+    GAME_RNG.with(|rng| f(&mut *rng.borrow_mut()))
+}
+
+/// Creates a new random (version 4) ID, drawn from the game's random number
+/// generator so that IDs are the same each time a session is repeated.
+pub fn new_id() -> Uuid {
+    let mut bytes = [0u8; 16];
+    with(|rng| rng.fill_bytes(&mut bytes));
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    Uuid::from_bytes(&bytes).expect("Sixteen bytes always make a valid ID")
+}
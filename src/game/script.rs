@@ -0,0 +1,339 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::mem;
+use std::rc::Rc;
+use rhai::{Array, Dynamic, Engine, ImmutableString, Scope, AST};
+use super::actor::ActorInfo;
+use super::message::MessageType;
+use super::tile::{FloorType, TileType};
+use uuid::Uuid;
+use super::{Map, Message, Movable, MovementDirection, MovementResult, Noise, Positioned};
+
+/// The name of the hook called when an actor is created.
+pub const HOOK_ON_CREATE: &'static str = "on_create";
+
+/// The name of the hook called on each update tick.
+pub const HOOK_ON_UPDATE: &'static str = "on_update";
+
+/// The name of the hook called when an actor is interacted with.
+pub const HOOK_ON_INTERACT: &'static str = "on_interact";
+
+/// The name of the hook called before an actor is removed.
+pub const HOOK_ON_REMOVE: &'static str = "on_remove";
+
+const MAX_OPERATIONS: u64 = 50_000;
+const MAX_CALL_LEVELS: usize = 32;
+const MAX_STRING_SIZE: usize = 4096;
+const MAX_COLLECTION_SIZE: usize = 1024;
+
+thread_local! {
+    static ENGINE: Engine = create_engine();
+}
+
+/// A compiled script, along with the values it has chosen to remember
+/// between calls.
+pub struct ActorScript {
+    ast: AST,
+    hooks: HashSet<String>,
+    memory: HashMap<String, Dynamic>,
+}
+
+/// A snapshot of the game state that a script is allowed to see while one of
+/// its hooks runs, along with the changes the script asked for.
+#[derive(Default)]
+pub struct ScriptFrame {
+    /// The ID of the actor running the script.
+    pub id: Option<Uuid>,
+
+    /// The display name of the actor running the script.
+    pub name: String,
+
+    /// The position of the actor running the script.
+    pub position: [i32; 2],
+
+    /// The current health of the actor running the script.
+    pub health: i32,
+
+    /// Information about every actor in the map.
+    pub actors: Vec<ActorInfo>,
+
+    /// The current map, if the hook being run has access to it.  It is
+    /// shared with the game rather than copied for each call.
+    pub map: Option<Rc<Map>>,
+
+    /// Messages the script has queued for display to the player.
+    pub messages: VecDeque<Message>,
+
+    /// Noises the script has made.
+    pub noises: VecDeque<Noise>,
+
+    /// The ID of an adjacent actor that the script wants to attack.
+    pub attack_target: Option<Uuid>,
+
+    memory: HashMap<String, Dynamic>,
+}
+
+/// The handle through which scripts access their `ScriptFrame`.  This is the
+/// `actor` value that is passed to each hook.
+#[derive(Clone)]
+struct ScriptApi(Rc<RefCell<ScriptFrame>>);
+
+impl ActorScript {
+    /// Compiles the provided script source.
+    pub fn compile(source: &str) -> Result<ActorScript, String> {
+        let ast = ENGINE.with(|engine| engine.compile(source));
+        match ast {
+            Ok(ast) => {
+                let hooks = ast.iter_functions()
+                    .map(|f| String::from(f.name))
+                    .collect();
+                Ok(ActorScript {
+                    ast: ast,
+                    hooks: hooks,
+                    memory: HashMap::<String, Dynamic>::new(),
+                })
+            }
+            Err(why) => Err(format!("Unable to compile script: {}", why)),
+        }
+    }
+
+    /// Takes over the values remembered by another script, such as the one
+    /// this script replaced when its source was reloaded.
+    pub fn inherit_memory(&mut self, from: ActorScript) {
+        self.memory = from.memory;
+    }
+
+    /// Returns a value indicating whether the script defines the named hook.
+    pub fn has_hook(&self, hook: &str) -> bool {
+        self.hooks.contains(hook)
+    }
+
+    /// Runs the named hook against the provided frame, returning the frame
+    /// as the script left it.  Returns `None` if the script does not define
+    /// the hook.
+    pub fn call(&mut self, hook: &str, mut frame: ScriptFrame) -> Option<ScriptFrame> {
+        if !self.has_hook(hook) {
+            return None;
+        }
+
+        frame.memory = mem::replace(&mut self.memory, HashMap::new());
+        let api = ScriptApi(Rc::new(RefCell::new(frame)));
+
+        let ast = &self.ast;
+        let result = ENGINE.with(|engine| {
+            engine.call_fn::<Dynamic>(&mut Scope::new(), ast, hook, (api.clone(),))
+        });
+        if let Err(why) = result {
+            error!("Script hook {:?} failed: {}", hook, why);
+        }
+
+        let mut frame = api.0.replace(ScriptFrame::default());
+        self.memory = mem::replace(&mut frame.memory, HashMap::new());
+        Some(frame)
+    }
+}
+
+impl Positioned for ScriptFrame {
+    fn current_position(&self) -> [i32; 2] {
+        self.position
+    }
+}
+
+impl Movable for ScriptFrame {
+    fn move_toward(&mut self, dir: &MovementDirection) {
+        self.position = super::map_direction_to_position(self.position, dir, 1);
+    }
+
+    fn set_x(&mut self, x: i32) {
+        self.position = [x, self.position[1]];
+    }
+
+    fn set_y(&mut self, y: i32) {
+        self.position = [self.position[0], y];
+    }
+}
+
+impl ScriptFrame {
+    /// Attempts to move one space in the provided direction, returning a
+    /// description of what was found there.
+    fn try_step(&mut self, dir: MovementDirection) -> &'static str {
+        let destination = super::map_direction_to_position(self.position, &dir, 1);
+        if self.actors.iter().any(|a| a.position == destination) {
+            return "blocked";
+        }
+
+        let result = match self.map {
+            Some(ref map) => super::try_move(self, map, &dir, 1),
+            None => return "unknown",
+        };
+
+        match result {
+            MovementResult::Clear => {
+                self.move_toward(&dir);
+                "clear"
+            }
+            MovementResult::Wall => "wall",
+            MovementResult::Fluid => "fluid",
+            MovementResult::MapEdge(_) => "edge",
+        }
+    }
+
+    /// Describes the tile at the provided position.
+    fn tile_at(&self, position: [i32; 2]) -> &'static str {
+        let map = match self.map {
+            Some(ref map) => map,
+            None => return "unknown",
+        };
+        match map.get_at(position) {
+            Some(tile) => {
+                match tile.tile_type {
+                    TileType::Empty => "empty",
+                    TileType::Wall(_, _) => "wall",
+                    TileType::Floor(FloorType::Water) |
+                    TileType::Floor(FloorType::Mud) => "fluid",
+                    TileType::Floor(_) => "floor",
+                }
+            }
+            None => "edge",
+        }
+    }
+}
+
+fn parse_direction(name: &str) -> Option<MovementDirection> {
+    use super::MovementDirection::*;
+    match name {
+        "up" => Some(Up),
+        "down" => Some(Down),
+        "left" => Some(Left),
+        "right" => Some(Right),
+        "up_left" => Some(UpLeft),
+        "up_right" => Some(UpRight),
+        "down_left" => Some(DownLeft),
+        "down_right" => Some(DownRight),
+        _ => None,
+    }
+}
+
+fn parse_message_type(name: &str) -> MessageType {
+    match name {
+        "danger" => MessageType::Danger,
+        "benefit" => MessageType::Benefit,
+        "background" => MessageType::Background,
+        _ => MessageType::Normal,
+    }
+}
+
+/// Builds the script engine, limiting the resources that scripts can use
+/// and registering the functions through which they can affect the game.
+fn create_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(MAX_CALL_LEVELS);
+    engine.set_max_string_size(MAX_STRING_SIZE);
+    engine.set_max_array_size(MAX_COLLECTION_SIZE);
+    engine.set_max_map_size(MAX_COLLECTION_SIZE);
+    engine.on_print(|text| info!("[script] {}", text));
+
+    engine.register_type_with_name::<ScriptApi>("Actor");
+
+    engine.register_get("id", |api: &mut ScriptApi| -> String {
+        api.0.borrow().id.map_or(String::new(), |id| id.to_string())
+    });
+    engine.register_get("name", |api: &mut ScriptApi| -> String {
+        api.0.borrow().name.clone()
+    });
+    engine.register_get("x", |api: &mut ScriptApi| -> i64 {
+        api.0.borrow().position[0] as i64
+    });
+    engine.register_get("y", |api: &mut ScriptApi| -> i64 {
+        api.0.borrow().position[1] as i64
+    });
+    engine.register_get("health", |api: &mut ScriptApi| -> i64 {
+        api.0.borrow().health as i64
+    });
+
+    // returns an array of object maps describing every other actor
+    engine.register_fn("actors", |api: &mut ScriptApi| -> Array {
+        let frame = api.0.borrow();
+        let actors: Array = frame
+            .actors
+            .iter()
+            .map(|a| {
+                let mut info = ::rhai::Map::new();
+                info.insert("id".into(), Dynamic::from(a.id.to_string()));
+                info.insert("kind".into(), Dynamic::from(a.actor_type.name()));
+                info.insert("faction".into(), Dynamic::from(a.faction.clone()));
+                info.insert(
+                    "relationship".into(),
+                    Dynamic::from(format!("{:?}", a.relationship).to_lowercase()),
+                );
+                info.insert("x".into(), Dynamic::from(a.position[0] as i64));
+                info.insert("y".into(), Dynamic::from(a.position[1] as i64));
+                Dynamic::from(info)
+            })
+            .collect();
+        actors
+    });
+
+    engine.register_fn("tile_at", |api: &mut ScriptApi, x: i64, y: i64| -> String {
+        String::from(api.0.borrow().tile_at([x as i32, y as i32]))
+    });
+
+    engine.register_fn("try_move", |api: &mut ScriptApi, dir: ImmutableString| -> String {
+        match parse_direction(dir.as_str()) {
+            Some(dir) => String::from(api.0.borrow_mut().try_step(dir)),
+            None => String::from("invalid"),
+        }
+    });
+
+    engine.register_fn("move_toward", |api: &mut ScriptApi, x: i64, y: i64| -> String {
+        let mut frame = api.0.borrow_mut();
+        match super::direction_toward(frame.position, [x as i32, y as i32]) {
+            Some(dir) => String::from(frame.try_step(dir)),
+            None => String::from("clear"),
+        }
+    });
+
+    engine.register_fn("message", |api: &mut ScriptApi, text: ImmutableString, kind: ImmutableString| {
+        api.0.borrow_mut().messages.push_back(Message {
+            contents: String::from(text.as_str()),
+            message_type: parse_message_type(kind.as_str()),
+        });
+    });
+
+    engine.register_fn("make_noise", |api: &mut ScriptApi, loudness: i64, description: ImmutableString| {
+        let mut frame = api.0.borrow_mut();
+        let noise = Noise::new(
+            frame.position,
+            loudness.max(0) as u32,
+            frame.id,
+            description.as_str(),
+        );
+        frame.noises.push_back(noise);
+    });
+
+    engine.register_fn("attack", |api: &mut ScriptApi, id: ImmutableString| {
+        match Uuid::parse_str(id.as_str()) {
+            Ok(id) => api.0.borrow_mut().attack_target = Some(id),
+            Err(_) => warn!("Script tried to attack an invalid ID {:?}", id.as_str()),
+        }
+    });
+
+    engine.register_fn("get", |api: &mut ScriptApi, key: ImmutableString| -> Dynamic {
+        api.0
+            .borrow()
+            .memory
+            .get(key.as_str())
+            .cloned()
+            .unwrap_or(Dynamic::UNIT)
+    });
+
+    engine.register_fn("set", |api: &mut ScriptApi, key: ImmutableString, value: Dynamic| {
+        api.0.borrow_mut().memory.insert(
+            String::from(key.as_str()),
+            value,
+        );
+    });
+
+    engine
+}
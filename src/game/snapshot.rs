@@ -0,0 +1,306 @@
+//! Golden snapshots of generated maps and of how the game is drawn, used to
+//! notice when either changes.  Maps are generated from fixed seeds and
+//! offsets and written out as text; views are drawn by the software renderer
+//! and saved as images.  Both are compared with copies checked in beside the
+//! assets folder.
+
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use find_folder::Search;
+use image;
+use render::{Renderer, SoftwareRenderer};
+use super::animation::AnimationClock;
+use super::{GameController, GameView, MapBuilder};
+
+const ASSETS_DIR: &'static str = "assets/";
+
+/// The folder, beside the assets folder, holding the golden maps.
+const SNAPSHOT_DIR: &'static str = "snapshots/maps";
+
+/// The folder, beside the assets folder, holding the golden views.
+const VIEW_SNAPSHOT_DIR: &'static str = "snapshots/views";
+
+/// The size, in pixels, that views are drawn at for the snapshots.
+const VIEW_SIZE: [u32; 2] = [640, 480];
+
+/// The time that the animation clock is stopped at while drawing views.
+const VIEW_MILLIS: u64 = 0;
+
+/// The seeds that maps are generated from for the snapshots.
+const SEEDS: [usize; 3] = [1, 42, 31337];
+
+/// The offsets from the starting map that maps are generated at for the
+/// snapshots.  Offsets away from the start can be flooded, and are sized
+/// differently from the starting map.
+const OFFSETS: [[i32; 2]; 5] = [[0, 0], [1, 0], [0, -1], [-2, 3], [7, 7]];
+
+/// The steps of a walk from the starting map across the edges of its
+/// neighbors and back, each generating a map from the previous one the way
+/// the player crossing an edge does.
+const WALK: [[i32; 2]; 4] = [[1, 0], [0, 1], [-1, 0], [0, -1]];
+
+/// A map generated from a fixed seed and offset, written out as text.
+struct Snapshot {
+    name: String,
+    contents: String,
+}
+
+/// Generates a map for every combination of seed and offset.
+fn generate() -> Vec<Snapshot> {
+    let mut snapshots = Vec::<Snapshot>::new();
+    for seed in &SEEDS {
+        for offset in &OFFSETS {
+            let mut builder = MapBuilder::with_seed(*seed);
+            let map = builder.create_offset(*offset);
+            snapshots.push(Snapshot {
+                name: format!("seed-{}_offset-{}_{}.txt", seed, offset[0], offset[1]),
+                contents: format!(
+                    "seed {} offset {:?} biome {:?} size {}x{}\n{}",
+                    seed,
+                    offset,
+                    builder.biome(),
+                    map.width(),
+                    map.height(),
+                    map.to_ascii()
+                ),
+            });
+        }
+
+        let mut builder = MapBuilder::with_seed(*seed);
+        let mut contents = String::new();
+        for (index, step) in WALK.iter().enumerate() {
+            let map = builder.create_offset(*step);
+            contents.push_str(&format!(
+                "step {} by {:?} biome {:?} size {}x{}\n{}",
+                index,
+                step,
+                builder.biome(),
+                map.width(),
+                map.height(),
+                map.to_ascii()
+            ));
+        }
+        snapshots.push(Snapshot {
+            name: format!("seed-{}_walk.txt", seed),
+            contents: contents,
+        });
+    }
+    snapshots
+}
+
+/// Starts a game from each seed and draws it without any animation.
+fn draw_views() -> Result<Vec<(String, image::RgbaImage)>, String> {
+    let mut views = Vec::new();
+    for seed in &SEEDS {
+        let mut controller = GameController::with_seed(*seed);
+        controller.set_window_size(VIEW_SIZE[0], VIEW_SIZE[1]);
+        let mut renderer = SoftwareRenderer::new(VIEW_SIZE[0], VIEW_SIZE[1]);
+        renderer.clear([0.0, 0.0, 0.0, 1.0]);
+        GameView::with_clock(AnimationClock::stopped(VIEW_MILLIS)).draw(&controller, &mut renderer);
+        views.push((format!("seed-{}.png", seed), renderer.image()?));
+    }
+    Ok(views)
+}
+
+fn snapshot_dir(folder: &str) -> Result<PathBuf, String> {
+    let assets = Search::ParentsThenKids(3, 3)
+        .for_folder(ASSETS_DIR)
+        .map_err(|why| format!("Unable to find assets folder: {:?}", why))?;
+    let root = assets.parent().ok_or_else(|| {
+        String::from("The assets folder has no parent folder.")
+    })?;
+    Ok(root.join(folder))
+}
+
+fn create_dir(dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|why| {
+        format!("Unable to create snapshot folder: {:?}", why)
+    })
+}
+
+/// Compares the snapshot maps and views with the golden copies, returning a
+/// description of every one that differs or is missing.
+pub fn check() -> Result<(), String> {
+    let failures: Vec<String> = vec![check_maps(), check_views()]
+        .into_iter()
+        .filter_map(Result::err)
+        .collect();
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures.join("\n"))
+    }
+}
+
+/// Generates the snapshot maps and compares them with the golden copies.
+fn check_maps() -> Result<(), String> {
+    let dir = snapshot_dir(SNAPSHOT_DIR)?;
+    let mut failures = Vec::<String>::new();
+    let snapshots = generate();
+    for snapshot in &snapshots {
+        let path = dir.join(&snapshot.name);
+        let mut golden = String::new();
+        let read = File::open(&path).and_then(|mut f| f.read_to_string(&mut golden));
+        if read.is_err() {
+            failures.push(format!("{}: no golden copy at {:?}", snapshot.name, path));
+            continue;
+        }
+        if golden == snapshot.contents {
+            continue;
+        }
+
+        // point out the first line that differs
+        let difference = golden
+            .lines()
+            .zip(snapshot.contents.lines())
+            .enumerate()
+            .find(|&(_, (expected, actual))| expected != actual);
+        failures.push(match difference {
+            Some((line, (expected, actual))) => {
+                format!(
+                    "{}: line {} differs\n  expected: {}\n  actual:   {}",
+                    snapshot.name,
+                    line + 1,
+                    expected,
+                    actual
+                )
+            }
+            None => format!("{}: the number of lines differs", snapshot.name),
+        });
+    }
+
+    if failures.is_empty() {
+        info!("All {} map snapshots match.", snapshots.len());
+        Ok(())
+    } else {
+        Err(format!(
+            "{} of {} map snapshots failed (bless them with --bless-snapshots if \
+             generation was meant to change):\n{}",
+            failures.len(),
+            snapshots.len(),
+            failures.join("\n")
+        ))
+    }
+}
+
+/// Draws the snapshot views and compares them with the golden copies,
+/// pixel by pixel.
+fn check_views() -> Result<(), String> {
+    let dir = snapshot_dir(VIEW_SNAPSHOT_DIR)?;
+    let mut failures = Vec::<String>::new();
+    let views = draw_views()?;
+    for &(ref name, ref view) in &views {
+        let path = dir.join(name);
+        let golden = match image::open(&path) {
+            Ok(golden) => golden.to_rgba(),
+            Err(_) => {
+                failures.push(format!("{}: no golden copy at {:?}", name, path));
+                continue;
+            }
+        };
+        if golden.dimensions() != view.dimensions() {
+            failures.push(format!(
+                "{}: expected a {:?} image but drew {:?}",
+                name,
+                golden.dimensions(),
+                view.dimensions()
+            ));
+            continue;
+        }
+
+        // point out the first pixel that differs, and how many do
+        let mut differing = golden
+            .enumerate_pixels()
+            .zip(view.pixels())
+            .filter(|&((_, _, expected), actual)| expected != actual);
+        if let Some(((x, y, expected), actual)) = differing.next() {
+            failures.push(format!(
+                "{}: {} pixels differ, starting at ({}, {})\n  expected: {:?}\n  actual:   {:?}",
+                name,
+                differing.count() + 1,
+                x,
+                y,
+                expected,
+                actual
+            ));
+        }
+    }
+
+    if failures.is_empty() {
+        info!("All {} view snapshots match.", views.len());
+        Ok(())
+    } else {
+        Err(format!(
+            "{} of {} view snapshots failed (bless them with --bless-snapshots if \
+             drawing was meant to change):\n{}",
+            failures.len(),
+            views.len(),
+            failures.join("\n")
+        ))
+    }
+}
+
+/// Generates the snapshot maps and views and saves them as the new golden
+/// copies.
+pub fn bless() -> Result<(), String> {
+    bless_maps()?;
+    bless_views()
+}
+
+fn bless_maps() -> Result<(), String> {
+    let dir = snapshot_dir(SNAPSHOT_DIR)?;
+    create_dir(&dir)?;
+    let snapshots = generate();
+    for snapshot in &snapshots {
+        let path = dir.join(&snapshot.name);
+        File::create(&path)
+            .and_then(|mut f| f.write_all(snapshot.contents.as_bytes()))
+            .map_err(|why| format!("Unable to write snapshot {:?}: {:?}", path, why))?;
+    }
+    info!("Blessed {} map snapshots in {:?}", snapshots.len(), dir);
+    Ok(())
+}
+
+fn bless_views() -> Result<(), String> {
+    let dir = snapshot_dir(VIEW_SNAPSHOT_DIR)?;
+    create_dir(&dir)?;
+    let views = draw_views()?;
+    for &(ref name, ref view) in &views {
+        let path = dir.join(name);
+        view.save(&path).map_err(|why| {
+            format!("Unable to write snapshot {:?}: {:?}", path, why)
+        })?;
+    }
+    info!("Blessed {} view snapshots in {:?}", views.len(), dir);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_match_golden_copies() {
+        if let Err(why) = check_maps() {
+            panic!("{}", why);
+        }
+    }
+
+    #[test]
+    fn views_match_golden_copies() {
+        if let Err(why) = check_views() {
+            panic!("{}", why);
+        }
+    }
+
+    #[test]
+    fn generation_is_repeatable() {
+        let first = generate();
+        let second = generate();
+        assert_eq!(first.len(), second.len());
+        for (a, b) in first.iter().zip(second.iter()) {
+            assert_eq!(a.contents, b.contents, "{} changed between runs", a.name);
+        }
+    }
+}
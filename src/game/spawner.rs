@@ -0,0 +1,242 @@
+use std::fs::File;
+use std::io::BufReader;
+use rand::Rng;
+use find_folder::Search;
+use serde_json;
+use super::actor::{self, Actor, ActorType};
+use super::rng;
+use super::{Biome, Map, Movable, Positioned};
+
+const SPAWN_TABLES_DIR: &'static str = "assets/";
+const SPAWN_TABLES_FILE: &'static str = "spawn_tables.json";
+
+/// The contents of the spawn tables file.
+#[derive(Deserialize, Default)]
+struct SpawnSettings {
+    /// The largest number of actors, not counting the player, that may be
+    /// present in a map at once.
+    max_population: usize,
+
+    /// The number of ticks between attempts to spawn a new actor.
+    respawn_interval: u32,
+
+    /// The minimum number of tiles between the player and any spawned actor.
+    min_player_distance: i32,
+
+    tables: Vec<SpawnTable>,
+}
+
+/// A weighted list of actors that may appear in maps of certain biomes
+/// and depths.
+#[derive(Deserialize)]
+struct SpawnTable {
+    /// The biomes that this table applies to.  Applies to every biome if empty.
+    #[serde(default)]
+    biomes: Vec<Biome>,
+
+    /// The shallowest depth that this table applies to.
+    #[serde(default)]
+    min_depth: u32,
+
+    /// The deepest depth that this table applies to, if any.
+    #[serde(default)]
+    max_depth: Option<u32>,
+
+    /// The number of actors chosen from this table when a map is populated.
+    population: u32,
+
+    entries: Vec<SpawnEntry>,
+}
+
+#[derive(Deserialize)]
+struct SpawnEntry {
+    /// The name of the actor type, as understood by `ActorType::from_name`.
+    actor: String,
+    weight: u32,
+}
+
+/// Decides which actors populate each map, where they are placed, and when
+/// new ones appear.
+pub struct Spawner {
+    settings: SpawnSettings,
+    ticks_until_respawn: u32,
+}
+
+impl SpawnTable {
+    fn applies_to(&self, biome: Biome, depth: u32) -> bool {
+        (self.biomes.is_empty() || self.biomes.contains(&biome)) && depth >= self.min_depth &&
+            self.max_depth.map_or(true, |max| depth <= max)
+    }
+
+    /// Picks an actor type from the table's entries according to their weights.
+    fn choose<R: Rng>(&self, rng: &mut R) -> Option<ActorType> {
+        let total = self.entries.iter().map(|e| e.weight).sum::<u32>();
+        if total == 0 {
+            return None;
+        }
+
+        let mut roll = rng.gen_range(0, total);
+        for entry in &self.entries {
+            if roll < entry.weight {
+                return Some(ActorType::from_name(entry.actor.as_str()));
+            }
+            roll -= entry.weight;
+        }
+        None
+    }
+}
+
+impl Spawner {
+    /// Creates and returns a new instance of the Spawner struct, reading its
+    /// spawn tables from the assets folder.
+    pub fn new() -> Spawner {
+        let settings = Spawner::load_settings().unwrap_or_else(|why| {
+            error!("{}  No actors will be spawned.", why);
+            SpawnSettings::default()
+        });
+        Spawner {
+            ticks_until_respawn: settings.respawn_interval,
+            settings: settings,
+        }
+    }
+
+    /// Creates the initial set of actors for a newly-generated map.
+    ////
+    /// `occupied` should contain the positions of every actor already in the
+    /// map, including the player.
+    pub fn populate(
+        &mut self,
+        map: &Map,
+        biome: Biome,
+        depth: u32,
+        player_position: [i32; 2],
+        occupied: &[[i32; 2]],
+    ) -> Vec<Box<Actor>> {
+        let mut occupied = occupied.to_vec();
+        let mut spawned = Vec::<Box<Actor>>::new();
+
+        self.ticks_until_respawn = self.settings.respawn_interval;
+
+        for table in self.settings.tables.iter().filter(
+            |t| t.applies_to(biome, depth),
+        )
+        {
+            for _ in 0..table.population {
+                if occupied.len().saturating_sub(1) >= self.settings.max_population {
+                    return spawned;
+                }
+                if let Some(actor_type) = rng::with(|rng| table.choose(rng)) {
+                    if let Some(actor) = self.spawn(&actor_type, map, player_position, &occupied) {
+                        occupied.push(actor.current_position());
+                        spawned.push(actor);
+                    }
+                }
+            }
+        }
+
+        info!(
+            "Populated map (biome: {:?}, depth: {}) with {} actors",
+            biome,
+            depth,
+            spawned.len()
+        );
+        spawned
+    }
+
+    /// Advances the respawn timer by one tick, returning a new actor if one
+    /// should appear and there is room for it.
+    pub fn tick(
+        &mut self,
+        map: &Map,
+        biome: Biome,
+        depth: u32,
+        player_position: [i32; 2],
+        occupied: &[[i32; 2]],
+    ) -> Option<Box<Actor>> {
+        if self.settings.respawn_interval == 0 {
+            return None;
+        }
+        if self.ticks_until_respawn > 1 {
+            self.ticks_until_respawn -= 1;
+            return None;
+        }
+        self.ticks_until_respawn = self.settings.respawn_interval;
+
+        if occupied.len().saturating_sub(1) >= self.settings.max_population {
+            return None;
+        }
+
+        let tables: Vec<&SpawnTable> = self.settings
+            .tables
+            .iter()
+            .filter(|t| t.applies_to(biome, depth))
+            .collect();
+        let choice = rng::with(|rng| rng.choose(&tables).and_then(|t| t.choose(rng)));
+        match choice {
+            Some(actor_type) => self.spawn(&actor_type, map, player_position, occupied),
+            None => None,
+        }
+    }
+
+    /// Moves the provided actor to a random valid spawning position.  Returns
+    /// `false` if there was no such position available.
+    pub fn place(
+        &self,
+        actor: &mut Actor,
+        map: &Map,
+        player_position: [i32; 2],
+        occupied: &[[i32; 2]],
+    ) -> bool {
+        let candidates: Vec<[i32; 2]> = map.reachable_from(player_position)
+            .into_iter()
+            .filter(|p| {
+                super::chebyshev_distance(*p, player_position) >= self.settings.min_player_distance &&
+                    !occupied.contains(p)
+            })
+            .collect();
+
+        match rng::with(|rng| rng.choose(&candidates).cloned()) {
+            Some(position) => {
+                actor.set_x(position[0]);
+                actor.set_y(position[1]);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn spawn(
+        &self,
+        actor_type: &ActorType,
+        map: &Map,
+        player_position: [i32; 2],
+        occupied: &[[i32; 2]],
+    ) -> Option<Box<Actor>> {
+        match actor::create(actor_type) {
+            Ok(mut actor) => {
+                if self.place(actor.as_mut(), map, player_position, occupied) {
+                    Some(actor)
+                } else {
+                    warn!("No valid position found to spawn {:?}", actor_type);
+                    None
+                }
+            }
+            Err(why) => {
+                error!("{}", why);
+                None
+            }
+        }
+    }
+
+    fn load_settings() -> Result<SpawnSettings, String> {
+        let assets = Search::ParentsThenKids(3, 3)
+            .for_folder(SPAWN_TABLES_DIR)
+            .map_err(|why| format!("Unable to find assets folder: {:?}", why))?;
+        let file = File::open(assets.join(SPAWN_TABLES_FILE)).map_err(|why| {
+            format!("Unable to open spawn tables: {:?}", why)
+        })?;
+        serde_json::from_reader(BufReader::new(file)).map_err(|why| {
+            format!("Unable to parse spawn tables: {}", why)
+        })
+    }
+}
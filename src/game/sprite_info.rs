@@ -1,5 +1,27 @@
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+lazy_static! {
+    static ref INTERNED_KEYS: Mutex<HashSet<&'static str>> = Mutex::new(HashSet::new());
+}
+
 /// Contains information about a sprite and its color.
 pub struct SpriteInfo {
     pub key: &'static str,
     pub color: [f32; 4],
 }
+
+/// Returns a `'static` copy of the provided sprite key, allocating it only
+/// the first time that a particular key is seen.
+////
+/// This allows sprite keys loaded from data files to be used in `SpriteInfo`
+/// without leaking a new string every time the files are reloaded.
+pub fn intern_key(key: &str) -> &'static str {
+    let mut keys = INTERNED_KEYS.lock().unwrap();
+    if let Some(existing) = keys.get(key) {
+        return *existing;
+    }
+    let interned: &'static str = Box::leak(String::from(key).into_boxed_str());
+    keys.insert(interned);
+    interned
+}
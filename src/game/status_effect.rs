@@ -0,0 +1,354 @@
+use rand::Rng;
+use super::rng;
+use super::MovementDirection;
+
+/// The farthest that a blinded actor can see, in tiles.
+const BLINDED_SIGHT_RADIUS: u32 = 1;
+
+/// The chance, as one in this many, that a confused actor moves in a
+/// random direction instead of the one it intended.
+const CONFUSION_CHANCE: u32 = 2;
+
+/// The kinds of lingering conditions that can affect an actor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EffectKind {
+    /// Deals damage each tick.  Stacks in intensity.
+    Poison,
+
+    /// Deals damage each tick.  Stacks in duration.
+    Bleeding,
+
+    /// The actor only acts every other tick.  Cancels out haste.
+    Slowed,
+
+    /// The actor acts twice each tick.  Cancels out slowness.
+    Hasted,
+
+    /// The actor can barely see.
+    Blinded,
+
+    /// The actor sometimes stumbles in a random direction.
+    Confused,
+}
+
+/// Describes what happens when an effect is applied to an actor that is
+/// already suffering from it.
+enum Stacking {
+    /// The potencies are added together, and the longer duration is kept.
+    Intensity,
+
+    /// The durations are added together, and the greater potency is kept.
+    Duration,
+
+    /// The longer duration and greater potency are kept.
+    Refresh,
+}
+
+impl EffectKind {
+    /// Returns the word used to describe an actor suffering from the effect.
+    pub fn adjective(&self) -> &'static str {
+        match *self {
+            EffectKind::Poison => "poisoned",
+            EffectKind::Bleeding => "bleeding",
+            EffectKind::Slowed => "slowed",
+            EffectKind::Hasted => "hasted",
+            EffectKind::Blinded => "blinded",
+            EffectKind::Confused => "confused",
+        }
+    }
+
+    /// Returns the word used to describe the effect itself.
+    pub fn noun(&self) -> &'static str {
+        match *self {
+            EffectKind::Poison => "poison",
+            EffectKind::Bleeding => "bleeding",
+            EffectKind::Slowed => "slowness",
+            EffectKind::Hasted => "haste",
+            EffectKind::Blinded => "blindness",
+            EffectKind::Confused => "confusion",
+        }
+    }
+
+    fn stacking(&self) -> Stacking {
+        match *self {
+            EffectKind::Poison => Stacking::Intensity,
+            EffectKind::Bleeding => Stacking::Duration,
+            _ => Stacking::Refresh,
+        }
+    }
+
+    /// Returns the effect that this one cancels out, if any.
+    fn opposite(&self) -> Option<EffectKind> {
+        match *self {
+            EffectKind::Slowed => Some(EffectKind::Hasted),
+            EffectKind::Hasted => Some(EffectKind::Slowed),
+            _ => None,
+        }
+    }
+
+    /// Called once per tick for each active effect of this kind.
+    fn on_tick(&self, effect: &StatusEffect) -> Option<EffectEvent> {
+        match *self {
+            EffectKind::Poison |
+            EffectKind::Bleeding => Some(EffectEvent::Damaged(*self, effect.potency.max(1))),
+            _ => None,
+        }
+    }
+}
+
+/// A single condition affecting an actor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusEffect {
+    /// What kind of effect this is.
+    pub kind: EffectKind,
+
+    /// The number of ticks remaining until the effect wears off.
+    pub duration: u32,
+
+    /// How strong the effect is, such as the damage dealt each tick by poison.
+    #[serde(default = "default_potency")]
+    pub potency: i32,
+}
+
+fn default_potency() -> i32 {
+    1
+}
+
+impl StatusEffect {
+    /// Creates and returns a new instance of the StatusEffect struct.
+    pub fn new(kind: EffectKind, duration: u32, potency: i32) -> StatusEffect {
+        StatusEffect {
+            kind: kind,
+            duration: duration,
+            potency: potency,
+        }
+    }
+}
+
+/// Something that happened to an actor because of its status effects,
+/// which its controller should respond to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EffectEvent {
+    /// The actor started suffering from the effect.
+    Began(EffectKind),
+
+    /// The effect wore off or was cancelled out.
+    Ended(EffectKind),
+
+    /// The effect dealt the contained amount of damage to the actor.
+    Damaged(EffectKind, i32),
+}
+
+/// The set of status effects currently affecting an actor.
+#[derive(Debug, Clone, Default)]
+pub struct StatusEffects {
+    effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    /// Creates and returns a new, empty instance of the StatusEffects struct.
+    pub fn new() -> StatusEffects {
+        StatusEffects { effects: Vec::new() }
+    }
+
+    /// Returns a value indicating whether the actor is suffering from the
+    /// provided kind of effect.
+    pub fn has(&self, kind: EffectKind) -> bool {
+        self.effects.iter().any(|e| e.kind == kind)
+    }
+
+    /// Returns the effects currently active.
+    pub fn active(&self) -> &[StatusEffect] {
+        &self.effects
+    }
+
+    /// Adds an effect, stacking it with any existing effect of the same kind
+    /// and cancelling out any opposing effect.
+    pub fn apply(&mut self, effect: StatusEffect) -> Vec<EffectEvent> {
+        if effect.duration == 0 {
+            return Vec::new();
+        }
+
+        if let Some(opposite) = effect.kind.opposite() {
+            if self.has(opposite) {
+                self.effects.retain(|e| e.kind != opposite);
+                return vec![EffectEvent::Ended(opposite)];
+            }
+        }
+
+        match self.effects.iter_mut().find(|e| e.kind == effect.kind) {
+            Some(existing) => {
+                match effect.kind.stacking() {
+                    Stacking::Intensity => {
+                        existing.potency += effect.potency;
+                        existing.duration = existing.duration.max(effect.duration);
+                    }
+                    Stacking::Duration => {
+                        existing.duration += effect.duration;
+                        existing.potency = existing.potency.max(effect.potency);
+                    }
+                    Stacking::Refresh => {
+                        existing.duration = existing.duration.max(effect.duration);
+                        existing.potency = existing.potency.max(effect.potency);
+                    }
+                }
+                return Vec::new();
+            }
+            None => {}
+        }
+
+        let kind = effect.kind;
+        self.effects.push(effect);
+        vec![EffectEvent::Began(kind)]
+    }
+
+    /// Advances every effect by one tick, running their per-tick behavior
+    /// and removing any that have worn off.
+    pub fn tick(&mut self) -> Vec<EffectEvent> {
+        let mut events = Vec::<EffectEvent>::new();
+        for effect in self.effects.iter_mut() {
+            if let Some(event) = effect.kind.on_tick(effect) {
+                events.push(event);
+            }
+            effect.duration = effect.duration.saturating_sub(1);
+            if effect.duration == 0 {
+                events.push(EffectEvent::Ended(effect.kind));
+            }
+        }
+        self.effects.retain(|e| e.duration > 0);
+        events
+    }
+
+    /// Returns the number of times the actor gets to act during the
+    /// provided tick.
+    pub fn actions_during(&self, tick: u64) -> u32 {
+        if self.has(EffectKind::Hasted) {
+            2
+        } else if self.has(EffectKind::Slowed) {
+            (tick % 2) as u32
+        } else {
+            1
+        }
+    }
+
+    /// Returns how far the actor can see, given how far it could see if
+    /// it were unaffected.
+    pub fn sight_radius(&self, radius: u32) -> u32 {
+        if self.has(EffectKind::Blinded) {
+            radius.min(BLINDED_SIGHT_RADIUS)
+        } else {
+            radius
+        }
+    }
+
+    /// Returns the direction that the actor actually moves in when it tries
+    /// to move in the provided direction.
+    pub fn redirect(&self, dir: MovementDirection) -> MovementDirection {
+        if !self.has(EffectKind::Confused) {
+            return dir;
+        }
+        rng::with(|rng| if rng.gen_weighted_bool(CONFUSION_CHANCE) {
+            use super::MovementDirection::*;
+            let directions = [Up, Down, Left, Right, UpLeft, UpRight, DownLeft, DownRight];
+            rng.choose(&directions).cloned().unwrap_or(dir)
+        } else {
+            dir
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(effects: &StatusEffects, kind: EffectKind) -> StatusEffect {
+        effects.active().iter().find(|e| e.kind == kind).cloned().unwrap()
+    }
+
+    #[test]
+    fn poison_stacks_in_intensity() {
+        let mut effects = StatusEffects::new();
+        assert_eq!(
+            effects.apply(StatusEffect::new(EffectKind::Poison, 3, 1)),
+            vec![EffectEvent::Began(EffectKind::Poison)]
+        );
+        assert!(effects.apply(StatusEffect::new(EffectKind::Poison, 5, 2)).is_empty());
+        let poison = find(&effects, EffectKind::Poison);
+        assert_eq!((poison.duration, poison.potency), (5, 3));
+    }
+
+    #[test]
+    fn bleeding_stacks_in_duration() {
+        let mut effects = StatusEffects::new();
+        effects.apply(StatusEffect::new(EffectKind::Bleeding, 3, 2));
+        effects.apply(StatusEffect::new(EffectKind::Bleeding, 4, 1));
+        let bleeding = find(&effects, EffectKind::Bleeding);
+        assert_eq!((bleeding.duration, bleeding.potency), (7, 2));
+    }
+
+    #[test]
+    fn other_effects_refresh() {
+        let mut effects = StatusEffects::new();
+        effects.apply(StatusEffect::new(EffectKind::Blinded, 5, 1));
+        effects.apply(StatusEffect::new(EffectKind::Blinded, 2, 3));
+        let blinded = find(&effects, EffectKind::Blinded);
+        assert_eq!((blinded.duration, blinded.potency), (5, 3));
+        assert_eq!(effects.active().len(), 1);
+    }
+
+    #[test]
+    fn opposites_cancel_out() {
+        let mut effects = StatusEffects::new();
+        effects.apply(StatusEffect::new(EffectKind::Hasted, 5, 1));
+        assert_eq!(
+            effects.apply(StatusEffect::new(EffectKind::Slowed, 5, 1)),
+            vec![EffectEvent::Ended(EffectKind::Hasted)]
+        );
+        assert!(!effects.has(EffectKind::Hasted));
+        assert!(!effects.has(EffectKind::Slowed));
+    }
+
+    #[test]
+    fn effects_without_duration_are_ignored() {
+        let mut effects = StatusEffects::new();
+        assert!(effects.apply(StatusEffect::new(EffectKind::Poison, 0, 5)).is_empty());
+        assert!(effects.active().is_empty());
+    }
+
+    #[test]
+    fn ticking_deals_damage_and_wears_off() {
+        let mut effects = StatusEffects::new();
+        effects.apply(StatusEffect::new(EffectKind::Poison, 2, 3));
+        assert_eq!(effects.tick(), vec![EffectEvent::Damaged(EffectKind::Poison, 3)]);
+        assert_eq!(
+            effects.tick(),
+            vec![
+                EffectEvent::Damaged(EffectKind::Poison, 3),
+                EffectEvent::Ended(EffectKind::Poison),
+            ]
+        );
+        assert!(effects.active().is_empty());
+        assert!(effects.tick().is_empty());
+    }
+
+    #[test]
+    fn speed_changes_actions_per_tick() {
+        let mut effects = StatusEffects::new();
+        assert_eq!(effects.actions_during(1), 1);
+        effects.apply(StatusEffect::new(EffectKind::Hasted, 5, 1));
+        assert_eq!(effects.actions_during(1), 2);
+
+        let mut effects = StatusEffects::new();
+        effects.apply(StatusEffect::new(EffectKind::Slowed, 5, 1));
+        assert_eq!(effects.actions_during(0) + effects.actions_during(1), 1);
+    }
+
+    #[test]
+    fn blindness_limits_sight() {
+        let mut effects = StatusEffects::new();
+        assert_eq!(effects.sight_radius(8), 8);
+        effects.apply(StatusEffect::new(EffectKind::Blinded, 5, 1));
+        assert_eq!(effects.sight_radius(8), BLINDED_SIGHT_RADIUS);
+        assert_eq!(effects.sight_radius(0), 0);
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fs::File;
 use std::path::PathBuf;
 use std::io::{BufWriter, BufReader};
@@ -8,7 +9,7 @@ use graphics::math::Matrix2d;
 use graphics::DrawState;
 use find_folder::Search;
 use serde_json;
-use image::{self, DynamicImage};
+use image::{self, DynamicImage, RgbaImage};
 use texture_coords::CoordinateMap;
 use texture::{Filter, TextureSettings};
 
@@ -28,8 +29,9 @@ pub struct TextureMapper {
 impl TextureMapper {
     /// Creates and returns a new instance of the TextureMapper struct.
     pub fn new() -> TextureMapper {
+        let atlas = TextureMapper::load_atlas();
         TextureMapper {
-            texture: TextureMapper::load_texture(),
+            texture: Texture::from_image(&atlas, &TextureSettings::new().filter(Filter::Nearest)),
             coords: TextureMapper::load_coords(),
             draw_state: DrawState::default(),
         }
@@ -55,7 +57,9 @@ impl TextureMapper {
         }
     }
 
-    fn load_texture() -> Texture {
+    /// Loads the image holding every sprite, for renderers that draw
+    /// sprites themselves.
+    pub fn load_atlas() -> RgbaImage {
         let assets = Search::ParentsThenKids(3, 3)
             .for_folder(TEXTURE_DIR)
             .unwrap();
@@ -66,15 +70,14 @@ impl TextureMapper {
             Err(e) => panic!("Could not load texture: {:?}", e),
         };
 
-        let img = match img {
+        match img {
             DynamicImage::ImageRgba8(img) => img,
             x => x.to_rgba(),
-        };
-
-        Texture::from_image(&img, &TextureSettings::new().filter(Filter::Nearest))
+        }
     }
 
-    fn load_coords() -> CoordinateMap {
+    /// Loads the location of each sprite within the atlas.
+    pub fn load_coords() -> CoordinateMap {
         let assets = Search::ParentsThenKids(3, 3)
             .for_folder(TEXTURE_DIR)
             .unwrap();
@@ -101,6 +104,7 @@ impl TextureMapper {
                     .iter()
                     .cloned()
                     .collect(),
+                animations: HashMap::new(),
             },
         ).unwrap_or_else(|_| {
             panic!("Unable to write template texture coordinate file.")
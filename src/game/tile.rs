@@ -52,6 +52,64 @@ impl Tile {
             position: [0, 0],
         }
     }
+
+    /// Returns a value indicating whether an actor is able to walk onto this tile.
+    pub fn passable(&self) -> bool {
+        match self.tile_type {
+            TileType::Floor(FloorType::Water) |
+            TileType::Floor(FloorType::Mud) => false,
+            TileType::Floor(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the character that stands for this tile when a map is written
+    /// out as text.  Every kind of tile has its own character.
+    pub fn symbol(&self) -> char {
+        match self.tile_type {
+            TileType::Empty => 'X',
+            TileType::Wall(WallOrientation::Top, WallType::Stone) => '#',
+            TileType::Wall(WallOrientation::Top, WallType::Brick) => 'B',
+            TileType::Wall(WallOrientation::Top, WallType::Adobe) => 'A',
+            TileType::Wall(WallOrientation::Face, WallType::Stone) => '=',
+            TileType::Wall(WallOrientation::Face, WallType::Brick) => 'b',
+            TileType::Wall(WallOrientation::Face, WallType::Adobe) => 'a',
+            TileType::Floor(ref style) => {
+                match *style {
+                    FloorType::Dirt => '.',
+                    FloorType::Stone => ',',
+                    FloorType::Grass => '"',
+                    FloorType::Gravel => ':',
+                    FloorType::Wood => '_',
+                    FloorType::Overgrown => '&',
+                    FloorType::Water => '~',
+                    FloorType::Mud => '%',
+                }
+            }
+        }
+    }
+
+    /// Returns the key used to look up this tile's description.
+    pub fn description_key(&self) -> &'static str {
+        match self.tile_type {
+            TileType::Empty => "empty",
+            TileType::Wall(_, WallType::Brick) => "wall_brick",
+            TileType::Wall(_, WallType::Stone) => "wall_stone",
+            TileType::Wall(_, WallType::Adobe) => "wall_adobe",
+            TileType::Floor(ref style) => {
+                match *style {
+                    FloorType::Dirt => "floor_dirt",
+                    FloorType::Stone => "floor_stone",
+                    FloorType::Grass => "floor_grass",
+                    FloorType::Gravel => "floor_gravel",
+                    FloorType::Wood => "floor_wood",
+                    FloorType::Overgrown => "floor_overgrown",
+                    FloorType::Water => "floor_water",
+                    FloorType::Mud => "floor_mud",
+                }
+            }
+        }
+    }
 }
 
 impl Positioned for Tile {
@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// How long, in milliseconds, an actor takes to slide from one tile to the
+/// next.  Input given while actors are sliding waits until they arrive.
+pub const TWEEN_MILLIS: u64 = 120;
+
+/// How long, in milliseconds, an actor flashes after being hurt.
+const FLASH_MILLIS: u64 = 200;
+
+/// How long, in milliseconds, a damage number floats above where it was dealt.
+const NUMBER_MILLIS: u64 = 800;
+
+/// How far, in tiles, a damage number rises before it fades away.
+const NUMBER_RISE: f64 = 0.75;
+
+/// Something that happened during a turn that is worth animating.
+#[derive(Debug, Clone)]
+pub enum TurnEvent {
+    /// An actor stepped from one tile to a neighboring one.
+    Moved {
+        /// The ID of the actor that moved.
+        actor: Uuid,
+
+        /// The tile the actor left.
+        from: [i32; 2],
+
+        /// The tile the actor arrived at.
+        to: [i32; 2],
+    },
+
+    /// An actor was hurt.
+    Hit {
+        /// The ID of the actor that was hurt.
+        actor: Uuid,
+
+        /// Where the actor was when it was hurt.
+        position: [i32; 2],
+
+        /// How much health the actor lost.
+        damage: i32,
+    },
+}
+
+/// An actor sliding between tiles.
+struct Slide {
+    from: [f64; 2],
+    to: [i32; 2],
+    started: u64,
+}
+
+/// A damage number floating above the tile where the damage was dealt.
+struct FloatingNumber {
+    position: [i32; 2],
+    text: String,
+    started: u64,
+}
+
+/// Animates the events of each turn between one turn and the next: actors
+/// slide from tile to tile instead of jumping, flash when they are hurt,
+/// and the damage they take floats up from where they stood.  Times are in
+/// milliseconds, as given by an `AnimationClock`.
+pub struct Tweens {
+    seen: u64,
+    slides: HashMap<Uuid, Slide>,
+    flashes: HashMap<Uuid, u64>,
+    numbers: Vec<FloatingNumber>,
+}
+
+impl Tweens {
+    /// Creates and returns a new instance of the Tweens struct, with nothing
+    /// animating.
+    pub fn new() -> Tweens {
+        Tweens {
+            seen: 0,
+            slides: HashMap::new(),
+            flashes: HashMap::new(),
+            numbers: Vec::new(),
+        }
+    }
+
+    /// Returns the number of the last event that has been animated.
+    pub fn seen(&self) -> u64 {
+        self.seen
+    }
+
+    /// Starts animating the provided events, each given along with its
+    /// number, and forgets the animations that have finished.
+    pub fn update(&mut self, events: Vec<(u64, TurnEvent)>, now: u64) {
+        for (number, event) in events {
+            self.seen = self.seen.max(number);
+            match event {
+                TurnEvent::Moved { actor, from, to } => {
+                    // an actor that moves again before arriving carries on from where it is
+                    let from = match self.slides.get(&actor) {
+                        Some(slide) => slide.position(now).unwrap_or_else(|| to_f64(from)),
+                        None => to_f64(from),
+                    };
+                    self.slides.insert(
+                        actor,
+                        Slide {
+                            from: from,
+                            to: to,
+                            started: now,
+                        },
+                    );
+                }
+                TurnEvent::Hit {
+                    actor,
+                    position,
+                    damage,
+                } => {
+                    self.flashes.insert(actor, now);
+                    self.numbers.push(FloatingNumber {
+                        position: position,
+                        text: format!("{}", damage),
+                        started: now,
+                    });
+                }
+            }
+        }
+
+        self.slides.retain(|_, slide| slide.position(now).is_some());
+        self.flashes.retain(|_, started| now - *started < FLASH_MILLIS);
+        self.numbers.retain(|number| now - number.started < NUMBER_MILLIS);
+    }
+
+    /// Returns where the actor with the provided ID should be drawn, in
+    /// fractional tiles, given the tile it is actually on.
+    pub fn position(&self, actor: &Uuid, position: [i32; 2], now: u64) -> [f64; 2] {
+        match self.slides.get(actor) {
+            Some(slide) if slide.to == position => {
+                slide.position(now).unwrap_or_else(|| to_f64(position))
+            }
+            _ => to_f64(position),
+        }
+    }
+
+    /// Returns how strongly the actor with the provided ID is flashing, from
+    /// zero when it isn't to one when it has just been hurt.
+    pub fn flash(&self, actor: &Uuid, now: u64) -> f32 {
+        match self.flashes.get(actor) {
+            Some(started) if now - started < FLASH_MILLIS => {
+                1.0 - (now - started) as f32 / FLASH_MILLIS as f32
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Returns the text of each damage number, where it should be drawn in
+    /// fractional tiles, and how opaque it should be.
+    pub fn numbers(&self, now: u64) -> Vec<(&str, [f64; 2], f32)> {
+        self.numbers
+            .iter()
+            .filter(|number| now - number.started < NUMBER_MILLIS)
+            .map(|number| {
+                let progress = (now - number.started) as f64 / NUMBER_MILLIS as f64;
+                let position = [
+                    number.position[0] as f64,
+                    number.position[1] as f64 - progress * NUMBER_RISE,
+                ];
+                (number.text.as_str(), position, 1.0 - progress as f32)
+            })
+            .collect()
+    }
+}
+
+impl Slide {
+    /// Returns where the actor is along the way, or `None` once it has
+    /// arrived.
+    fn position(&self, now: u64) -> Option<[f64; 2]> {
+        let elapsed = now.saturating_sub(self.started);
+        if elapsed >= TWEEN_MILLIS {
+            return None;
+        }
+
+        // ease out, so that actors slow down as they arrive
+        let t = elapsed as f64 / TWEEN_MILLIS as f64;
+        let eased = 1.0 - (1.0 - t) * (1.0 - t);
+        Some([
+            self.from[0] + (self.to[0] as f64 - self.from[0]) * eased,
+            self.from[1] + (self.to[1] as f64 - self.from[1]) * eased,
+        ])
+    }
+}
+
+fn to_f64(position: [i32; 2]) -> [f64; 2] {
+    [position[0] as f64, position[1] as f64]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(n: u8) -> Uuid {
+        Uuid::from_bytes(&[n; 16]).unwrap()
+    }
+
+    fn moved(actor: Uuid, from: [i32; 2], to: [i32; 2]) -> TurnEvent {
+        TurnEvent::Moved {
+            actor: actor,
+            from: from,
+            to: to,
+        }
+    }
+
+    #[test]
+    fn actors_slide_and_ease_out() {
+        let mut tweens = Tweens::new();
+        tweens.update(vec![(1, moved(id(1), [0, 0], [1, 0]))], 0);
+        assert_eq!(tweens.position(&id(1), [1, 0], 0), [0.0, 0.0]);
+
+        let halfway = tweens.position(&id(1), [1, 0], TWEEN_MILLIS / 2);
+        assert!(halfway[0] > 0.5 && halfway[0] < 1.0, "{:?}", halfway);
+        assert_eq!(halfway[1], 0.0);
+
+        assert_eq!(tweens.position(&id(1), [1, 0], TWEEN_MILLIS), [1.0, 0.0]);
+        tweens.update(Vec::new(), TWEEN_MILLIS);
+        assert!(tweens.slides.is_empty());
+    }
+
+    #[test]
+    fn actors_that_moved_again_are_drawn_where_they_are() {
+        let mut tweens = Tweens::new();
+        tweens.update(vec![(1, moved(id(1), [0, 0], [1, 0]))], 0);
+        assert_eq!(tweens.position(&id(1), [5, 5], 10), [5.0, 5.0]);
+        assert_eq!(tweens.position(&id(2), [3, 4], 10), [3.0, 4.0]);
+    }
+
+    #[test]
+    fn moving_again_carries_on_from_partway() {
+        let mut tweens = Tweens::new();
+        tweens.update(vec![(1, moved(id(1), [0, 0], [1, 0]))], 0);
+        let partway = tweens.position(&id(1), [1, 0], TWEEN_MILLIS / 2);
+        tweens.update(vec![(2, moved(id(1), [1, 0], [2, 0]))], TWEEN_MILLIS / 2);
+        assert_eq!(tweens.position(&id(1), [2, 0], TWEEN_MILLIS / 2), partway);
+        assert_eq!(tweens.seen(), 2);
+    }
+
+    #[test]
+    fn hits_flash_and_show_damage() {
+        let mut tweens = Tweens::new();
+        let hit = TurnEvent::Hit {
+            actor: id(1),
+            position: [2, 3],
+            damage: 4,
+        };
+        tweens.update(vec![(7, hit)], 0);
+        assert_eq!(tweens.seen(), 7);
+
+        assert_eq!(tweens.flash(&id(1), 0), 1.0);
+        assert_eq!(tweens.flash(&id(1), FLASH_MILLIS / 2), 0.5);
+        assert_eq!(tweens.flash(&id(1), FLASH_MILLIS), 0.0);
+        assert_eq!(tweens.flash(&id(2), 0), 0.0);
+
+        let numbers = tweens.numbers(0);
+        assert_eq!(numbers.len(), 1);
+        assert_eq!(numbers[0].0, "4");
+        assert_eq!(numbers[0].1, [2.0, 3.0]);
+        assert_eq!(numbers[0].2, 1.0);
+
+        let risen = tweens.numbers(NUMBER_MILLIS / 2);
+        assert!(risen[0].1[1] < 3.0 && risen[0].2 < 1.0);
+        assert!(tweens.numbers(NUMBER_MILLIS).is_empty());
+
+        tweens.update(Vec::new(), NUMBER_MILLIS);
+        assert!(tweens.flashes.is_empty() && tweens.numbers.is_empty());
+    }
+
+    #[test]
+    fn seen_never_goes_back() {
+        let mut tweens = Tweens::new();
+        tweens.update(vec![(5, moved(id(1), [0, 0], [0, 1]))], 0);
+        tweens.update(vec![(3, moved(id(2), [0, 0], [1, 1]))], 0);
+        assert_eq!(tweens.seen(), 5);
+    }
+}
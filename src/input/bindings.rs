@@ -0,0 +1,403 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+use piston::input::Key;
+use find_folder::Search;
+use serde_json;
+use super::keys;
+use super::Control;
+
+const BINDINGS_DIR: &'static str = "assets/";
+const BINDINGS_FILE: &'static str = "key_bindings.json";
+
+/// The modifier keys held down while another key is pressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Modifiers {
+    /// Whether either shift key is held.
+    pub shift: bool,
+
+    /// Whether either control key is held.
+    pub ctrl: bool,
+
+    /// Whether either alt key is held.
+    pub alt: bool,
+}
+
+/// A key, along with the modifiers that must be held when it is pressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Chord {
+    key: Key,
+    modifiers: Modifiers,
+}
+
+impl Chord {
+    /// Creates and returns a new instance of the Chord struct.
+    pub fn new(key: Key, modifiers: Modifiers) -> Chord {
+        Chord {
+            key: key,
+            modifiers: modifiers,
+        }
+    }
+
+    /// Parses a chord written like "K", "Shift+Up", or "Ctrl+Alt+F1".
+    fn parse(text: &str) -> Result<Chord, String> {
+        let mut modifiers = Modifiers::default();
+        let mut parts: Vec<&str> = text.split('+').map(|p| p.trim()).collect();
+        let key_name = parts.pop().unwrap_or("");
+        for part in parts {
+            match part.to_lowercase().as_str() {
+                "shift" => modifiers.shift = true,
+                "ctrl" | "control" => modifiers.ctrl = true,
+                "alt" => modifiers.alt = true,
+                other => return Err(format!("Unknown modifier {:?} in {:?}", other, text)),
+            }
+        }
+        match keys::key_from_name(key_name) {
+            Some(key) => Ok(Chord::new(key, modifiers)),
+            None => Err(format!("Unknown key {:?} in {:?}", key_name, text)),
+        }
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.modifiers.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.alt {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.shift {
+            write!(f, "Shift+")?;
+        }
+        write!(f, "{}", keys::key_name(self.key).unwrap_or("?"))
+    }
+}
+
+/// Built-in sets of movement keys that can be enabled in the bindings file.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    /// The numeric keypad, with 5 and 0 to wait.
+    Numpad,
+
+    /// The vi-keys: hjkl for orthogonal moves and yubn for diagonal ones,
+    /// with period to wait.  Holding shift runs or waits for a while.
+    Vi,
+
+    /// The arrow keys, with home, page up, end and page down for diagonal
+    /// moves and space to wait.  Holding shift runs or waits for a while.
+    Arrows,
+}
+
+impl Preset {
+    fn bindings(&self) -> Vec<(Control, &'static str)> {
+        use super::Control::*;
+        match *self {
+            Preset::Numpad => vec![
+                (MoveUp, "NumPad8"),
+                (MoveDown, "NumPad2"),
+                (MoveLeft, "NumPad4"),
+                (MoveRight, "NumPad6"),
+                (MoveUpLeft, "NumPad7"),
+                (MoveUpRight, "NumPad9"),
+                (MoveDownLeft, "NumPad1"),
+                (MoveDownRight, "NumPad3"),
+                (Wait, "NumPad5"),
+                (Wait, "NumPad0"),
+                (RunUp, "Shift+NumPad8"),
+                (RunDown, "Shift+NumPad2"),
+                (RunLeft, "Shift+NumPad4"),
+                (RunRight, "Shift+NumPad6"),
+                (RunUpLeft, "Shift+NumPad7"),
+                (RunUpRight, "Shift+NumPad9"),
+                (RunDownLeft, "Shift+NumPad1"),
+                (RunDownRight, "Shift+NumPad3"),
+                (WaitTurns, "Shift+NumPad5"),
+                (WaitTurns, "Shift+NumPad0"),
+            ],
+            Preset::Vi => vec![
+                (MoveUp, "K"),
+                (MoveDown, "J"),
+                (MoveLeft, "H"),
+                (MoveRight, "L"),
+                (MoveUpLeft, "Y"),
+                (MoveUpRight, "U"),
+                (MoveDownLeft, "B"),
+                (MoveDownRight, "N"),
+                (Wait, "Period"),
+                (RunUp, "Shift+K"),
+                (RunDown, "Shift+J"),
+                (RunLeft, "Shift+H"),
+                (RunRight, "Shift+L"),
+                (RunUpLeft, "Shift+Y"),
+                (RunUpRight, "Shift+U"),
+                (RunDownLeft, "Shift+B"),
+                (RunDownRight, "Shift+N"),
+                (WaitTurns, "Shift+Period"),
+            ],
+            Preset::Arrows => vec![
+                (MoveUp, "Up"),
+                (MoveDown, "Down"),
+                (MoveLeft, "Left"),
+                (MoveRight, "Right"),
+                (MoveUpLeft, "Home"),
+                (MoveUpRight, "PageUp"),
+                (MoveDownLeft, "End"),
+                (MoveDownRight, "PageDown"),
+                (Wait, "Space"),
+                (RunUp, "Shift+Up"),
+                (RunDown, "Shift+Down"),
+                (RunLeft, "Shift+Left"),
+                (RunRight, "Shift+Right"),
+                (RunUpLeft, "Shift+Home"),
+                (RunUpRight, "Shift+PageUp"),
+                (RunDownLeft, "Shift+End"),
+                (RunDownRight, "Shift+PageDown"),
+                (WaitTurns, "Shift+Space"),
+            ],
+        }
+    }
+}
+
+/// The contents of the key bindings file.
+#[derive(Serialize, Deserialize)]
+struct BindingsFile {
+    /// The sets of movement keys to enable.
+    #[serde(default = "default_presets")]
+    presets: Vec<Preset>,
+
+    /// Keys for each control, written like "K", "Shift+Up", or "Ctrl+F1".
+    /// Replaces any keys given to the same control by a preset.
+    #[serde(default)]
+    bindings: BTreeMap<Control, Vec<String>>,
+}
+
+fn default_presets() -> Vec<Preset> {
+    vec![Preset::Numpad]
+}
+
+impl Default for BindingsFile {
+    fn default() -> BindingsFile {
+        use super::Control::*;
+        let defaults = [
+            (Fire, "F"),
+            (Throw, "T"),
+            (Pickup, "G"),
+            (Rest, "R"),
+            (Look, "Semicolon"),
+            (Explore, "X"),
+            (ToggleMessages, "Tab"),
+            (SpawnSoldier, "F1"),
+            (SpawnRandom, "F2"),
+            (ToggleDebugInfo, "F3"),
+            (Screenshot, "F12"),
+            (Options, "O"),
+            (ToggleFullscreen, "F11"),
+            (ZoomIn, "Ctrl+Equals"),
+            (ZoomOut, "Ctrl+Minus"),
+            (CycleTarget, "Tab"),
+            (Confirm, "Return"),
+            (Cancel, "Backspace"),
+            (PauseReplay, "Space"),
+            (StepReplay, "Period"),
+            (FastForwardReplay, "Equals"),
+        ];
+        BindingsFile {
+            presets: default_presets(),
+            bindings: defaults
+                .iter()
+                .map(|&(control, key)| (control, vec![String::from(key)]))
+                .collect(),
+        }
+    }
+}
+
+/// Maps chords to the controls that they are bound to.
+pub struct KeyBindings {
+    controls: HashMap<Chord, Vec<Control>>,
+}
+
+impl KeyBindings {
+    /// Creates and returns a new instance of the KeyBindings struct, reading
+    /// the bindings file from the assets folder.  A default bindings file is
+    /// written if there is none.
+    pub fn new() -> KeyBindings {
+        let file = KeyBindings::load().unwrap_or_else(|why| {
+            error!("{}  Using the default key bindings.", why);
+            BindingsFile::default()
+        });
+        KeyBindings::from_file(file)
+    }
+
+    /// Returns the controls bound to the provided chord.
+    pub fn controls(&self, chord: &Chord) -> &[Control] {
+        self.controls.get(chord).map_or(&[][..], |c| c.as_slice())
+    }
+
+    fn from_file(file: BindingsFile) -> KeyBindings {
+        // start with the presets, then let the file replace whatever it mentions
+        let mut chords = BTreeMap::<Control, Vec<Chord>>::new();
+        for preset in &file.presets {
+            for (control, key) in preset.bindings() {
+                if let Ok(chord) = Chord::parse(key) {
+                    chords.entry(control).or_insert_with(Vec::new).push(chord);
+                }
+            }
+        }
+        let defaults = BindingsFile::default().bindings;
+        for (control, names) in defaults.iter().chain(file.bindings.iter()) {
+            let parsed = names.iter()
+                .filter_map(|key| {
+                    Chord::parse(key.as_str())
+                        .map_err(|why| error!("Key bindings: {}", why))
+                        .ok()
+                })
+                .collect();
+            chords.insert(*control, parsed);
+        }
+
+        // a chord may only do one thing at a time; the first control bound to
+        // it keeps it
+        let mut controls = HashMap::<Chord, Vec<Control>>::new();
+        for (control, control_chords) in chords {
+            for chord in control_chords {
+                let bound = controls.entry(chord).or_insert_with(Vec::new);
+                match bound.iter().find(|c| c.conflicts_with(&control)) {
+                    Some(existing) => {
+                        error!(
+                            "Key bindings: {} is bound to both {:?} and {:?}; keeping {:?}",
+                            chord,
+                            existing,
+                            control,
+                            existing
+                        );
+                    }
+                    None => bound.push(control),
+                }
+            }
+        }
+        KeyBindings { controls: controls }
+    }
+
+    fn load() -> Result<BindingsFile, String> {
+        let assets = Search::ParentsThenKids(3, 3)
+            .for_folder(BINDINGS_DIR)
+            .map_err(|why| format!("Unable to find assets folder: {:?}", why))?;
+        let path = assets.join(BINDINGS_FILE);
+        if !path.exists() {
+            KeyBindings::save_defaults(path.clone());
+        }
+        let file = File::open(path).map_err(|why| {
+            format!("Unable to open key bindings file: {:?}", why)
+        })?;
+        serde_json::from_reader(BufReader::new(file)).map_err(|why| {
+            format!("Unable to parse key bindings file: {}", why)
+        })
+    }
+
+    fn save_defaults(path: PathBuf) {
+        match File::create(path) {
+            Ok(file) => {
+                if let Err(why) = serde_json::to_writer_pretty(
+                    BufWriter::new(file),
+                    &BindingsFile::default(),
+                )
+                {
+                    error!("Unable to write default key bindings file: {}", why);
+                }
+            }
+            Err(why) => error!("Unable to create default key bindings file: {:?}", why),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Control::*;
+
+    fn chord(key: Key, shift: bool, ctrl: bool, alt: bool) -> Chord {
+        Chord::new(
+            key,
+            Modifiers {
+                shift: shift,
+                ctrl: ctrl,
+                alt: alt,
+            },
+        )
+    }
+
+    fn bindings(presets: Vec<Preset>, bound: Vec<(Control, &str)>) -> KeyBindings {
+        KeyBindings::from_file(BindingsFile {
+            presets: presets,
+            bindings: bound
+                .into_iter()
+                .map(|(control, key)| (control, vec![String::from(key)]))
+                .collect(),
+        })
+    }
+
+    #[test]
+    fn chords_parse_with_modifiers() {
+        assert_eq!(Chord::parse("K"), Ok(chord(Key::K, false, false, false)));
+        assert_eq!(Chord::parse("Shift+Up"), Ok(chord(Key::Up, true, false, false)));
+        assert_eq!(Chord::parse("ctrl + alt + F1"), Ok(chord(Key::F1, false, true, true)));
+        assert_eq!(Chord::parse("Control+Equals"), Ok(chord(Key::Equals, false, true, false)));
+    }
+
+    #[test]
+    fn chords_reject_unknown_names() {
+        assert!(Chord::parse("Hyper+K").is_err());
+        assert!(Chord::parse("Shift+Nothing").is_err());
+        assert!(Chord::parse("").is_err());
+    }
+
+    #[test]
+    fn chords_display_as_they_parse() {
+        for text in &["K", "Shift+NumPad8", "Ctrl+Alt+F1", "Ctrl+Shift+Period"] {
+            let parsed = Chord::parse(text).unwrap();
+            assert_eq!(Chord::parse(parsed.to_string().as_str()), Ok(parsed));
+        }
+    }
+
+    #[test]
+    fn presets_bind_movement() {
+        let keys = bindings(vec![Preset::Numpad, Preset::Vi], Vec::new());
+        assert_eq!(keys.controls(&chord(Key::NumPad8, false, false, false)), &[MoveUp]);
+        assert_eq!(keys.controls(&chord(Key::K, false, false, false)), &[MoveUp]);
+        assert_eq!(keys.controls(&chord(Key::K, true, false, false)), &[RunUp]);
+        assert!(keys.controls(&chord(Key::Up, false, false, false)).is_empty());
+    }
+
+    #[test]
+    fn file_bindings_replace_presets() {
+        let keys = bindings(vec![Preset::Numpad], vec![(MoveUp, "W")]);
+        assert_eq!(keys.controls(&chord(Key::W, false, false, false)), &[MoveUp]);
+        assert!(keys.controls(&chord(Key::NumPad8, false, false, false)).is_empty());
+    }
+
+    #[test]
+    fn controls_in_different_situations_share_keys() {
+        let keys = bindings(vec![Preset::Numpad], Vec::new());
+        let tab = keys.controls(&chord(Key::Tab, false, false, false));
+        assert!(tab.contains(&ToggleMessages));
+        assert!(tab.contains(&CycleTarget));
+    }
+
+    #[test]
+    fn conflicting_bindings_keep_the_first() {
+        // controls are bound in the order they are declared, so rest comes
+        // before picking up, which is left without a key
+        let keys = bindings(vec![Preset::Numpad], vec![(Rest, "G")]);
+        assert_eq!(keys.controls(&chord(Key::G, false, false, false)), &[Rest]);
+        assert!(keys.controls(&chord(Key::R, false, false, false)).is_empty());
+
+        // a control used everywhere can't share a key with anything
+        let keys = bindings(vec![Preset::Vi], vec![(Options, "K")]);
+        assert_eq!(keys.controls(&chord(Key::K, false, false, false)), &[MoveUp]);
+        assert!(keys.controls(&chord(Key::O, false, false, false)).is_empty());
+    }
+}
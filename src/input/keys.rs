@@ -0,0 +1,44 @@
+use piston::input::Key;
+
+/// Defines the functions that convert between keys and the names used for
+/// them in the key bindings file.  The name of each key is the name of its
+/// variant in piston's `Key` enum.
+macro_rules! named_keys {
+    ($($name:ident),*,) => {
+        /// Returns the key with the provided name, if there is one.
+        pub fn key_from_name(name: &str) -> Option<Key> {
+            match name {
+                $(stringify!($name) => Some(Key::$name),)*
+                _ => None,
+            }
+        }
+
+        /// Returns the name of the provided key, if it can be bound.
+        pub fn key_name(key: Key) -> Option<&'static str> {
+            match key {
+                $(Key::$name => Some(stringify!($name)),)*
+                _ => None,
+            }
+        }
+    }
+}
+
+named_keys! {
+    A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+    D0, D1, D2, D3, D4, D5, D6, D7, D8, D9,
+    NumPad0, NumPad1, NumPad2, NumPad3, NumPad4, NumPad5, NumPad6, NumPad7, NumPad8, NumPad9,
+    NumPadPeriod, NumPadEnter, NumPadPlus, NumPadMinus, NumPadMultiply, NumPadDivide,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+    Up, Down, Left, Right, Home, End, PageUp, PageDown, Insert, Delete,
+    Backspace, Tab, Return, Space, Escape,
+    Comma, Period, Slash, Semicolon, Quote, Minus, Equals, Backquote,
+    LeftBracket, RightBracket, Backslash,
+}
+
+/// Returns a value indicating whether the key is a modifier, such as shift.
+pub fn is_modifier(key: Key) -> bool {
+    match key {
+        Key::LShift | Key::RShift | Key::LCtrl | Key::RCtrl | Key::LAlt | Key::RAlt => true,
+        _ => false,
+    }
+}
@@ -0,0 +1,295 @@
+#![deny(missing_docs)]
+
+//! Translates raw keyboard input into the controls that the game responds to,
+//! according to the bindings in the user-editable key bindings file.
+
+mod bindings;
+mod keys;
+
+use piston::input::{Button, GenericEvent, Key};
+use game::{ActorType, Command, MovementDirection, Weapon, LONG_WAIT_TURNS};
+use self::bindings::{Chord, KeyBindings};
+
+pub use self::bindings::Modifiers;
+pub use self::keys::key_from_name;
+
+/// The things that the player can ask the game to do by pressing a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Control {
+    /// Moves (or attacks, or moves a cursor) to the north.
+    MoveUp,
+
+    /// Moves (or attacks, or moves a cursor) to the south.
+    MoveDown,
+
+    /// Moves (or attacks, or moves a cursor) to the west.
+    MoveLeft,
+
+    /// Moves (or attacks, or moves a cursor) to the east.
+    MoveRight,
+
+    /// Moves (or attacks, or moves a cursor) to the northwest.
+    MoveUpLeft,
+
+    /// Moves (or attacks, or moves a cursor) to the northeast.
+    MoveUpRight,
+
+    /// Moves (or attacks, or moves a cursor) to the southwest.
+    MoveDownLeft,
+
+    /// Moves (or attacks, or moves a cursor) to the southeast.
+    MoveDownRight,
+
+    /// Runs to the north.
+    RunUp,
+
+    /// Runs to the south.
+    RunDown,
+
+    /// Runs to the west.
+    RunLeft,
+
+    /// Runs to the east.
+    RunRight,
+
+    /// Runs to the northwest.
+    RunUpLeft,
+
+    /// Runs to the northeast.
+    RunUpRight,
+
+    /// Runs to the southwest.
+    RunDownLeft,
+
+    /// Runs to the southeast.
+    RunDownRight,
+
+    /// Lets a tick pass without doing anything.
+    Wait,
+
+    /// Waits for several ticks.
+    WaitTurns,
+
+    /// Rests until healed.
+    Rest,
+
+    /// Picks up whatever is lying at the player's feet.
+    Pickup,
+
+    /// Aims the sling, or fires it while aiming.
+    Fire,
+
+    /// Aims a throwing knife, or throws it while aiming.
+    Throw,
+
+    /// Starts or stops looking around.
+    Look,
+
+    /// Starts exploring automatically.
+    Explore,
+
+    /// Shows or hides the message log.
+    ToggleMessages,
+
+    /// Spawns a dummy enemy.
+    SpawnSoldier,
+
+    /// Spawns a random actor from the assets folder.
+    SpawnRandom,
+
+    /// Moves the aiming cursor to the next visible enemy.
+    CycleTarget,
+
+    /// Fires at whatever the aiming cursor is on.
+    Confirm,
+
+    /// Stops aiming or looking around.
+    Cancel,
+
+    /// Shows or hides the debug information.
+    ToggleDebugInfo,
+
+    /// Saves a picture of the game to the screenshots folder.
+    Screenshot,
+
+    /// Opens or closes the options menu.
+    Options,
+
+    /// Switches between fullscreen and windowed.
+    ToggleFullscreen,
+
+    /// Zooms the camera in.
+    ZoomIn,
+
+    /// Zooms the camera out.
+    ZoomOut,
+
+    /// Pauses or resumes the replay being played.
+    PauseReplay,
+
+    /// Pauses the replay being played, and plays its next command.
+    StepReplay,
+
+    /// Starts or stops fast-forwarding through the replay being played.
+    FastForwardReplay,
+}
+
+/// The situations in which a control can be used.  Two controls may share
+/// a key as long as they are never used in the same situation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Context {
+    /// The control can be used at any time.
+    Everywhere,
+
+    /// The control is only used while the player is moving around freely.
+    Normal,
+
+    /// The control is only used while the player is moving a cursor around.
+    Cursor,
+
+    /// The control is only used while a replay is being played.
+    Replay,
+}
+
+impl Control {
+    /// Returns the direction that the control moves in, if it is a movement
+    /// control.
+    pub fn direction(&self) -> Option<MovementDirection> {
+        match *self {
+            Control::MoveUp => Some(MovementDirection::Up),
+            Control::MoveDown => Some(MovementDirection::Down),
+            Control::MoveLeft => Some(MovementDirection::Left),
+            Control::MoveRight => Some(MovementDirection::Right),
+            Control::MoveUpLeft => Some(MovementDirection::UpLeft),
+            Control::MoveUpRight => Some(MovementDirection::UpRight),
+            Control::MoveDownLeft => Some(MovementDirection::DownLeft),
+            Control::MoveDownRight => Some(MovementDirection::DownRight),
+            _ => None,
+        }
+    }
+
+    /// Returns the direction that the control runs in, if it is a running
+    /// control.
+    pub fn run_direction(&self) -> Option<MovementDirection> {
+        match *self {
+            Control::RunUp => Some(MovementDirection::Up),
+            Control::RunDown => Some(MovementDirection::Down),
+            Control::RunLeft => Some(MovementDirection::Left),
+            Control::RunRight => Some(MovementDirection::Right),
+            Control::RunUpLeft => Some(MovementDirection::UpLeft),
+            Control::RunUpRight => Some(MovementDirection::UpRight),
+            Control::RunDownLeft => Some(MovementDirection::DownLeft),
+            Control::RunDownRight => Some(MovementDirection::DownRight),
+            _ => None,
+        }
+    }
+
+    /// Returns the command that the control stands for, given whether the
+    /// player is choosing a target.  Controls that only affect the interface,
+    /// such as looking around, have no command.
+    pub fn command(&self, aiming: bool) -> Option<Command> {
+        if let Some(dir) = self.direction() {
+            return Some(if aiming {
+                Command::MoveCursor(dir)
+            } else {
+                Command::Move(dir)
+            });
+        }
+        if aiming {
+            return match *self {
+                Control::CycleTarget => Some(Command::CycleTarget),
+                Control::Confirm | Control::Fire | Control::Throw => Some(Command::Release),
+                Control::Cancel => Some(Command::CancelAim),
+                _ => None,
+            };
+        }
+        if let Some(dir) = self.run_direction() {
+            return Some(Command::Run(dir));
+        }
+        match *self {
+            Control::Wait => Some(Command::Wait),
+            Control::WaitTurns => Some(Command::WaitTurns(LONG_WAIT_TURNS)),
+            Control::Rest => Some(Command::Rest),
+            Control::Pickup => Some(Command::Pickup),
+            Control::Fire => Some(Command::Aim(Weapon::Sling)),
+            Control::Throw => Some(Command::Aim(Weapon::Knife)),
+            Control::Explore => Some(Command::Explore),
+            Control::ToggleMessages => Some(Command::ToggleMessages),
+            Control::SpawnSoldier => Some(Command::Spawn(ActorType::Soldier)),
+            Control::SpawnRandom => Some(Command::SpawnRandom),
+            _ => None,
+        }
+    }
+
+    fn context(&self) -> Context {
+        if self.run_direction().is_some() {
+            return Context::Normal;
+        }
+        match *self {
+            Control::Wait | Control::WaitTurns | Control::Rest | Control::Pickup |
+            Control::Explore | Control::ToggleMessages |
+            Control::SpawnSoldier | Control::SpawnRandom => Context::Normal,
+            Control::CycleTarget | Control::Confirm | Control::Cancel => Context::Cursor,
+            Control::PauseReplay | Control::StepReplay | Control::FastForwardReplay => {
+                Context::Replay
+            }
+            _ => Context::Everywhere,
+        }
+    }
+
+    /// Returns a value indicating whether the two controls can be used in
+    /// the same situation, and therefore may not share a key.
+    fn conflicts_with(&self, other: &Control) -> bool {
+        let (a, b) = (self.context(), other.context());
+        a == Context::Everywhere || b == Context::Everywhere || a == b
+    }
+}
+
+/// Tracks which modifier keys are held down, and turns key presses into
+/// controls using the loaded key bindings.
+pub struct InputMapper {
+    bindings: KeyBindings,
+    modifiers: Modifiers,
+}
+
+impl InputMapper {
+    /// Creates and returns a new instance of the InputMapper struct, loading
+    /// the key bindings file from the assets folder.
+    pub fn new() -> InputMapper {
+        InputMapper {
+            bindings: KeyBindings::new(),
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    /// Returns the controls triggered by the provided event, if any.
+    pub fn update<E: GenericEvent>(&mut self, event: &E) -> Vec<Control> {
+        if let Some(Button::Keyboard(key)) = event.press_args() {
+            if keys::is_modifier(key) {
+                self.set_modifier(key, true);
+            } else {
+                return self.controls_for(key, self.modifiers);
+            }
+        } else if let Some(Button::Keyboard(key)) = event.release_args() {
+            self.set_modifier(key, false);
+        }
+        Vec::new()
+    }
+
+    /// Returns the controls bound to the provided key when pressed with the
+    /// provided modifiers, for frontends that report modifiers themselves.
+    pub fn controls_for(&self, key: Key, modifiers: Modifiers) -> Vec<Control> {
+        self.bindings
+            .controls(&Chord::new(key, modifiers))
+            .to_vec()
+    }
+
+    fn set_modifier(&mut self, key: Key, held: bool) {
+        match key {
+            Key::LShift | Key::RShift => self.modifiers.shift = held,
+            Key::LCtrl | Key::RCtrl => self.modifiers.ctrl = held,
+            Key::LAlt | Key::RAlt => self.modifiers.alt = held,
+            _ => {}
+        }
+    }
+}
@@ -13,11 +13,15 @@ extern crate bresenham;
 extern crate log;
 extern crate simple_logger;
 extern crate find_folder;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
 extern crate serde_json;
 extern crate texture_coords;
 extern crate image;
 extern crate texture;
 extern crate rand;
+extern crate rhai;
 extern crate ndarray_parallel;
 extern crate noise;
 extern crate bmfont;
@@ -30,23 +34,35 @@ extern crate uuid;
 
 mod debug_info;
 mod game;
+mod input;
+mod render;
+mod settings;
+mod terminal;
 mod text_renderer;
 
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
 use piston::window::{Window, WindowSettings};
 use piston::event_loop::{Events, EventSettings, EventLoop};
-use piston::input::RenderEvent;
+use piston::input::{RenderEvent, ResizeEvent};
 use glutin_window::GlutinWindow;
 use opengl_graphics::{GlGraphics, OpenGL};
 use debug_info::{DebugInfoController, DebugInfoView};
-use game::{GameController, GameView};
+use game::{GameController, GameView, Replay, ReplayPlayer, TextureMapper};
+use game::snapshot;
+use input::{Control, InputMapper};
+use render::{GlRenderer, Renderer, SoftwareRenderer, Viewport};
+use settings::{OptionsController, OptionsView, Scaling, Settings};
+use status::ControllerStatus;
 use text_renderer::TextRenderer;
 
-const WINDOW_WIDTH: u32 = 1386;
-const WINDOW_HEIGHT: u32 = 792;
-const MAX_FPS: u64 = 65;
-const VSYNC: bool = true;
-const LAZY: bool = false;
-const EXIT_ON_ESC: bool = true;
+/// Where each session is recorded, unless another path is given.
+const DEFAULT_RECORDING: &'static str = "replays/latest.replay";
+
+/// Where screenshots taken while playing are saved.
+const SCREENSHOT_DIR: &'static str = "screenshots/";
 
 lazy_static! {
     static ref TEXT_RENDERER: TextRenderer = TextRenderer::new();
@@ -61,30 +77,336 @@ mod status {
         /// Indicates that the returning controller wishes to close
         /// the game window.
         Quit,
+
+        /// Indicates that the returning controller wishes to change the
+        /// size of the game window.
         Resize(u32, u32),
+
+        /// Indicates that the returning controller wishes to make the game
+        /// window cover the whole screen, or stop covering it.
+        SetFullscreen(bool),
+
+        /// Indicates that the returning controller wishes to hide or show
+        /// the game window's title bar and border.
+        SetBorderless(bool),
+    }
+}
+
+/// Options given on the command line.
+struct Options {
+    /// The replay file to play back, if any.
+    replay: Option<PathBuf>,
+
+    /// Where to record the session, when not playing back a replay.
+    record: PathBuf,
+
+    /// Whether to play back the replay without opening a window.
+    headless: bool,
+
+    /// Whether to play in the terminal instead of a window.
+    terminal: bool,
+
+    /// Where to save a screenshot when playing without a window.
+    screenshot: Option<PathBuf>,
+
+    /// What to do with the map and view snapshots instead of playing, if
+    /// anything.
+    snapshots: Option<SnapshotMode>,
+
+    /// Settings to use instead of those in the settings file, by name and
+    /// value.  These are not saved.
+    overrides: Vec<(String, String)>,
+
+    /// Problems with the arguments, to be logged once logging has been set
+    /// up the way the options say to.
+    problems: Vec<String>,
+}
+
+/// The ways in which the map and view snapshots can be used from the
+/// command line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SnapshotMode {
+    /// Compares newly generated maps and views with the golden copies.
+    Check,
+
+    /// Replaces the golden copies with newly generated maps and views.
+    Bless,
+}
+
+impl Options {
+    /// Reads the options from the command line:
+    ///
+    /// `--replay FILE` plays back a replay, `--headless` does so without a
+    /// window, `--record FILE` records the session somewhere other than the
+    /// default, and `--terminal` plays in the terminal.  `--screenshot FILE`
+    /// saves a picture of the game, once the replay has played if there is
+    /// one, and is meant to be used along with `--headless`.
+    /// `--check-snapshots` and `--bless-snapshots` check or replace the
+    /// golden map and view snapshots, then exit.  `--set NAME=VALUE`
+    /// overrides a setting from the settings file, such as
+    /// `--set window.width=1024`.
+    fn from_args() -> Options {
+        let mut options = Options {
+            replay: None,
+            record: PathBuf::from(DEFAULT_RECORDING),
+            headless: false,
+            terminal: false,
+            screenshot: None,
+            snapshots: None,
+            overrides: Vec::new(),
+            problems: Vec::new(),
+        };
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--replay" => options.replay = args.next().map(PathBuf::from),
+                "--record" => {
+                    if let Some(path) = args.next() {
+                        options.record = PathBuf::from(path);
+                    }
+                }
+                "--headless" => options.headless = true,
+                "--terminal" => options.terminal = true,
+                "--screenshot" => options.screenshot = args.next().map(PathBuf::from),
+                "--check-snapshots" => options.snapshots = Some(SnapshotMode::Check),
+                "--bless-snapshots" => options.snapshots = Some(SnapshotMode::Bless),
+                "--set" => {
+                    let setting = args.next().unwrap_or_default();
+                    match setting.find('=') {
+                        Some(index) => {
+                            let (name, value) = (&setting[..index], &setting[index + 1..]);
+                            options.overrides.push((name.to_owned(), value.to_owned()));
+                        }
+                        None => {
+                            options.problems.push(
+                                String::from("--set needs a setting written as NAME=VALUE."),
+                            )
+                        }
+                    }
+                }
+                other => {
+                    options.problems.push(format!("Ignoring unknown argument {:?}", other))
+                }
+            }
+        }
+        options
+    }
+}
+
+/// Plays back a replay as quickly as possible without opening a window,
+/// logging the messages that the player would have seen at the end.
+fn play_headless(path: &Path, settings: &Settings) -> Option<GameController> {
+    let replay = match Replay::load(path) {
+        Ok(replay) => replay,
+        Err(why) => {
+            error!("{}", why);
+            return None;
+        }
+    };
+    let mut game_controller = GameController::with_seed(replay.seed);
+    game_controller.set_input_enabled(false);
+    apply_settings(&mut game_controller, settings);
+    let mut replay_player = ReplayPlayer::new(replay);
+    while replay_player.play_next(&mut game_controller) {
+        if let Some(status::ControllerStatus::Quit) = game_controller.get_status() {
+            info!("The game ended before the replay did.");
+            break;
+        }
+    }
+
+    info!("Replay stopped on tick {}.", game_controller.tick());
+    for message in game_controller.get_messages(10).into_iter().rev() {
+        info!("{}", message.contents);
+    }
+    Some(game_controller)
+}
+
+/// Draws the game without the graphics card and saves it as a PNG file.
+fn take_screenshot(controller: &GameController, view: &mut GameView, size: [u32; 2], path: &Path) {
+    let mut renderer = SoftwareRenderer::new(size[0], size[1]);
+    renderer.clear([0.0, 0.0, 0.0, 1.0]);
+    view.draw(controller, &mut renderer);
+    match renderer.save(path) {
+        Ok(_) => info!("Saved a screenshot to {:?}", path),
+        Err(why) => error!("{}", why),
+    }
+}
+
+/// Returns a path in the screenshots folder named after the current time.
+fn screenshot_path() -> PathBuf {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    Path::new(SCREENSHOT_DIR).join(format!("{}.png", seconds))
+}
+
+/// Starts the game, either played by the user and recorded, or played back
+/// from a replay, in which case the player for the replay is returned too.
+fn start_game(options: &Options, settings: &Settings) -> (GameController, Option<ReplayPlayer>) {
+    let (mut controller, replay_player) = match options.replay.as_ref().map(|p| Replay::load(p)) {
+        Some(Ok(replay)) => {
+            let mut controller = GameController::with_seed(replay.seed);
+            controller.set_input_enabled(false);
+            (controller, Some(ReplayPlayer::new(replay)))
+        }
+        other => {
+            if let Some(Err(why)) = other {
+                error!("{}  Starting a new game instead.", why);
+            }
+            let mut controller = GameController::new();
+            if let Err(why) = controller.record_to(&options.record) {
+                error!("{}  This session will not be recorded.", why);
+            }
+            (controller, None)
+        }
+    };
+    apply_settings(&mut controller, settings);
+    (controller, replay_player)
+}
+
+/// Makes the game follow the provided settings.
+fn apply_settings(controller: &mut GameController, settings: &Settings) {
+    controller.set_gameplay_options(settings.gameplay.clone());
+    controller.set_camera_options(settings.camera.clone());
+    controller.set_pixel_perfect(settings.window.scaling == Scaling::Integer);
+}
+
+/// Returns where the game's screen sits within a window of the provided
+/// size, given the settings.
+fn viewport_for(settings: &Settings, window_size: [f64; 2]) -> Viewport {
+    Viewport::new(
+        settings.window.scaling,
+        [settings.window.width, settings.window.height],
+        window_size,
+    )
+}
+
+/// Returns the size of the window, in the units that the mouse and the
+/// game's views use.
+fn window_size(window: &GlutinWindow) -> [f64; 2] {
+    let size = window.size();
+    [size.width as f64, size.height as f64]
+}
+
+/// Carries out the request that a controller made through its status.
+fn handle_status(window: &mut GlutinWindow, status: ControllerStatus, source: &str) {
+    use status::ControllerStatus::*;
+
+    match status {
+        Quit => {
+            info!("Received Quit status from {}, quitting now...", source);
+            window.set_should_close(true);
+        }
+        Resize(width, height) => {
+            info!(
+                "Received Resize status from {}, resizing to {:?}x{:?}...",
+                source,
+                width,
+                height
+            );
+            window.window.set_inner_size(width, height);
+        }
+        SetFullscreen(fullscreen) => {
+            info!("Received SetFullscreen({}) status from {}...", fullscreen, source);
+            let monitor = if fullscreen {
+                Some(window.window.get_current_monitor())
+            } else {
+                None
+            };
+            window.window.set_fullscreen(monitor);
+        }
+        SetBorderless(borderless) => {
+            info!("Received SetBorderless({}) status from {}...", borderless, source);
+            window.window.set_decorations(!borderless);
+        }
     }
 }
 
 fn main() {
-    // initialize logging
-    simple_logger::init().expect("Unable to init logger.");
+    let options = Options::from_args();
+
+    // initialize logging, which the terminal frontend keeps off of its screen
+    if options.terminal {
+        terminal::init_logging().expect("Unable to init logger.");
+    } else {
+        simple_logger::init().expect("Unable to init logger.");
+    }
     info!("Logging initialized.");
+    for problem in &options.problems {
+        warn!("{}", problem);
+    }
+    if let Some(mode) = options.snapshots {
+        let result = match mode {
+            SnapshotMode::Check => snapshot::check(),
+            SnapshotMode::Bless => snapshot::bless(),
+        };
+        if let Err(why) = result {
+            error!("{}", why);
+            process::exit(1);
+        }
+        return;
+    }
+
+    // settings from the command line are used without being saved
+    let saved_settings = Settings::load();
+    let mut settings = saved_settings.clone();
+    for &(ref name, ref value) in &options.overrides {
+        if let Err(why) = settings.set(name, value) {
+            warn!("{}  Ignoring it.", why);
+        }
+    }
+    let screen_size = [settings.window.width, settings.window.height];
+
+    if options.headless {
+        let game_controller = match options.replay {
+            Some(ref path) => play_headless(path, &settings),
+            None if options.screenshot.is_some() => {
+                let mut controller = GameController::new();
+                apply_settings(&mut controller, &settings);
+                Some(controller)
+            }
+            None => {
+                error!("--headless can only be used along with --replay or --screenshot.");
+                None
+            }
+        };
+        if let (Some(mut controller), Some(path)) = (game_controller, options.screenshot.as_ref()) {
+            controller.set_window_size(screen_size[0], screen_size[1]);
+            take_screenshot(&controller, &mut GameView::new(), screen_size, path);
+        }
+        return;
+    }
+    if options.terminal {
+        let (game_controller, replay_player) = start_game(&options, &settings);
+        terminal::run(
+            game_controller,
+            replay_player,
+            OptionsController::new(saved_settings, settings),
+        );
+        return;
+    }
 
     // initialize window settings, events, and graphics
     let opengl = OpenGL::V3_2;
 
-    let settings = WindowSettings::new("finniko", [WINDOW_WIDTH, WINDOW_HEIGHT])
-        .vsync(VSYNC)
+    let window_settings = WindowSettings::new("finniko", screen_size)
+        .vsync(settings.window.vsync)
         .opengl(opengl)
-        .exit_on_esc(EXIT_ON_ESC);
+        .exit_on_esc(settings.window.exit_on_esc)
+        .fullscreen(settings.window.fullscreen)
+        .decorated(!settings.window.borderless);
 
-    let mut window: GlutinWindow = settings.build().expect("Could not create window");
+    let mut window: GlutinWindow = window_settings.build().expect("Could not create window");
 
-    let event_settings = EventSettings::new().max_fps(MAX_FPS).lazy(LAZY);
+    let event_settings = EventSettings::new()
+        .max_fps(settings.window.max_fps)
+        .lazy(settings.window.lazy);
 
     let mut events = Events::new(event_settings);
 
     let mut gl = GlGraphics::new(opengl);
+    let textures = TextureMapper::new();
 
     info!(
         "Graphical systems initialized...  Using Opengl version {:?}",
@@ -93,47 +415,77 @@ fn main() {
 
     // initialize state models
 
-    // Primary game state logic
-    let mut game_controller = GameController::new();
-    let mut game_view = GameView::new(&TEXT_RENDERER);
+    // Primary game state logic, either played by the user and recorded, or
+    // played back from a replay
+    let (mut game_controller, mut replay_player) = start_game(&options, &settings);
+    let mut viewport = viewport_for(&settings, window_size(&window));
+    game_controller.set_viewport(viewport);
+    let mut game_view = GameView::new();
 
     // Debug information meant to aid in troubleshooting and optimization
-    let mut debug_controller = DebugInfoController::new(WINDOW_HEIGHT, WINDOW_WIDTH);
-    let mut debug_view = DebugInfoView::new(&TEXT_RENDERER);
+    let size = window.size();
+    let mut debug_controller = DebugInfoController::new(size.width as u32, size.height as u32);
+    let mut debug_view = DebugInfoView::new();
 
-    while let Some(e) = events.next(&mut window) {
-        // pass event reference to controllers
-        game_controller.update(&e);
-        debug_controller.update(&e);
+    // The options menu, which saves any changes made in it to the settings file
+    let mut options_controller = OptionsController::new(saved_settings, settings);
+    let mut options_view = OptionsView::new();
 
-        // check game controller status
-        if let Some(status) = game_controller.get_status() {
-            use status::ControllerStatus::*;
+    // Translates key presses into controls using the key bindings file
+    let mut input = InputMapper::new();
 
-            // handle whatever status the controller returned
-            match status {
-                Quit => {
-                    info!("Received Quit status from game_controller, quitting now...");
-                    window.set_should_close(true);
-                }
-                Resize(width, height) => {
-                    info!(
-                        "Received Resize status from game_controller, resizing to {:?}x{:?}...",
-                        width,
-                        height
-                    );
-                    window.window.set_inner_size(width, height);
-                }
+    while let Some(e) = events.next(&mut window) {
+        // pass event reference, and whatever controls it triggered, to controllers
+        let controls = options_controller.update(&input.update(&e));
+        if let Some(changes) = options_controller.take_changes() {
+            apply_settings(&mut game_controller, changes);
+            events.set_max_fps(changes.window.max_fps);
+            viewport = viewport_for(changes, window_size(&window));
+            game_controller.set_viewport(viewport);
+        }
+        if let Some(size) = e.resize_args() {
+            let size = [size[0] as f64, size[1] as f64];
+            viewport = viewport_for(options_controller.settings(), size);
+            game_controller.set_viewport(viewport);
+        }
+
+        // the game is paused while the options menu is open
+        if !options_controller.is_open() {
+            if let Some(ref mut replay_player) = replay_player {
+                replay_player.control(&controls);
+                replay_player.update(&e, &mut game_controller);
             }
+            game_controller.update(&e, &controls);
+        }
+        debug_controller.update(&e, &controls);
+        if controls.contains(&Control::Screenshot) {
+            let size = viewport.screen_size();
+            take_screenshot(
+                &game_controller,
+                &mut game_view,
+                [size[0] as u32, size[1] as u32],
+                &screenshot_path(),
+            );
+        }
+
+        // handle whatever statuses the controllers returned
+        if let Some(status) = game_controller.get_status() {
+            handle_status(&mut window, status, "game_controller");
+        }
+        while let Some(status) = options_controller.get_status() {
+            handle_status(&mut window, status, "options_controller");
         }
 
         if let Some(args) = e.render_args() {
             gl.draw(args.viewport(), |c, g| {
-                graphics::clear([0.0; 4], g);
+                let mut renderer = GlRenderer::new(c, g, &textures, &TEXT_RENDERER, viewport);
+                renderer.clear([0.0; 4]);
 
-                //pass controller reference, context reference, and graphics instance to views
-                game_view.draw(&game_controller, &c, g);
-                debug_view.draw(&debug_controller, &c, g);
+                //pass controller reference and renderer to views
+                game_view.draw(&game_controller, &mut renderer);
+                options_view.draw(&options_controller, &mut renderer);
+                debug_view.draw(&debug_controller, &mut renderer);
+                renderer.draw_letterbox([0.0, 0.0, 0.0, 1.0]);
 
                 debug_controller.fps_tick();
             });
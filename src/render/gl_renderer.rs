@@ -0,0 +1,94 @@
+use graphics::{self, Context, Transformed};
+use opengl_graphics::GlGraphics;
+use game::TextureMapper;
+use text_renderer::{FontSize, TextRenderer};
+use super::{Renderer, Viewport};
+
+/// Draws onto the window using OpenGL, for the duration of a single frame.
+pub struct GlRenderer<'a> {
+    /// The context for drawing onto the game's screen within the window.
+    context: Context,
+
+    /// The context for drawing onto the window itself.
+    window_context: Context,
+
+    viewport: Viewport,
+    g: &'a mut GlGraphics,
+    textures: &'a TextureMapper,
+    text_renderer: &'a TextRenderer,
+}
+
+impl<'a> GlRenderer<'a> {
+    /// Creates and returns a new instance of the GlRenderer struct, drawing
+    /// with the provided context and graphics backend onto the part of the
+    /// window that the viewport covers.
+    pub fn new(
+        context: Context,
+        g: &'a mut GlGraphics,
+        textures: &'a TextureMapper,
+        text_renderer: &'a TextRenderer,
+        viewport: Viewport,
+    ) -> GlRenderer<'a> {
+        let offset = viewport.offset();
+        GlRenderer {
+            context: context.trans(offset[0], offset[1]).scale(
+                viewport.scale(),
+                viewport.scale(),
+            ),
+            window_context: context,
+            viewport: viewport,
+            g: g,
+            textures: textures,
+            text_renderer: text_renderer,
+        }
+    }
+
+    /// Covers whatever part of the window the game's screen doesn't, hiding
+    /// anything drawn past its edges.
+    pub fn draw_letterbox(&mut self, color: [f32; 4]) {
+        for bar in self.viewport.letterbox() {
+            graphics::rectangle(color, bar, self.window_context.transform, self.g);
+        }
+    }
+}
+
+impl<'a> Renderer for GlRenderer<'a> {
+    fn size(&self) -> [f64; 2] {
+        self.viewport.screen_size()
+    }
+
+    fn line_height(&self, size: FontSize) -> f64 {
+        self.text_renderer.line_height(size) as f64
+    }
+
+    fn clear(&mut self, color: [f32; 4]) {
+        graphics::clear(color, self.g);
+    }
+
+    fn draw_sprite(&mut self, key: &str, rect: [f64; 4], color: [f32; 4]) {
+        self.textures
+            .draw_at(rect, key, color, self.context.transform, self.g);
+    }
+
+    fn draw_text(&mut self, text: &str, position: [f64; 2], size: FontSize, color: [f32; 4]) {
+        self.text_renderer
+            .draw_at(position, text, self.context.transform, size, color, self.g);
+    }
+
+    fn draw_rect(&mut self, rect: [f64; 4], color: [f32; 4]) {
+        graphics::rectangle(color, rect, self.context.transform, self.g);
+    }
+
+    fn draw_border(&mut self, rect: [f64; 4], color: [f32; 4], thickness: f64) {
+        graphics::Rectangle::new_border(color, thickness).draw(
+            rect,
+            &self.context.draw_state,
+            self.context.transform,
+            self.g,
+        );
+    }
+
+    fn draw_ellipse(&mut self, rect: [f64; 4], color: [f32; 4]) {
+        graphics::ellipse(color, rect, self.context.transform, self.g);
+    }
+}
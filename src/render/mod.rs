@@ -0,0 +1,47 @@
+#![deny(missing_docs)]
+
+//! The drawing operations that views are built from, kept separate from any
+//! particular graphics library so that the same views can be drawn by
+//! different backends.
+
+mod gl_renderer;
+mod software_renderer;
+mod terminal_renderer;
+mod viewport;
+
+pub use self::gl_renderer::GlRenderer;
+pub use self::software_renderer::SoftwareRenderer;
+pub use self::terminal_renderer::TerminalRenderer;
+pub use self::viewport::Viewport;
+
+use text_renderer::FontSize;
+
+/// A surface that views draw onto.  Positions and sizes are given in the
+/// backend's own units, starting from the top-left corner of the surface.
+pub trait Renderer {
+    /// Returns the width and height of the surface being drawn onto.
+    fn size(&self) -> [f64; 2];
+
+    /// Returns the distance between lines of text of the provided size.
+    fn line_height(&self, size: FontSize) -> f64;
+
+    /// Fills the whole surface with the provided color.
+    fn clear(&mut self, color: [f32; 4]);
+
+    /// Draws the sprite found under `key` in the texture atlas, stretched to
+    /// fill the provided rectangle and tinted with the provided color.
+    fn draw_sprite(&mut self, key: &str, rect: [f64; 4], color: [f32; 4]);
+
+    /// Draws a line of text with its top-left corner at the provided position.
+    fn draw_text(&mut self, text: &str, position: [f64; 2], size: FontSize, color: [f32; 4]);
+
+    /// Fills the provided rectangle with the provided color.
+    fn draw_rect(&mut self, rect: [f64; 4], color: [f32; 4]);
+
+    /// Outlines the provided rectangle with a border of the provided
+    /// thickness.
+    fn draw_border(&mut self, rect: [f64; 4], color: [f32; 4], thickness: f64);
+
+    /// Fills the ellipse that fits inside the provided rectangle.
+    fn draw_ellipse(&mut self, rect: [f64; 4], color: [f32; 4]);
+}
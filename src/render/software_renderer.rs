@@ -0,0 +1,269 @@
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+use bmfont::BMFont;
+use image::RgbaImage;
+use texture_coords::CoordinateMap;
+use game::TextureMapper;
+use text_renderer::{self, FontSize};
+use super::Renderer;
+
+/// An image held as rows of RGBA bytes.
+struct Bitmap {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Bitmap {
+    fn new(width: u32, height: u32) -> Bitmap {
+        Bitmap {
+            width: width,
+            height: height,
+            pixels: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    fn from_image(image: RgbaImage) -> Bitmap {
+        Bitmap {
+            width: image.width(),
+            height: image.height(),
+            pixels: image.into_raw(),
+        }
+    }
+
+    /// Returns the color of the provided pixel, with each channel between
+    /// zero and one.
+    fn get(&self, x: u32, y: u32) -> [f32; 4] {
+        let index = (y as usize * self.width as usize + x as usize) * 4;
+        let p = &self.pixels[index..index + 4];
+        [
+            p[0] as f32 / 255.0,
+            p[1] as f32 / 255.0,
+            p[2] as f32 / 255.0,
+            p[3] as f32 / 255.0,
+        ]
+    }
+
+    /// Returns the pixels whose centers lie within the provided rectangle,
+    /// as ranges of columns and rows.
+    fn covered(&self, rect: [f64; 4]) -> (Range<u32>, Range<u32>) {
+        let first = |start: f64, limit: u32| ((start - 0.5).ceil().max(0.0) as u32).min(limit);
+        (
+            first(rect[0], self.width)..first(rect[0] + rect[2], self.width),
+            first(rect[1], self.height)..first(rect[1] + rect[3], self.height),
+        )
+    }
+
+    /// Draws the provided color over the provided pixel, blending by its
+    /// alpha the way that the window does.
+    fn blend(&mut self, x: u32, y: u32, color: [f32; 4]) {
+        let index = (y as usize * self.width as usize + x as usize) * 4;
+        let alpha = color[3].max(0.0).min(1.0);
+        for channel in 0..3 {
+            let beneath = self.pixels[index + channel] as f32 / 255.0;
+            let blended = color[channel] * alpha + beneath * (1.0 - alpha);
+            self.pixels[index + channel] = to_byte(blended);
+        }
+        let beneath = self.pixels[index + 3] as f32 / 255.0;
+        self.pixels[index + 3] = to_byte(alpha + beneath * (1.0 - alpha));
+    }
+}
+
+/// Draws into an image in memory without any help from the graphics card,
+/// so that the game can be drawn where there is no window, such as when
+/// taking screenshots from the command line.
+////
+/// Sprites and text are drawn the same way the OpenGL backend draws them:
+/// each texel is multiplied by the tint color and blended over what is
+/// beneath it, sampling the nearest texel without any filtering.
+pub struct SoftwareRenderer {
+    canvas: Bitmap,
+    atlas: Bitmap,
+    coords: CoordinateMap,
+    fonts: Vec<(FontSize, BMFont, Bitmap)>,
+}
+
+impl SoftwareRenderer {
+    /// Creates and returns a new instance of the SoftwareRenderer struct,
+    /// drawing into an image of the provided size.
+    pub fn new(width: u32, height: u32) -> SoftwareRenderer {
+        let fonts = [FontSize::Size32, FontSize::Size24, FontSize::Size18]
+            .iter()
+            .map(|size| {
+                let (font, page) = text_renderer::load_font(*size);
+                (*size, font, Bitmap::from_image(page))
+            })
+            .collect();
+        SoftwareRenderer {
+            canvas: Bitmap::new(width, height),
+            atlas: Bitmap::from_image(TextureMapper::load_atlas()),
+            coords: TextureMapper::load_coords(),
+            fonts: fonts,
+        }
+    }
+
+    /// Writes everything drawn so far to a PNG file at the provided path,
+    /// creating its folder if necessary.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|why| {
+                format!("Unable to create screenshot folder: {:?}", why)
+            })?;
+        }
+        self.image()?.save(path).map_err(|why| {
+            format!("Unable to save screenshot: {:?}", why)
+        })
+    }
+
+    /// Returns a copy of everything drawn so far.
+    pub fn image(&self) -> Result<RgbaImage, String> {
+        RgbaImage::from_raw(
+            self.canvas.width,
+            self.canvas.height,
+            self.canvas.pixels.clone(),
+        ).ok_or_else(|| String::from("The screenshot is the wrong size."))
+    }
+
+    fn font(&self, size: FontSize) -> Option<&(FontSize, BMFont, Bitmap)> {
+        self.fonts.iter().find(|f| f.0 == size)
+    }
+
+    /// Draws part of the provided image, stretched to fill the destination
+    /// and multiplied by the tint color.
+    fn blit(
+        canvas: &mut Bitmap,
+        image: &Bitmap,
+        source: [f64; 4],
+        destination: [f64; 4],
+        tint: [f32; 4],
+    ) {
+        if destination[2] <= 0.0 || destination[3] <= 0.0 || image.width == 0 ||
+            image.height == 0
+        {
+            return;
+        }
+        let (columns, rows) = canvas.covered(destination);
+        for y in rows {
+            let v = source[1] + (y as f64 + 0.5 - destination[1]) / destination[3] * source[3];
+            let texel_y = (v.floor().max(0.0) as u32).min(image.height - 1);
+            for x in columns.clone() {
+                let u = source[0] +
+                    (x as f64 + 0.5 - destination[0]) / destination[2] * source[2];
+                let texel_x = (u.floor().max(0.0) as u32).min(image.width - 1);
+                let texel = image.get(texel_x, texel_y);
+                canvas.blend(
+                    x,
+                    y,
+                    [
+                        texel[0] * tint[0],
+                        texel[1] * tint[1],
+                        texel[2] * tint[2],
+                        texel[3] * tint[3],
+                    ],
+                );
+            }
+        }
+    }
+}
+
+impl Renderer for SoftwareRenderer {
+    fn size(&self) -> [f64; 2] {
+        [self.canvas.width as f64, self.canvas.height as f64]
+    }
+
+    fn line_height(&self, size: FontSize) -> f64 {
+        self.font(size).map_or(0.0, |f| f.1.line_height() as f64)
+    }
+
+    fn clear(&mut self, color: [f32; 4]) {
+        let pixel = [
+            to_byte(color[0]),
+            to_byte(color[1]),
+            to_byte(color[2]),
+            to_byte(color[3]),
+        ];
+        for chunk in self.canvas.pixels.chunks_mut(4) {
+            chunk.copy_from_slice(&pixel);
+        }
+    }
+
+    fn draw_sprite(&mut self, key: &str, rect: [f64; 4], color: [f32; 4]) {
+        match self.coords.map.get(key) {
+            Some(source) => {
+                SoftwareRenderer::blit(&mut self.canvas, &self.atlas, *source, rect, color);
+            }
+            None => error!("No texture coordinate info found for key {:?}", key),
+        }
+    }
+
+    fn draw_text(&mut self, text: &str, position: [f64; 2], size: FontSize, color: [f32; 4]) {
+        let index = match self.fonts.iter().position(|f| f.0 == size) {
+            Some(index) => index,
+            None => return,
+        };
+        let (_, ref font, ref page) = self.fonts[index];
+        if let Ok(characters) = font.parse(text) {
+            for c in &characters {
+                SoftwareRenderer::blit(
+                    &mut self.canvas,
+                    page,
+                    [
+                        c.page_rect.x as f64,
+                        c.page_rect.y as f64,
+                        c.page_rect.width as f64,
+                        c.page_rect.height as f64,
+                    ],
+                    [
+                        position[0] + c.screen_rect.x as f64,
+                        position[1] + c.screen_rect.y as f64,
+                        c.screen_rect.width as f64,
+                        c.screen_rect.height as f64,
+                    ],
+                    color,
+                );
+            }
+        }
+    }
+
+    fn draw_rect(&mut self, rect: [f64; 4], color: [f32; 4]) {
+        let (columns, rows) = self.canvas.covered(rect);
+        for y in rows {
+            for x in columns.clone() {
+                self.canvas.blend(x, y, color);
+            }
+        }
+    }
+
+    fn draw_border(&mut self, rect: [f64; 4], color: [f32; 4], thickness: f64) {
+        // each edge is a strip of the given thickness, centered on the edge
+        let half = thickness / 2.0;
+        let (x, y, w, h) = (rect[0], rect[1], rect[2], rect[3]);
+        self.draw_rect([x - half, y - half, w + thickness, thickness], color);
+        self.draw_rect([x - half, y + h - half, w + thickness, thickness], color);
+        self.draw_rect([x - half, y + half, thickness, h - thickness], color);
+        self.draw_rect([x + w - half, y + half, thickness, h - thickness], color);
+    }
+
+    fn draw_ellipse(&mut self, rect: [f64; 4], color: [f32; 4]) {
+        let radius = [rect[2] / 2.0, rect[3] / 2.0];
+        let center = [rect[0] + radius[0], rect[1] + radius[1]];
+        if radius[0] <= 0.0 || radius[1] <= 0.0 {
+            return;
+        }
+        let (columns, rows) = self.canvas.covered(rect);
+        for y in rows {
+            for x in columns.clone() {
+                let dx = (x as f64 + 0.5 - center[0]) / radius[0];
+                let dy = (y as f64 + 0.5 - center[1]) / radius[1];
+                if dx * dx + dy * dy <= 1.0 {
+                    self.canvas.blend(x, y, color);
+                }
+            }
+        }
+    }
+}
+
+fn to_byte(channel: f32) -> u8 {
+    (channel.max(0.0).min(1.0) * 255.0).round() as u8
+}
@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs::File;
+use std::io::{self, BufReader, Write};
+use std::ops::Range;
+use find_folder::Search;
+use serde_json;
+use text_renderer::FontSize;
+use super::Renderer;
+
+const GLYPHS_DIR: &'static str = "assets/";
+const GLYPHS_FILE: &'static str = "glyphs.json";
+
+/// The size of a single character cell, in the units that views draw with.
+/// Roughly the size of a character in the window's smallest font, so that
+/// text laid out for the window fits about as well in the terminal.
+const CELL_WIDTH: f64 = 8.0;
+const CELL_HEIGHT: f64 = 16.0;
+
+/// The glyph drawn for sprites that only darken whatever is beneath them.
+const OVERLAY_GLYPH: char = ' ';
+
+/// The glyph drawn for projectiles in flight.
+const ELLIPSE_GLYPH: char = '*';
+
+/// A single character on the screen, with its colors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cell {
+    glyph: char,
+    fg: [f32; 3],
+    bg: [f32; 3],
+}
+
+impl Cell {
+    fn blank(bg: [f32; 3]) -> Cell {
+        Cell {
+            glyph: ' ',
+            fg: bg,
+            bg: bg,
+        }
+    }
+}
+
+/// Draws onto a grid of colored characters, written to a terminal using
+/// ANSI escape codes.
+////
+/// Sprites are drawn as the glyphs listed for their keys in the glyphs
+/// file, with any key not listed there drawn as its first letter.
+pub struct TerminalRenderer {
+    columns: usize,
+    rows: usize,
+    cells: Vec<Cell>,
+    glyphs: HashMap<String, char>,
+    truecolor: bool,
+}
+
+impl TerminalRenderer {
+    /// Creates and returns a new instance of the TerminalRenderer struct,
+    /// covering the provided number of character cells.
+    pub fn new(columns: usize, rows: usize) -> TerminalRenderer {
+        let glyphs = TerminalRenderer::load_glyphs().unwrap_or_else(|why| {
+            error!("{}  Sprites will be drawn as their first letters.", why);
+            HashMap::new()
+        });
+        let truecolor = env::var("COLORTERM")
+            .map(|c| c.contains("truecolor") || c.contains("24bit"))
+            .unwrap_or(false);
+        TerminalRenderer {
+            columns: columns,
+            rows: rows,
+            cells: vec![Cell::blank([0.0; 3]); columns * rows],
+            glyphs: glyphs,
+            truecolor: truecolor,
+        }
+    }
+
+    /// Changes the number of character cells drawn onto.
+    pub fn resize(&mut self, columns: usize, rows: usize) {
+        if columns != self.columns || rows != self.rows {
+            self.columns = columns;
+            self.rows = rows;
+            self.cells = vec![Cell::blank([0.0; 3]); columns * rows];
+        }
+    }
+
+    /// Writes everything drawn so far to the provided terminal, starting
+    /// from its top-left corner.
+    pub fn present<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let mut screen = String::from("\x1b[H");
+        for row in 0..self.rows {
+            let mut last: Option<([u8; 3], [u8; 3])> = None;
+            for cell in &self.cells[row * self.columns..(row + 1) * self.columns] {
+                let colors = (to_bytes(cell.fg), to_bytes(cell.bg));
+                if last != Some(colors) {
+                    screen.push_str(self.color_codes(colors.0, colors.1).as_str());
+                    last = Some(colors);
+                }
+                screen.push(cell.glyph);
+            }
+            screen.push_str("\x1b[0m");
+            if row + 1 < self.rows {
+                screen.push_str("\r\n");
+            }
+        }
+        out.write_all(screen.as_bytes())?;
+        out.flush()
+    }
+
+    /// Returns the escape codes that switch to the provided colors, using
+    /// 24-bit color if the terminal supports it, or the nearest of the 256
+    /// standard colors otherwise.
+    fn color_codes(&self, fg: [u8; 3], bg: [u8; 3]) -> String {
+        if self.truecolor {
+            format!(
+                "\x1b[38;2;{};{};{};48;2;{};{};{}m",
+                fg[0],
+                fg[1],
+                fg[2],
+                bg[0],
+                bg[1],
+                bg[2]
+            )
+        } else {
+            format!("\x1b[38;5;{};48;5;{}m", to_ansi_256(fg), to_ansi_256(bg))
+        }
+    }
+
+    /// Returns the indices of the cells whose centers lie within the provided
+    /// rectangle, as ranges of columns and rows.
+    fn covered(&self, rect: [f64; 4]) -> (Range<usize>, Range<usize>) {
+        let first = |start: f64, size: f64| ((start / size) - 0.5).ceil().max(0.0) as usize;
+        let columns = first(rect[0], CELL_WIDTH)..
+            first(rect[0] + rect[2], CELL_WIDTH).min(self.columns);
+        let rows = first(rect[1], CELL_HEIGHT)..first(rect[1] + rect[3], CELL_HEIGHT).min(self.rows);
+        (columns, rows)
+    }
+
+    /// Returns the cell holding the provided position, if it is on the screen.
+    fn cell_at(&mut self, position: [f64; 2]) -> Option<&mut Cell> {
+        if position[0] < 0.0 || position[1] < 0.0 {
+            return None;
+        }
+        let column = (position[0] / CELL_WIDTH) as usize;
+        let row = (position[1] / CELL_HEIGHT) as usize;
+        if column < self.columns && row < self.rows {
+            Some(&mut self.cells[row * self.columns + column])
+        } else {
+            None
+        }
+    }
+
+    /// Returns the glyph for the sprite, which is the same for every frame
+    /// of an animated sprite unless a frame, such as "fluid_2", has its own.
+    fn glyph(&self, key: &str) -> char {
+        let base = key.trim_end_matches(|c: char| c.is_ascii_digit())
+            .trim_end_matches('_');
+        match self.glyphs.get(key).or_else(|| self.glyphs.get(base)) {
+            Some(glyph) => *glyph,
+            None => key.chars().next().unwrap_or('?'),
+        }
+    }
+
+    fn load_glyphs() -> Result<HashMap<String, char>, String> {
+        let assets = Search::ParentsThenKids(3, 3)
+            .for_folder(GLYPHS_DIR)
+            .map_err(|why| format!("Unable to find assets folder: {:?}", why))?;
+        let file = File::open(assets.join(GLYPHS_FILE)).map_err(|why| {
+            format!("Unable to open glyphs file: {:?}", why)
+        })?;
+        serde_json::from_reader(BufReader::new(file)).map_err(|why| {
+            format!("Unable to parse glyphs file: {}", why)
+        })
+    }
+}
+
+impl Renderer for TerminalRenderer {
+    fn size(&self) -> [f64; 2] {
+        [
+            self.columns as f64 * CELL_WIDTH,
+            self.rows as f64 * CELL_HEIGHT,
+        ]
+    }
+
+    fn line_height(&self, _: FontSize) -> f64 {
+        CELL_HEIGHT
+    }
+
+    fn clear(&mut self, color: [f32; 4]) {
+        let blank = Cell::blank(blend([0.0; 3], color));
+        for cell in &mut self.cells {
+            *cell = blank;
+        }
+    }
+
+    fn draw_sprite(&mut self, key: &str, rect: [f64; 4], color: [f32; 4]) {
+        let glyph = self.glyph(key);
+        let (columns, rows) = self.covered(rect);
+        for row in rows {
+            for column in columns.clone() {
+                let cell = &mut self.cells[row * self.columns + column];
+                if glyph == OVERLAY_GLYPH {
+                    // shading, such as the dimming of distant tiles
+                    cell.fg = blend(cell.fg, color);
+                    cell.bg = blend(cell.bg, color);
+                } else if column == columns.start {
+                    cell.glyph = glyph;
+                    cell.fg = blend(cell.bg, color);
+                } else {
+                    // sprites wider than a cell are drawn once, at their left
+                    cell.glyph = ' ';
+                }
+            }
+        }
+    }
+
+    fn draw_text(&mut self, text: &str, position: [f64; 2], _: FontSize, color: [f32; 4]) {
+        // text sits on the row holding the middle of the line
+        let mut position = [position[0], position[1] + CELL_HEIGHT / 2.0];
+        for glyph in text.chars() {
+            if let Some(cell) = self.cell_at(position) {
+                cell.glyph = glyph;
+                cell.fg = blend(cell.bg, color);
+            }
+            position[0] += CELL_WIDTH;
+        }
+    }
+
+    fn draw_rect(&mut self, rect: [f64; 4], color: [f32; 4]) {
+        let (columns, rows) = self.covered(rect);
+        for row in rows {
+            for column in columns.clone() {
+                let cell = &mut self.cells[row * self.columns + column];
+                cell.bg = blend(cell.bg, color);
+            }
+        }
+    }
+
+    fn draw_border(&mut self, rect: [f64; 4], color: [f32; 4], _: f64) {
+        // a border can be no thinner than a cell, so shade the outermost ones
+        let (columns, rows) = self.covered(rect);
+        for row in rows.clone() {
+            for column in columns.clone() {
+                let edge = row == rows.start || row + 1 == rows.end || column == columns.start ||
+                    column + 1 == columns.end;
+                if edge {
+                    let cell = &mut self.cells[row * self.columns + column];
+                    cell.bg = blend(cell.bg, color);
+                }
+            }
+        }
+    }
+
+    fn draw_ellipse(&mut self, rect: [f64; 4], color: [f32; 4]) {
+        let center = [rect[0] + rect[2] / 2.0, rect[1] + rect[3] / 2.0];
+        if let Some(cell) = self.cell_at(center) {
+            cell.glyph = ELLIPSE_GLYPH;
+            cell.fg = blend(cell.bg, color);
+        }
+    }
+}
+
+/// Returns the color that results from drawing `color` over `beneath`,
+/// according to its alpha.
+fn blend(beneath: [f32; 3], color: [f32; 4]) -> [f32; 3] {
+    let alpha = color[3].max(0.0).min(1.0);
+    [
+        beneath[0] * (1.0 - alpha) + color[0] * alpha,
+        beneath[1] * (1.0 - alpha) + color[1] * alpha,
+        beneath[2] * (1.0 - alpha) + color[2] * alpha,
+    ]
+}
+
+fn to_bytes(color: [f32; 3]) -> [u8; 3] {
+    let byte = |c: f32| (c.max(0.0).min(1.0) * 255.0).round() as u8;
+    [byte(color[0]), byte(color[1]), byte(color[2])]
+}
+
+/// Returns the closest of the 256 standard terminal colors, choosing from
+/// the 6x6x6 color cube, or from the grayscale ramp for grays.
+fn to_ansi_256(color: [u8; 3]) -> u8 {
+    let (r, g, b) = (color[0], color[1], color[2]);
+    let (min, max) = (r.min(g).min(b), r.max(g).max(b));
+    if max - min < 10 {
+        // the ramp runs from 8 to 238 in 24 steps of 10
+        return if r < 4 {
+            16
+        } else if r > 246 {
+            231
+        } else {
+            232 + ((r as u32).saturating_sub(3) / 10).min(23) as u8
+        };
+    }
+    let level = |c: u8| ((c as u32 * 5 + 127) / 255) as u8;
+    16 + 36 * level(r) + 6 * level(g) + level(b)
+}
@@ -0,0 +1,181 @@
+use settings::Scaling;
+
+/// Where the game's screen sits within the window, and how much it is scaled
+/// by.  Views draw onto the screen, which is the same as the window unless
+/// the game is scaled, in which case any part of the window that it doesn't
+/// cover is left as black bars.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    screen: [f64; 2],
+    window: [f64; 2],
+    scale: f64,
+    offset: [f64; 2],
+}
+
+impl Viewport {
+    /// Creates and returns a new instance of the Viewport struct, for a
+    /// screen that fills a window of the provided size without scaling.
+    pub fn unscaled(size: [f64; 2]) -> Viewport {
+        Viewport {
+            screen: size,
+            window: size,
+            scale: 1.0,
+            offset: [0.0; 2],
+        }
+    }
+
+    /// Creates and returns a new instance of the Viewport struct, fitting a
+    /// screen of the provided size into a window of the provided size the
+    /// way the scaling mode says to.
+    pub fn new(scaling: Scaling, screen: [u32; 2], window: [f64; 2]) -> Viewport {
+        let screen = [screen[0].max(1) as f64, screen[1].max(1) as f64];
+        let fit = (window[0] / screen[0]).min(window[1] / screen[1]);
+        let scale = match scaling {
+            Scaling::Resize => return Viewport::unscaled(window),
+            Scaling::Fit => fit,
+
+            // windows too small for the screen crop it, rather than shrinking
+            // it by a fraction
+            Scaling::Integer => fit.floor().max(1.0),
+        };
+
+        // keep the screen centered, on whole pixels
+        Viewport {
+            screen: screen,
+            window: window,
+            scale: scale,
+            offset: [
+                ((window[0] - screen[0] * scale) / 2.0).floor(),
+                ((window[1] - screen[1] * scale) / 2.0).floor(),
+            ],
+        }
+    }
+
+    /// Returns the width and height of the screen that views draw onto.
+    pub fn screen_size(&self) -> [f64; 2] {
+        self.screen
+    }
+
+    /// Returns how many of the window's units each unit of the screen covers.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Returns the position of the screen's top-left corner in the window.
+    pub fn offset(&self) -> [f64; 2] {
+        self.offset
+    }
+
+    /// Returns the point on the screen under the provided point in the
+    /// window, such as the mouse cursor.
+    pub fn window_to_screen(&self, point: [f64; 2]) -> [f64; 2] {
+        [
+            (point[0] - self.offset[0]) / self.scale,
+            (point[1] - self.offset[1]) / self.scale,
+        ]
+    }
+
+    /// Returns the rectangles of the window, in the window's units, that the
+    /// screen doesn't cover.
+    pub fn letterbox(&self) -> Vec<[f64; 4]> {
+        let (w, h) = (self.window[0], self.window[1]);
+        let left = self.offset[0];
+        let top = self.offset[1];
+        let right = self.offset[0] + self.screen[0] * self.scale;
+        let bottom = self.offset[1] + self.screen[1] * self.scale;
+        [
+            [0.0, 0.0, w, top],
+            [0.0, bottom, w, h - bottom],
+            [0.0, top, left, bottom - top],
+            [right, top, w - right, bottom - top],
+        ].iter()
+            .cloned()
+            .filter(|bar| bar[2] > 0.0 && bar[3] > 0.0)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: [u32; 2] = [640, 480];
+
+    fn covers(bar: &[f64; 4], point: [f64; 2]) -> bool {
+        point[0] >= bar[0] && point[0] < bar[0] + bar[2] && point[1] >= bar[1] &&
+            point[1] < bar[1] + bar[3]
+    }
+
+    #[test]
+    fn resize_fills_the_window() {
+        let viewport = Viewport::new(Scaling::Resize, SCREEN, [1001.0, 777.0]);
+        assert_eq!(viewport, Viewport::unscaled([1001.0, 777.0]));
+        assert_eq!(viewport.screen_size(), [1001.0, 777.0]);
+        assert!(viewport.letterbox().is_empty());
+        assert_eq!(viewport.window_to_screen([10.0, 20.0]), [10.0, 20.0]);
+    }
+
+    #[test]
+    fn fit_scales_to_the_narrower_side() {
+        let viewport = Viewport::new(Scaling::Fit, SCREEN, [1001.0, 720.0]);
+        assert_eq!(viewport.screen_size(), [640.0, 480.0]);
+        assert_eq!(viewport.scale(), 1.5);
+        assert_eq!(viewport.offset(), [20.0, 0.0]);
+
+        // bars down the left and right, with the odd pixel on the right
+        assert_eq!(
+            viewport.letterbox(),
+            vec![[0.0, 0.0, 20.0, 720.0], [980.0, 0.0, 21.0, 720.0]]
+        );
+    }
+
+    #[test]
+    fn fit_shrinks_into_small_windows() {
+        let viewport = Viewport::new(Scaling::Fit, SCREEN, [320.0, 300.0]);
+        assert_eq!(viewport.scale(), 0.5);
+        assert_eq!(viewport.offset(), [0.0, 30.0]);
+        assert_eq!(viewport.window_to_screen([160.0, 150.0]), [320.0, 240.0]);
+    }
+
+    #[test]
+    fn integer_scales_by_whole_numbers() {
+        let viewport = Viewport::new(Scaling::Integer, SCREEN, [1930.0, 1451.0]);
+        assert_eq!(viewport.scale(), 3.0);
+        assert_eq!(viewport.offset(), [5.0, 5.0]);
+        assert_eq!(viewport.letterbox().len(), 4);
+
+        let viewport = Viewport::new(Scaling::Integer, SCREEN, [1001.0, 777.0]);
+        assert_eq!(viewport.scale(), 1.0);
+        assert_eq!(viewport.offset(), [180.0, 148.0]);
+    }
+
+    #[test]
+    fn integer_crops_in_small_windows() {
+        let viewport = Viewport::new(Scaling::Integer, SCREEN, [301.0, 200.0]);
+        assert_eq!(viewport.scale(), 1.0);
+        assert_eq!(viewport.offset(), [-170.0, -140.0]);
+        assert!(viewport.letterbox().is_empty());
+        assert_eq!(viewport.window_to_screen([0.0, 0.0]), [170.0, 140.0]);
+    }
+
+    #[test]
+    fn mouse_in_the_bars_is_off_the_screen() {
+        let viewport = Viewport::new(Scaling::Integer, SCREEN, [1001.0, 777.0]);
+        let bars = viewport.letterbox();
+        for &(point, inside) in &[
+            ([0.0, 0.0], false),
+            ([179.0, 400.0], false),
+            ([1000.0, 776.0], false),
+            ([500.0, 10.0], false),
+            ([180.0, 148.0], true),
+            ([819.0, 627.0], true),
+        ] {
+            let on_screen = viewport.window_to_screen(point);
+            let within = on_screen[0] >= 0.0 && on_screen[0] < 640.0 &&
+                on_screen[1] >= 0.0 && on_screen[1] < 480.0;
+            assert_eq!(within, inside, "{:?} is at {:?}", point, on_screen);
+            assert_eq!(bars.iter().any(|bar| covers(bar, point)), !inside, "{:?}", point);
+        }
+        assert_eq!(viewport.window_to_screen([180.0, 148.0]), [0.0, 0.0]);
+    }
+}
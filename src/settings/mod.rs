@@ -0,0 +1,352 @@
+#![deny(missing_docs)]
+
+//! The user-editable settings file, which holds options for the window,
+//! rendering, the camera, and gameplay, along with the in-game menu for
+//! changing them.
+
+mod options_controller;
+mod options_view;
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+use std::str::FromStr;
+use find_folder::Search;
+use serde_json;
+
+pub use self::options_controller::OptionsController;
+pub use self::options_view::OptionsView;
+
+const SETTINGS_DIR: &'static str = "assets/";
+const SETTINGS_FILE: &'static str = "settings.json";
+
+/// The ways in which the game can be fitted into a window that is a
+/// different size than the one given in the settings.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scaling {
+    /// The game is drawn at its actual size and fills the window, showing
+    /// more of the map in larger windows.
+    Resize,
+
+    /// The game is drawn at the size given in the settings, then scaled to
+    /// fill as much of the window as it can without being stretched.
+    Fit,
+
+    /// Like `Fit`, but only ever scaled by whole numbers, and with tiles
+    /// sized in whole multiples of the sprites, so that every texel covers
+    /// the same number of pixels.  Windows too small for the game crop it.
+    Integer,
+}
+
+impl Scaling {
+    /// Returns the name of the scaling mode, as written in the settings file.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Scaling::Resize => "resize",
+            Scaling::Fit => "fit",
+            Scaling::Integer => "integer",
+        }
+    }
+}
+
+impl FromStr for Scaling {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Scaling, ()> {
+        match name {
+            "resize" => Ok(Scaling::Resize),
+            "fit" => Ok(Scaling::Fit),
+            "integer" => Ok(Scaling::Integer),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Options for the window and how often it is drawn.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowOptions {
+    /// The width of the window when the game starts, which is also the
+    /// width of the game when it is scaled.
+    pub width: u32,
+
+    /// The height of the window when the game starts, which is also the
+    /// height of the game when it is scaled.
+    pub height: u32,
+
+    /// Whether the window covers the whole screen.
+    pub fullscreen: bool,
+
+    /// Whether the window is drawn without a title bar and border.
+    pub borderless: bool,
+
+    /// How the game is fitted into the window when their sizes differ.
+    pub scaling: Scaling,
+
+    /// The most frames drawn each second.
+    pub max_fps: u64,
+
+    /// Whether to wait for the display before drawing each frame.
+    pub vsync: bool,
+
+    /// Whether to only update when there is input, instead of every frame.
+    pub lazy: bool,
+
+    /// Whether pressing escape closes the window.
+    pub exit_on_esc: bool,
+}
+
+impl Default for WindowOptions {
+    fn default() -> WindowOptions {
+        WindowOptions {
+            width: 1386,
+            height: 792,
+            fullscreen: false,
+            borderless: false,
+            scaling: Scaling::Resize,
+            max_fps: 65,
+            vsync: true,
+            lazy: false,
+            exit_on_esc: true,
+        }
+    }
+}
+
+/// Options that change how the game plays.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameplayOptions {
+    /// How far, in tiles, the player can see.
+    pub view_distance: u32,
+
+    /// How many tiles at the edge of the player's sight are only dimly seen.
+    pub visibility_falloff: u32,
+
+    /// The time, in milliseconds, between each step the player takes while
+    /// travelling, exploring, or running.
+    pub travel_step_millis: u64,
+
+    /// Whether actors slide between tiles, flash when hurt, and show the
+    /// damage they take, with input waiting until they are done.  When off,
+    /// every turn is shown at once.
+    pub animations: bool,
+}
+
+impl Default for GameplayOptions {
+    fn default() -> GameplayOptions {
+        GameplayOptions {
+            view_distance: 8,
+            visibility_falloff: 5,
+            travel_step_millis: 60,
+            animations: true,
+        }
+    }
+}
+
+/// Options for how the camera frames the map.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraOptions {
+    /// The width and height of a tile on the screen at normal zoom.
+    pub tile_size: f64,
+
+    /// The size, in tiles, of the area in the middle of the screen that the
+    /// player can move around in without the camera following.
+    pub deadzone: [f64; 2],
+
+    /// The scales that the camera can zoom between, from farthest out to
+    /// closest in.
+    pub zoom_levels: Vec<f64>,
+
+    /// The index of the zoom level that the camera starts at.
+    pub default_zoom: usize,
+}
+
+impl Default for CameraOptions {
+    fn default() -> CameraOptions {
+        CameraOptions {
+            tile_size: 24.0,
+            deadzone: [8.0, 6.0],
+            zoom_levels: vec![0.5, 0.75, 1.0, 1.5, 2.0],
+            default_zoom: 2,
+        }
+    }
+}
+
+impl CameraOptions {
+    /// Returns an error unless the tile size and every zoom level are
+    /// positive and there is at least one zoom level.
+    pub fn check(&self) -> Result<(), String> {
+        if self.tile_size <= 0.0 || self.zoom_levels.is_empty() ||
+            self.zoom_levels.iter().any(|z| *z <= 0.0)
+        {
+            return Err(String::from(
+                "The camera needs a positive tile size and at least one zoom level, all \
+                 of them positive.",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The contents of the settings file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Options for the window.
+    pub window: WindowOptions,
+
+    /// Options for the camera.
+    pub camera: CameraOptions,
+
+    /// Options for gameplay.
+    pub gameplay: GameplayOptions,
+}
+
+impl Settings {
+    /// Loads the settings file from the assets folder, writing the default
+    /// settings there first if it doesn't exist.
+    pub fn load() -> Settings {
+        Settings::read().unwrap_or_else(|why| {
+            error!("{}  Using the default settings.", why);
+            Settings::default()
+        })
+    }
+
+    /// Writes the settings to the settings file in the assets folder.
+    pub fn save(&self) -> Result<(), String> {
+        let path = Settings::path()?;
+        let file = File::create(&path).map_err(|why| {
+            format!("Unable to create settings file: {:?}", why)
+        })?;
+        serde_json::to_writer_pretty(BufWriter::new(file), self)
+            .map_err(|why| format!("Unable to write settings file: {}", why))
+    }
+
+    /// Changes the setting with the provided name, written the way it is
+    /// nested in the settings file, such as "window.width", to the provided
+    /// value.  Settings holding more than one number, such as
+    /// "camera.zoom_levels", take them separated by commas.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        if name.starts_with("camera.") {
+            let mut camera = self.camera.clone();
+            match name {
+                "camera.tile_size" => camera.tile_size = parse(name, value)?,
+                "camera.deadzone" => {
+                    let deadzone = parse_list(name, value)?;
+                    if deadzone.len() != 2 {
+                        return Err(format!("{:?} needs a width and a height.", name));
+                    }
+                    camera.deadzone = [deadzone[0], deadzone[1]];
+                }
+                "camera.zoom_levels" => camera.zoom_levels = parse_list(name, value)?,
+                "camera.default_zoom" => camera.default_zoom = parse(name, value)?,
+                _ => return Err(format!("There is no setting named {:?}.", name)),
+            }
+            camera.check()?;
+            self.camera = camera;
+            return Ok(());
+        }
+        match name {
+            "window.width" => self.window.width = parse(name, value)?,
+            "window.height" => self.window.height = parse(name, value)?,
+            "window.fullscreen" => self.window.fullscreen = parse(name, value)?,
+            "window.borderless" => self.window.borderless = parse(name, value)?,
+            "window.scaling" => self.window.scaling = parse(name, value)?,
+            "window.max_fps" => self.window.max_fps = parse(name, value)?,
+            "window.vsync" => self.window.vsync = parse(name, value)?,
+            "window.lazy" => self.window.lazy = parse(name, value)?,
+            "window.exit_on_esc" => self.window.exit_on_esc = parse(name, value)?,
+            "gameplay.view_distance" => self.gameplay.view_distance = parse(name, value)?,
+            "gameplay.visibility_falloff" => {
+                self.gameplay.visibility_falloff = parse(name, value)?
+            }
+            "gameplay.travel_step_millis" => {
+                self.gameplay.travel_step_millis = parse(name, value)?
+            }
+            "gameplay.animations" => self.gameplay.animations = parse(name, value)?,
+            _ => return Err(format!("There is no setting named {:?}.", name)),
+        }
+        Ok(())
+    }
+
+    fn path() -> Result<PathBuf, String> {
+        let assets = Search::ParentsThenKids(3, 3)
+            .for_folder(SETTINGS_DIR)
+            .map_err(|why| format!("Unable to find assets folder: {:?}", why))?;
+        Ok(assets.join(SETTINGS_FILE))
+    }
+
+    fn read() -> Result<Settings, String> {
+        let path = Settings::path()?;
+        if !path.exists() {
+            Settings::default().save()?;
+        }
+        let file = File::open(path).map_err(|why| {
+            format!("Unable to open settings file: {:?}", why)
+        })?;
+        let mut settings: Settings = serde_json::from_reader(BufReader::new(file))
+            .map_err(|why| format!("Unable to parse settings file: {}", why))?;
+        if let Err(why) = settings.camera.check() {
+            error!("{}  Using the default camera settings.", why);
+            settings.camera = CameraOptions::default();
+        }
+        Ok(settings)
+    }
+}
+
+fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| {
+        format!("{:?} is not a valid value for {:?}.", value, name)
+    })
+}
+
+fn parse_list<T: FromStr>(name: &str, value: &str) -> Result<Vec<T>, String> {
+    value.split(',').map(|v| parse(name, v.trim())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_changes_nested_settings() {
+        let mut settings = Settings::default();
+        settings.set("window.width", "1024").unwrap();
+        settings.set("window.scaling", "integer").unwrap();
+        settings.set("gameplay.animations", "false").unwrap();
+        assert_eq!(settings.window.width, 1024);
+        assert_eq!(settings.window.scaling, Scaling::Integer);
+        assert!(!settings.gameplay.animations);
+    }
+
+    #[test]
+    fn set_takes_camera_lists() {
+        let mut settings = Settings::default();
+        settings.set("camera.zoom_levels", "1, 2,3").unwrap();
+        settings.set("camera.deadzone", "4,2").unwrap();
+        assert_eq!(settings.camera.zoom_levels, vec![1.0, 2.0, 3.0]);
+        assert_eq!(settings.camera.deadzone, [4.0, 2.0]);
+    }
+
+    #[test]
+    fn set_rejects_bad_values() {
+        let mut settings = Settings::default();
+        assert!(settings.set("window.width", "wide").is_err());
+        assert!(settings.set("window.depth", "1").is_err());
+        assert!(settings.set("camera.deadzone", "1,2,3").is_err());
+        assert!(settings.set("camera.tile_size", "0").is_err());
+        assert!(settings.set("camera.zoom_levels", "").is_err());
+        assert_eq!(settings, Settings::default());
+    }
+
+    #[test]
+    fn missing_sections_use_defaults() {
+        let settings: Settings = serde_json::from_str(r#"{ "window": { "width": 800 } }"#)
+            .unwrap();
+        assert_eq!(settings.window.width, 800);
+        assert_eq!(settings.window.height, WindowOptions::default().height);
+        assert_eq!(settings.camera, CameraOptions::default());
+    }
+}
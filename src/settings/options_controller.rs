@@ -0,0 +1,339 @@
+use std::collections::VecDeque;
+use input::Control;
+use status::ControllerStatus;
+use super::{Scaling, Settings};
+
+/// The settings that can be changed from the options menu, in the order
+/// they are listed.
+const ENTRIES: [Entry; 10] = [
+    Entry::ViewDistance,
+    Entry::VisibilityFalloff,
+    Entry::TravelStep,
+    Entry::Animations,
+    Entry::WindowSize,
+    Entry::Fullscreen,
+    Entry::Borderless,
+    Entry::Scaling,
+    Entry::MaxFps,
+    Entry::Vsync,
+];
+
+/// The window sizes that can be chosen from the options menu.
+const WINDOW_SIZES: [[u32; 2]; 6] = [
+    [960, 540],
+    [1280, 720],
+    [1386, 792],
+    [1600, 900],
+    [1920, 1080],
+    [2560, 1440],
+];
+
+const SCALING_MODES: [Scaling; 3] = [Scaling::Resize, Scaling::Fit, Scaling::Integer];
+
+const MAX_VIEW_DISTANCE: u32 = 30;
+const MAX_TRAVEL_STEP_MILLIS: u64 = 500;
+const TRAVEL_STEP_INCREMENT: u64 = 20;
+const MIN_FPS: u64 = 15;
+const MAX_FPS: u64 = 240;
+const FPS_INCREMENT: u64 = 5;
+
+/// A setting listed in the options menu.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Entry {
+    ViewDistance,
+    VisibilityFalloff,
+    TravelStep,
+    Animations,
+    WindowSize,
+    Fullscreen,
+    Borderless,
+    Scaling,
+    MaxFps,
+    Vsync,
+}
+
+impl Entry {
+    fn label(&self) -> &'static str {
+        match *self {
+            Entry::ViewDistance => "View distance",
+            Entry::VisibilityFalloff => "Dim edge of sight",
+            Entry::TravelStep => "Travel step delay",
+            Entry::Animations => "Animations",
+            Entry::WindowSize => "Window size",
+            Entry::Fullscreen => "Fullscreen",
+            Entry::Borderless => "Borderless",
+            Entry::Scaling => "Scaling",
+            Entry::MaxFps => "Maximum FPS",
+            Entry::Vsync => "Vertical sync",
+        }
+    }
+
+    fn value(&self, settings: &Settings) -> String {
+        match *self {
+            Entry::ViewDistance => format!("{} tiles", settings.gameplay.view_distance),
+            Entry::VisibilityFalloff => format!("{} tiles", settings.gameplay.visibility_falloff),
+            Entry::TravelStep => format!("{} ms", settings.gameplay.travel_step_millis),
+            Entry::Animations => on_off(settings.gameplay.animations).to_owned(),
+            Entry::WindowSize => {
+                format!("{} x {}", settings.window.width, settings.window.height)
+            }
+            Entry::Fullscreen => on_off(settings.window.fullscreen).to_owned(),
+            Entry::Borderless => on_off(settings.window.borderless).to_owned(),
+            Entry::Scaling => settings.window.scaling.name().to_owned(),
+            Entry::MaxFps => format!("{}", settings.window.max_fps),
+            Entry::Vsync => format!("{} (after restarting)", on_off(settings.window.vsync)),
+        }
+    }
+
+    /// Raises or lowers the setting by one step in the provided direction.
+    fn adjust(&self, settings: &mut Settings, up: bool) {
+        let gameplay = &mut settings.gameplay;
+        let window = &mut settings.window;
+        match *self {
+            Entry::ViewDistance => {
+                gameplay.view_distance = step(gameplay.view_distance, 1, up)
+                    .max(1)
+                    .min(MAX_VIEW_DISTANCE);
+                gameplay.visibility_falloff = gameplay.visibility_falloff.min(
+                    gameplay.view_distance,
+                );
+            }
+            Entry::VisibilityFalloff => {
+                gameplay.visibility_falloff = step(gameplay.visibility_falloff, 1, up)
+                    .min(gameplay.view_distance);
+            }
+            Entry::TravelStep => {
+                gameplay.travel_step_millis =
+                    step(gameplay.travel_step_millis, TRAVEL_STEP_INCREMENT, up)
+                        .min(MAX_TRAVEL_STEP_MILLIS);
+            }
+            Entry::Animations => gameplay.animations = !gameplay.animations,
+            Entry::WindowSize => {
+                let current = [window.width, window.height];
+                let size = cycle(&WINDOW_SIZES, &current, up);
+                window.width = size[0];
+                window.height = size[1];
+            }
+            Entry::Fullscreen => window.fullscreen = !window.fullscreen,
+            Entry::Borderless => window.borderless = !window.borderless,
+            Entry::Scaling => window.scaling = cycle(&SCALING_MODES, &window.scaling, up),
+            Entry::MaxFps => {
+                window.max_fps = step(window.max_fps, FPS_INCREMENT, up).max(MIN_FPS).min(
+                    MAX_FPS,
+                );
+            }
+            Entry::Vsync => window.vsync = !window.vsync,
+        }
+    }
+
+    /// Copies the setting from one set of settings to another.
+    fn copy(&self, from: &Settings, to: &mut Settings) {
+        match *self {
+            Entry::ViewDistance | Entry::VisibilityFalloff => {
+                to.gameplay.view_distance = from.gameplay.view_distance;
+                to.gameplay.visibility_falloff = from.gameplay.visibility_falloff;
+            }
+            Entry::TravelStep => to.gameplay.travel_step_millis = from.gameplay.travel_step_millis,
+            Entry::Animations => to.gameplay.animations = from.gameplay.animations,
+            Entry::WindowSize => {
+                to.window.width = from.window.width;
+                to.window.height = from.window.height;
+            }
+            Entry::Fullscreen => to.window.fullscreen = from.window.fullscreen,
+            Entry::Borderless => to.window.borderless = from.window.borderless,
+            Entry::Scaling => to.window.scaling = from.window.scaling,
+            Entry::MaxFps => to.window.max_fps = from.window.max_fps,
+            Entry::Vsync => to.window.vsync = from.window.vsync,
+        }
+    }
+
+    /// Returns what the window needs to do for the setting to take effect,
+    /// if anything.
+    fn status(&self, settings: &Settings) -> Option<ControllerStatus> {
+        match *self {
+            Entry::WindowSize => Some(ControllerStatus::Resize(
+                settings.window.width,
+                settings.window.height,
+            )),
+            Entry::Fullscreen => {
+                Some(ControllerStatus::SetFullscreen(settings.window.fullscreen))
+            }
+            Entry::Borderless => {
+                Some(ControllerStatus::SetBorderless(settings.window.borderless))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value { "on" } else { "off" }
+}
+
+/// Returns the choice after the current one, or before it, going around
+/// from one end of the choices to the other.  Choices not among them start
+/// from the first.
+fn cycle<T: Copy + PartialEq>(choices: &[T], current: &T, up: bool) -> T {
+    let index = match choices.iter().position(|c| c == current) {
+        Some(index) if up => (index + 1) % choices.len(),
+        Some(index) => (index + choices.len() - 1) % choices.len(),
+        None => 0,
+    };
+    choices[index]
+}
+
+/// Adds or subtracts the increment, without going below zero.
+fn step<T>(value: T, increment: T, up: bool) -> T
+where
+    T: ::std::ops::Add<Output = T> + ::std::ops::Sub<Output = T> + PartialOrd + Default,
+{
+    if up {
+        value + increment
+    } else if value > increment {
+        value - increment
+    } else {
+        T::default()
+    }
+}
+
+/// Opens and closes the options menu, and changes the settings as the player
+/// moves through it.  Changes are saved to the settings file when the menu
+/// is closed, or straight away when made with a key outside of the menu.
+pub struct OptionsController {
+    /// The settings as they are in the settings file.
+    saved: Settings,
+
+    /// The settings in effect, which may have been changed on the command
+    /// line without being saved.
+    current: Settings,
+
+    selected: usize,
+    open: bool,
+    unsaved: bool,
+    unapplied: bool,
+    statuses: VecDeque<ControllerStatus>,
+}
+
+impl OptionsController {
+    /// Creates and returns a new instance of the OptionsController struct,
+    /// given the settings from the settings file and the settings in effect.
+    pub fn new(saved: Settings, current: Settings) -> OptionsController {
+        OptionsController {
+            saved: saved,
+            current: current,
+            selected: 0,
+            open: false,
+            unsaved: false,
+            unapplied: false,
+            statuses: VecDeque::new(),
+        }
+    }
+
+    /// Returns the settings in effect.
+    pub fn settings(&self) -> &Settings {
+        &self.current
+    }
+
+    /// Returns a value indicating whether the options menu is open.
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Returns the label and value of each setting in the menu.
+    pub fn entries(&self) -> Vec<(&'static str, String)> {
+        ENTRIES
+            .iter()
+            .map(|entry| (entry.label(), entry.value(&self.current)))
+            .collect()
+    }
+
+    /// Returns the index of the selected setting in the menu.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Returns the next thing that the window needs to do for a changed
+    /// setting to take effect, if there is anything.
+    pub fn get_status(&mut self) -> Option<ControllerStatus> {
+        self.statuses.pop_front()
+    }
+
+    /// Returns the settings in effect if they have changed since the last
+    /// time this was called, so that they can be applied to the game.
+    pub fn take_changes(&mut self) -> Option<&Settings> {
+        if self.unapplied {
+            self.unapplied = false;
+            Some(&self.current)
+        } else {
+            None
+        }
+    }
+
+    /// Opens or closes the menu and moves through it based on the provided
+    /// controls.  Returns the controls that were not used up by the menu,
+    /// which is all of them while it is closed, and none of them while it
+    /// is open.
+    pub fn update(&mut self, controls: &[Control]) -> Vec<Control> {
+        if controls.contains(&Control::ToggleFullscreen) {
+            self.change(Entry::Fullscreen, true);
+            if !self.open {
+                self.save();
+            }
+        }
+
+        let toggled = controls.contains(&Control::Options);
+        if !self.open {
+            if toggled {
+                self.open = true;
+                self.selected = 0;
+            }
+            return controls
+                .iter()
+                .cloned()
+                .filter(|c| *c != Control::Options && *c != Control::ToggleFullscreen)
+                .collect();
+        }
+
+        for control in controls {
+            match *control {
+                Control::Options | Control::Cancel => {
+                    self.open = false;
+                    self.save();
+                }
+                Control::MoveUp => {
+                    self.selected = (self.selected + ENTRIES.len() - 1) % ENTRIES.len();
+                }
+                Control::MoveDown => self.selected = (self.selected + 1) % ENTRIES.len(),
+                Control::MoveLeft => self.adjust(false),
+                Control::MoveRight | Control::Confirm => self.adjust(true),
+                _ => {}
+            }
+        }
+        Vec::new()
+    }
+
+    fn adjust(&mut self, up: bool) {
+        let entry = ENTRIES[self.selected];
+        self.change(entry, up);
+    }
+
+    fn change(&mut self, entry: Entry, up: bool) {
+        entry.adjust(&mut self.current, up);
+        entry.copy(&self.current, &mut self.saved);
+        if let Some(status) = entry.status(&self.current) {
+            self.statuses.push_back(status);
+        }
+        self.unsaved = true;
+        self.unapplied = true;
+    }
+
+    fn save(&mut self) {
+        if self.unsaved {
+            match self.saved.save() {
+                Ok(_) => info!("Saved the settings."),
+                Err(why) => error!("{}", why),
+            }
+            self.unsaved = false;
+        }
+    }
+}
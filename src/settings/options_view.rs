@@ -0,0 +1,62 @@
+use render::Renderer;
+use text_renderer::FontSize;
+use super::OptionsController;
+
+const PANEL_WIDTH: f64 = 420.0;
+const PANEL_PAD: f64 = 12.0;
+const VALUE_OFFSET: f64 = 220.0;
+const PANEL_COLOR: [f32; 4] = [0.05, 0.05, 0.1, 0.95];
+const SELECTED_COLOR: [f32; 4] = [0.4, 0.8, 1.0, 0.25];
+const TITLE_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const TEXT_COLOR: [f32; 4] = [0.9, 0.9, 0.9, 1.0];
+const HINT_COLOR: [f32; 4] = [0.6, 0.6, 0.6, 1.0];
+const HINT: &'static str = "Up/down: choose   Left/right: change   O: close";
+
+/// Draws the options menu over the middle of the screen while it is open.
+pub struct OptionsView;
+
+impl OptionsView {
+    /// Creates and returns a new instance of the OptionsView struct.
+    pub fn new() -> OptionsView {
+        OptionsView
+    }
+
+    /// Draws the menu described by the provided controller with the provided
+    /// renderer, if the menu is open.
+    pub fn draw(&mut self, controller: &OptionsController, r: &mut Renderer) {
+        if !controller.is_open() {
+            return;
+        }
+
+        let screen_size = r.size();
+        let entries = controller.entries();
+        let title_height = r.line_height(FontSize::Size24);
+        let line_height = r.line_height(FontSize::Size18);
+
+        // the title, then a line for each setting, then a line of hints
+        let panel_h = title_height + line_height * (entries.len() + 2) as f64 + PANEL_PAD * 2.0;
+        let panel_x = ((screen_size[0] - PANEL_WIDTH) / 2.0).max(0.0);
+        let panel_y = ((screen_size[1] - panel_h) / 2.0).max(0.0);
+        r.draw_rect([panel_x, panel_y, PANEL_WIDTH, panel_h], PANEL_COLOR);
+
+        let left = panel_x + PANEL_PAD;
+        let mut y = panel_y + PANEL_PAD;
+        r.draw_text("Options", [left, y], FontSize::Size24, TITLE_COLOR);
+        y += title_height;
+
+        for (index, &(label, ref value)) in entries.iter().enumerate() {
+            if index == controller.selected() {
+                r.draw_rect(
+                    [panel_x, y, PANEL_WIDTH, line_height],
+                    SELECTED_COLOR,
+                );
+            }
+            r.draw_text(label, [left, y], FontSize::Size18, TEXT_COLOR);
+            r.draw_text(value.as_str(), [left + VALUE_OFFSET, y], FontSize::Size18, TEXT_COLOR);
+            y += line_height;
+        }
+
+        y += line_height;
+        r.draw_text(HINT, [left, y], FontSize::Size18, HINT_COLOR);
+    }
+}
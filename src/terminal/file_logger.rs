@@ -0,0 +1,51 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use log::{self, LevelFilter, Log, Metadata, Record};
+
+/// Writes log messages to a file, since anything written to the terminal
+/// while the game is drawn there would end up on top of it.
+struct FileLogger {
+    file: Mutex<BufWriter<File>>,
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        if let Ok(mut file) = self.file.lock() {
+            // there is nowhere left to report a failure to log
+            let _ = writeln!(
+                file,
+                "{:<5} [{}] {}",
+                record.level(),
+                record.module_path().unwrap_or(""),
+                record.args()
+            );
+            let _ = file.flush();
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// Sends every log message to the file at the provided path, replacing
+/// whatever it held before.  This can only be done once, before anything
+/// else has set up logging.
+pub fn init(path: &Path) -> Result<(), String> {
+    let file = File::create(path).map_err(|why| {
+        format!("Unable to create log file {:?}: {:?}", path, why)
+    })?;
+    let logger: &'static FileLogger =
+        Box::leak(Box::new(FileLogger { file: Mutex::new(BufWriter::new(file)) }));
+    log::set_logger(logger).map_err(|why| format!("Unable to set up logging: {}", why))?;
+    log::set_max_level(LevelFilter::Trace);
+    Ok(())
+}
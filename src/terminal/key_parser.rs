@@ -0,0 +1,255 @@
+use piston::input::Key;
+use input::{key_from_name, Modifiers};
+
+/// A key pressed in the terminal, along with the modifiers held with it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyPress {
+    /// The key that was pressed.
+    pub key: Key,
+
+    /// The modifiers held when the key was pressed.
+    pub modifiers: Modifiers,
+}
+
+impl KeyPress {
+    fn new(key: Key) -> KeyPress {
+        KeyPress {
+            key: key,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    fn with_shift(key: Key) -> KeyPress {
+        let mut press = KeyPress::new(key);
+        press.modifiers.shift = true;
+        press
+    }
+}
+
+/// Turns the bytes read from a terminal in raw mode into key presses.
+////
+/// Terminals can't tell the number pad apart from the number row, so digits
+/// are reported as number pad keys, letting the default bindings work.
+pub fn parse(bytes: &[u8]) -> Vec<KeyPress> {
+    let mut presses = Vec::<KeyPress>::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        let (press, length) = match bytes[index] {
+            0x1b => parse_escape(&bytes[index..]),
+            byte => (parse_byte(byte), 1),
+        };
+        if let Some(press) = press {
+            presses.push(press);
+        }
+        index += length;
+    }
+    presses
+}
+
+/// Parses a sequence beginning with an escape character, returning the key
+/// press it stands for and the number of bytes that it used up.
+fn parse_escape(bytes: &[u8]) -> (Option<KeyPress>, usize) {
+    match bytes.get(1) {
+        None => (Some(KeyPress::new(Key::Escape)), 1),
+        Some(&b'[') | Some(&b'O') => {
+            // control sequences end with a byte from '@' to '~'
+            match bytes[2..].iter().position(|b| *b >= 0x40 && *b <= 0x7e) {
+                Some(end) => {
+                    let length = end + 3;
+                    (parse_sequence(&bytes[2..length]), length)
+                }
+                None => (None, bytes.len()),
+            }
+        }
+        Some(&byte) => {
+            // escape followed by a key means that alt was held
+            let press = parse_byte(byte).map(|mut press| {
+                press.modifiers.alt = true;
+                press
+            });
+            (press, 2)
+        }
+    }
+}
+
+/// Parses the parameters and final byte of a control sequence, such as
+/// "1;2A" for shift and the up arrow.
+fn parse_sequence(sequence: &[u8]) -> Option<KeyPress> {
+    let (last, parameters) = match sequence.split_last() {
+        Some((last, parameters)) => (*last, String::from_utf8_lossy(parameters).into_owned()),
+        None => return None,
+    };
+    let numbers: Vec<u32> = parameters
+        .split(';')
+        .map(|n| n.parse().unwrap_or(1))
+        .collect();
+
+    let key = match last {
+        b'A' => Key::Up,
+        b'B' => Key::Down,
+        b'C' => Key::Right,
+        b'D' => Key::Left,
+        b'H' => Key::Home,
+        b'F' => Key::End,
+        b'P' => Key::F1,
+        b'Q' => Key::F2,
+        b'R' => Key::F3,
+        b'S' => Key::F4,
+        b'~' => {
+            match numbers.get(0).cloned().unwrap_or(0) {
+                1 | 7 => Key::Home,
+                2 => Key::Insert,
+                3 => Key::Delete,
+                4 | 8 => Key::End,
+                5 => Key::PageUp,
+                6 => Key::PageDown,
+                15 => Key::F5,
+                17 => Key::F6,
+                18 => Key::F7,
+                19 => Key::F8,
+                20 => Key::F9,
+                21 => Key::F10,
+                23 => Key::F11,
+                24 => Key::F12,
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+
+    // the second parameter, less one, holds the modifiers as bit flags
+    let flags = numbers.get(1).cloned().unwrap_or(1).saturating_sub(1);
+    let mut press = KeyPress::new(key);
+    press.modifiers.shift = flags & 1 != 0;
+    press.modifiers.alt = flags & 2 != 0;
+    press.modifiers.ctrl = flags & 4 != 0;
+    Some(press)
+}
+
+/// Parses a single byte standing for a key on its own.
+fn parse_byte(byte: u8) -> Option<KeyPress> {
+    let press = match byte {
+        b'\t' => KeyPress::new(Key::Tab),
+        b'\r' | b'\n' => KeyPress::new(Key::Return),
+        0x08 | 0x7f => KeyPress::new(Key::Backspace),
+        0x01..=0x1a => {
+            // control and a letter
+            let mut press = letter(byte - 0x01 + b'a')?;
+            press.modifiers.ctrl = true;
+            press
+        }
+        b'a'..=b'z' => letter(byte)?,
+        b'A'..=b'Z' => {
+            let mut press = letter(byte.to_ascii_lowercase())?;
+            press.modifiers.shift = true;
+            press
+        }
+        b'0'..=b'9' => {
+            let name = format!("NumPad{}", byte as char);
+            KeyPress::new(key_from_name(name.as_str())?)
+        }
+        b' ' => KeyPress::new(Key::Space),
+        b'.' => KeyPress::new(Key::Period),
+        b'>' => KeyPress::with_shift(Key::Period),
+        b',' => KeyPress::new(Key::Comma),
+        b'<' => KeyPress::with_shift(Key::Comma),
+        b';' => KeyPress::new(Key::Semicolon),
+        b':' => KeyPress::with_shift(Key::Semicolon),
+        b'=' => KeyPress::new(Key::Equals),
+        b'+' => KeyPress::with_shift(Key::Equals),
+        b'-' => KeyPress::new(Key::Minus),
+        b'/' => KeyPress::new(Key::Slash),
+        b'?' => KeyPress::with_shift(Key::Slash),
+        _ => return None,
+    };
+    Some(press)
+}
+
+/// Returns the key for a lowercase letter.
+fn letter(byte: u8) -> Option<KeyPress> {
+    let name = (byte as char).to_ascii_uppercase().to_string();
+    key_from_name(name.as_str()).map(KeyPress::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(key: Key, shift: bool, ctrl: bool, alt: bool) -> KeyPress {
+        KeyPress {
+            key: key,
+            modifiers: Modifiers {
+                shift: shift,
+                ctrl: ctrl,
+                alt: alt,
+            },
+        }
+    }
+
+    fn plain(key: Key) -> KeyPress {
+        press(key, false, false, false)
+    }
+
+    #[test]
+    fn letters_carry_shift_and_ctrl() {
+        assert_eq!(parse(b"k"), vec![plain(Key::K)]);
+        assert_eq!(parse(b"K"), vec![press(Key::K, true, false, false)]);
+        assert_eq!(parse(&[0x03]), vec![press(Key::C, false, true, false)]);
+    }
+
+    #[test]
+    fn control_characters_with_names_of_their_own() {
+        assert_eq!(parse(b"\t"), vec![plain(Key::Tab)]);
+        assert_eq!(parse(b"\r"), vec![plain(Key::Return)]);
+        assert_eq!(parse(&[0x7f]), vec![plain(Key::Backspace)]);
+    }
+
+    #[test]
+    fn digits_are_the_number_pad() {
+        assert_eq!(parse(b"85"), vec![plain(Key::NumPad8), plain(Key::NumPad5)]);
+    }
+
+    #[test]
+    fn punctuation_carries_shift() {
+        assert_eq!(parse(b"."), vec![plain(Key::Period)]);
+        assert_eq!(parse(b">"), vec![press(Key::Period, true, false, false)]);
+        assert_eq!(parse(b"+"), vec![press(Key::Equals, true, false, false)]);
+    }
+
+    #[test]
+    fn escape_on_its_own() {
+        assert_eq!(parse(b"\x1b"), vec![plain(Key::Escape)]);
+    }
+
+    #[test]
+    fn escape_before_a_key_means_alt() {
+        assert_eq!(parse(b"\x1bk"), vec![press(Key::K, false, false, true)]);
+    }
+
+    #[test]
+    fn control_sequences() {
+        assert_eq!(parse(b"\x1b[A"), vec![plain(Key::Up)]);
+        assert_eq!(parse(b"\x1bOD"), vec![plain(Key::Left)]);
+        assert_eq!(parse(b"\x1b[5~"), vec![plain(Key::PageUp)]);
+        assert_eq!(parse(b"\x1b[24~"), vec![plain(Key::F12)]);
+        assert_eq!(parse(b"\x1b[1;2A"), vec![press(Key::Up, true, false, false)]);
+        assert_eq!(parse(b"\x1b[1;5C"), vec![press(Key::Right, false, true, false)]);
+        assert_eq!(parse(b"\x1b[3;4~"), vec![press(Key::Delete, true, false, true)]);
+    }
+
+    #[test]
+    fn unknown_sequences_are_skipped() {
+        assert_eq!(parse(b"\x1b[99~k"), vec![plain(Key::K)]);
+        assert_eq!(parse(b"\x1b[Zk"), vec![plain(Key::K)]);
+        assert!(parse(b"\x1b[1;2").is_empty());
+        assert!(parse(&[0x80, 0xff]).is_empty());
+    }
+
+    #[test]
+    fn several_keys_at_once() {
+        assert_eq!(
+            parse(b"j\x1b[Bk"),
+            vec![plain(Key::J), plain(Key::Down), plain(Key::K)]
+        );
+    }
+}
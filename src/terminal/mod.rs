@@ -0,0 +1,238 @@
+#![deny(missing_docs)]
+
+//! Plays the game in a terminal instead of a window, drawing with colored
+//! characters and reading the keyboard through the terminal itself.
+
+mod file_logger;
+mod key_parser;
+
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+use piston::input::Key;
+use game::{GameController, GameView, ReplayPlayer};
+use input::{Control, InputMapper};
+use render::{Renderer, TerminalRenderer};
+use settings::{OptionsController, OptionsView};
+use status::ControllerStatus;
+
+/// The time between each frame drawn to the terminal.
+const FRAME_MILLIS: u64 = 33;
+
+/// The time between each check of the terminal's size.
+const RESIZE_CHECK_MILLIS: u64 = 1000;
+
+/// The size assumed for the terminal if it can't be found.
+const DEFAULT_SIZE: [usize; 2] = [112, 32];
+
+/// Where log messages are written while playing in the terminal.
+pub const LOG_FILE: &'static str = "terminal.log";
+
+/// Enters the alternate screen and hides the cursor.
+const ENTER_SCREEN: &'static str = "\x1b[?1049h\x1b[?25l";
+
+/// Shows the cursor and leaves the alternate screen.
+const LEAVE_SCREEN: &'static str = "\x1b[0m\x1b[?25h\x1b[?1049l";
+
+/// Puts the terminal into raw mode for as long as it is held, so that keys
+/// are read as soon as they are pressed and aren't echoed.
+struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    fn enable() -> Result<RawMode, String> {
+        let saved = stty(&["-g"])?;
+        stty(&["raw", "-echo"])?;
+        Ok(RawMode { saved: saved.trim().to_owned() })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        if let Err(why) = stty(&[self.saved.as_str()]) {
+            error!("Unable to restore the terminal: {}", why);
+        }
+    }
+}
+
+/// Runs `stty` on the terminal with the provided arguments, returning
+/// whatever it printed.
+fn stty(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()
+        .map_err(|why| format!("Unable to run stty: {:?}", why))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(format!(
+            "stty failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Returns the number of columns and rows in the terminal.
+fn terminal_size() -> [usize; 2] {
+    let size = stty(&["size"]).ok().and_then(|size| {
+        let numbers: Vec<usize> = size.split_whitespace()
+            .filter_map(|n| n.parse().ok())
+            .collect();
+        match numbers.as_slice() {
+            &[rows, columns] if rows > 0 && columns > 0 => Some([columns, rows]),
+            _ => None,
+        }
+    });
+    size.unwrap_or(DEFAULT_SIZE)
+}
+
+/// Reads from the terminal on another thread, so that the game carries on
+/// while waiting for keys.
+fn spawn_reader() -> Receiver<Vec<u8>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let stdin = io::stdin();
+        let mut buffer = [0u8; 64];
+        loop {
+            match stdin.lock().read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(count) => {
+                    if sender.send(buffer[..count].to_vec()).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+    receiver
+}
+
+/// Sends log messages to the terminal log file instead of the terminal
+/// itself, where they would be drawn over the game.  Must be called before
+/// any other logging is set up.
+pub fn init_logging() -> Result<(), String> {
+    file_logger::init(Path::new(LOG_FILE))
+}
+
+/// Plays the game in the terminal until the player quits, either by
+/// pressing escape or control-C, or by dying.  A replay player, if provided,
+/// plays back its replay as it would in the window, and the options menu
+/// works as it does there too.
+pub fn run(
+    mut controller: GameController,
+    mut replay_player: Option<ReplayPlayer>,
+    mut options: OptionsController,
+) {
+    let _raw_mode = match RawMode::enable() {
+        Ok(raw_mode) => raw_mode,
+        Err(why) => {
+            error!("{}  Is the game running in a terminal?", why);
+            return;
+        }
+    };
+    let mut out = io::stdout();
+    if let Err(why) = out.write_all(ENTER_SCREEN.as_bytes()) {
+        error!("Unable to write to the terminal: {:?}", why);
+        return;
+    }
+
+    let mut input = InputMapper::new();
+    let mut view = GameView::new();
+    let mut options_view = OptionsView::new();
+    let size = terminal_size();
+    let mut renderer = TerminalRenderer::new(size[0], size[1]);
+    let mut screen_size = renderer.size();
+    controller.set_window_size(screen_size[0] as u32, screen_size[1] as u32);
+    let mut last_resize_check = Instant::now();
+    let keys = spawn_reader();
+
+    'game: loop {
+        let frame_start = Instant::now();
+
+        // handle every key pressed since the last frame
+        loop {
+            let bytes = match keys.try_recv() {
+                Ok(bytes) => bytes,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => break 'game,
+            };
+            for press in key_parser::parse(&bytes) {
+                // control-C always quits, whatever it is bound to
+                if press.key == Key::C && press.modifiers.ctrl {
+                    break 'game;
+                }
+
+                // escape backs out of the menu, aiming, and looking around before
+                // it quits, and only quits if the settings say it should
+                let mut controls = input.controls_for(press.key, press.modifiers);
+                if press.key == Key::Escape && controls.is_empty() {
+                    if options.is_open() || controller.is_using_cursor() {
+                        controls.push(Control::Cancel);
+                    } else if options.settings().window.exit_on_esc {
+                        break 'game;
+                    }
+                }
+
+                let controls = options.update(&controls);
+                if options.is_open() {
+                    continue;
+                }
+                if let Some(ref mut replay_player) = replay_player {
+                    replay_player.control(&controls);
+                }
+                controller.update_without_window(Some(&controls));
+            }
+        }
+
+        if let Some(changes) = options.take_changes() {
+            controller.set_gameplay_options(changes.gameplay.clone());
+        }
+
+        // the terminal has no window to resize or make fullscreen
+        while options.get_status().is_some() {}
+
+        // the game is paused while the options menu is open
+        if !options.is_open() {
+            if let Some(ref mut replay_player) = replay_player {
+                replay_player.play_due(&mut controller);
+            }
+            controller.update_without_window(None);
+        }
+        if let Some(ControllerStatus::Quit) = controller.get_status() {
+            info!("Received Quit status from game_controller, quitting now...");
+            break;
+        }
+
+        if last_resize_check.elapsed() >= Duration::from_millis(RESIZE_CHECK_MILLIS) {
+            let size = terminal_size();
+            renderer.resize(size[0], size[1]);
+            last_resize_check = Instant::now();
+            if renderer.size() != screen_size {
+                screen_size = renderer.size();
+                controller.set_window_size(screen_size[0] as u32, screen_size[1] as u32);
+            }
+        }
+        renderer.clear([0.0; 4]);
+        view.draw(&controller, &mut renderer);
+        options_view.draw(&options, &mut renderer);
+        if let Err(why) = renderer.present(&mut out) {
+            error!("Unable to write to the terminal: {:?}", why);
+            break;
+        }
+
+        let frame = Duration::from_millis(FRAME_MILLIS);
+        let elapsed = frame_start.elapsed();
+        if elapsed < frame {
+            thread::sleep(frame - elapsed);
+        }
+    }
+
+    if let Err(why) = out.write_all(LEAVE_SCREEN.as_bytes()).and_then(|_| out.flush()) {
+        error!("Unable to write to the terminal: {:?}", why);
+    }
+}
@@ -1,10 +1,9 @@
 use std::fs::File;
-use std::path::PathBuf;
 use opengl_graphics::{GlGraphics, Texture};
 use graphics::image::Image;
 use graphics::math::Matrix2d;
 use graphics::DrawState;
-use image::{self, DynamicImage};
+use image::{self, DynamicImage, RgbaImage};
 use find_folder::Search;
 use texture::{Filter, TextureSettings};
 use bmfont::{OrdinateOrientation, BMFont};
@@ -15,6 +14,7 @@ const FONT_24: &'static str = "24.fnt";
 const FONT_18: &'static str = "18.fnt";
 
 /// Represents available predefined font sizes
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FontSize {
     Size32,
     Size24,
@@ -36,40 +36,15 @@ pub struct TextRenderer {
 impl TextRenderer {
     /// Creates and returns a new instance of the TextRenderer struct.
     pub fn new() -> TextRenderer {
-        let assets = Search::ParentsThenKids(3, 3).for_folder(FONTS_DIR).unwrap();
-        let font_32 = BMFont::new(
-            File::open(assets.join(FONT_32)).expect(
-                format!(
-                    "Unable to open {:?}",
-                    FONT_32
-                ).as_str(),
-            ),
-            OrdinateOrientation::TopToBottom,
-        ).expect("Unable to load size-32 font");
-        let font_24 = BMFont::new(
-            File::open(assets.join(FONT_24)).expect(
-                format!(
-                    "Unable to open {:?}",
-                    FONT_24
-                ).as_str(),
-            ),
-            OrdinateOrientation::TopToBottom,
-        ).expect("Unable to load size-24 font");
-        let font_18 = BMFont::new(
-            File::open(assets.join(FONT_18)).expect(
-                format!(
-                    "Unable to open {:?}",
-                    FONT_18
-                ).as_str(),
-            ),
-            OrdinateOrientation::TopToBottom,
-        ).expect("Unable to load size-18 font");
+        let (font_32, page_32) = load_font(FontSize::Size32);
+        let (font_24, page_24) = load_font(FontSize::Size24);
+        let (font_18, page_18) = load_font(FontSize::Size18);
         TextRenderer {
-            glyphs_32: TextRenderer::load_texture(assets.join(&font_32.pages()[0])),
+            glyphs_32: TextRenderer::load_texture(&page_32),
             font_32: font_32,
-            glyphs_24: TextRenderer::load_texture(assets.join(&font_24.pages()[0])),
+            glyphs_24: TextRenderer::load_texture(&page_24),
             font_24: font_24,
-            glyphs_18: TextRenderer::load_texture(assets.join(&font_18.pages()[0])),
+            glyphs_18: TextRenderer::load_texture(&page_18),
             font_18: font_18,
             draw_state: DrawState::default(),
         }
@@ -79,7 +54,7 @@ impl TextRenderer {
     pub fn draw_at(
         &self,
         position: [f64; 2],
-        text: String,
+        text: &str,
         transform: Matrix2d,
         size: FontSize,
         color: [f32; 4],
@@ -91,7 +66,7 @@ impl TextRenderer {
             FontSize::Size18 => (&self.font_18, &self.glyphs_18),
         };
 
-        if let Ok(map) = font.parse(text.as_str()) {
+        if let Ok(map) = font.parse(text) {
             for char_info in &map {
                 Image::new_color(color)
                     .src_rect(
@@ -124,17 +99,34 @@ impl TextRenderer {
         }
     }
 
-    fn load_texture(path: PathBuf) -> Texture {
-        let img = match image::open(path) {
-            Ok(img) => img,
-            Err(e) => panic!("Could not load texture: {:?}", e),
-        };
+    fn load_texture(page: &RgbaImage) -> Texture {
+        Texture::from_image(page, &TextureSettings::new().filter(Filter::Nearest))
+    }
+}
 
-        let img = match img {
-            DynamicImage::ImageRgba8(img) => img,
-            x => x.to_rgba(),
-        };
+/// Loads the bitmap font of the provided size, along with the image holding
+/// its glyphs.
+pub fn load_font(size: FontSize) -> (BMFont, RgbaImage) {
+    let file_name = match size {
+        FontSize::Size32 => FONT_32,
+        FontSize::Size24 => FONT_24,
+        FontSize::Size18 => FONT_18,
+    };
+    let assets = Search::ParentsThenKids(3, 3).for_folder(FONTS_DIR).unwrap();
+    let font = BMFont::new(
+        File::open(assets.join(file_name)).expect(
+            format!("Unable to open {:?}", file_name).as_str(),
+        ),
+        OrdinateOrientation::TopToBottom,
+    ).expect(format!("Unable to load font {:?}", file_name).as_str());
 
-        Texture::from_image(&img, &TextureSettings::new().filter(Filter::Nearest))
-    }
+    let img = match image::open(assets.join(&font.pages()[0])) {
+        Ok(img) => img,
+        Err(e) => panic!("Could not load texture: {:?}", e),
+    };
+    let page = match img {
+        DynamicImage::ImageRgba8(img) => img,
+        x => x.to_rgba(),
+    };
+    (font, page)
 }
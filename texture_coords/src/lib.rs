@@ -3,7 +3,15 @@ extern crate serde;
 
 use std::collections::HashMap;
 
+#[derive(Serialize, Deserialize)]
+pub struct Animation {
+    pub frames: Vec<String>,
+    pub frame_millis: u64,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct CoordinateMap {
     pub map: HashMap<String, [f64; 4]>,
+    #[serde(default)]
+    pub animations: HashMap<String, Animation>,
 }